@@ -0,0 +1,553 @@
+//! Archives browsed as virtual directories
+//!
+//! Zip, tar (plain, gzip, xz and zstd compressed) and 7z archives are read
+//! with native readers. A listing is read once when the archive is opened and
+//! kept sorted by member path, with directories that are only implied by
+//! their contents filled in. Member data is streamed with `for_each_entry`,
+//! which visits every member in archive order.
+
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{self, BufReader, Read},
+    path::{Path, PathBuf},
+    sync::{
+        Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+use crate::util;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ArchiveFormat {
+    Tar,
+    TarGz,
+    TarXz,
+    TarZst,
+    Zip,
+    SevenZ,
+}
+
+impl ArchiveFormat {
+    /// Format of an archive, judged by its file name
+    pub fn detect(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_string_lossy().to_lowercase();
+        let format = if name.ends_with(".tar") {
+            Self::Tar
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Self::TarGz
+        } else if name.ends_with(".tar.xz") || name.ends_with(".txz") {
+            Self::TarXz
+        } else if name.ends_with(".tar.zst") || name.ends_with(".tzst") {
+            Self::TarZst
+        } else if name.ends_with(".zip") || name.ends_with(".jar") {
+            Self::Zip
+        } else if name.ends_with(".7z") {
+            Self::SevenZ
+        } else {
+            return None;
+        };
+        Some(format)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum EntryKind {
+    File,
+    Dir,
+    /// Symbolic link with its target (empty if the listing does not say)
+    Symlink(String),
+    /// Hard link to an earlier member
+    Hardlink(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ArchiveEntry {
+    /// Path inside the archive, `/`-separated, without a leading `/` or `./`
+    pub path: String,
+    pub kind: EntryKind,
+    pub size: u64,
+    /// Unix permission bits, if the archive records them
+    pub mode: Option<u32>,
+}
+
+impl ArchiveEntry {
+    fn dir(path: String) -> Self {
+        Self {
+            path,
+            kind: EntryKind::Dir,
+            size: 0,
+            mode: None,
+        }
+    }
+
+    pub fn is_dir(&self) -> bool {
+        self.kind == EntryKind::Dir
+    }
+
+    /// Last component of the path
+    pub fn name(&self) -> &str {
+        self.path.rsplit('/').next().unwrap_or(&self.path)
+    }
+}
+
+/// Clean up a member name: drop empty and `.` components and any leading
+/// `/`. Returns None for names with `..` components, which could point
+/// outside the archive root, and for names that end up empty.
+pub fn normalize_name(name: &str) -> Option<String> {
+    let mut parts = Vec::new();
+    for part in name.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => return None,
+            _ => parts.push(part),
+        }
+    }
+    (!parts.is_empty()).then(|| parts.join("/"))
+}
+
+/// Directory part of a member path ("" for top-level members)
+fn parent_of(path: &str) -> &str {
+    path.rsplit_once('/').map_or("", |(parent, _)| parent)
+}
+
+fn other_error(e: impl ToString) -> io::Error {
+    io::Error::other(e.to_string())
+}
+
+fn tar_entries(
+    reader: impl Read,
+    mut f: impl FnMut(ArchiveEntry, &mut dyn Read) -> io::Result<bool>,
+) -> io::Result<()> {
+    let mut archive = tar::Archive::new(reader);
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let Some(path) = normalize_name(&String::from_utf8_lossy(&entry.path_bytes())) else {
+            continue;
+        };
+        let header = entry.header();
+        let link = || {
+            entry
+                .link_name_bytes()
+                .map(|name| String::from_utf8_lossy(&name).into_owned())
+                .unwrap_or_default()
+        };
+        let kind = match header.entry_type() {
+            tar::EntryType::Directory => EntryKind::Dir,
+            tar::EntryType::Symlink => EntryKind::Symlink(link()),
+            tar::EntryType::Link => EntryKind::Hardlink(link()),
+            tar::EntryType::Regular | tar::EntryType::Continuous | tar::EntryType::GNUSparse => {
+                EntryKind::File
+            }
+            // Devices, fifos and extension headers
+            _ => continue,
+        };
+        let member = ArchiveEntry {
+            path,
+            size: if kind == EntryKind::File {
+                entry.size()
+            } else {
+                0
+            },
+            mode: header.mode().ok().map(|m| m & 0o7777),
+            kind,
+        };
+        if !f(member, &mut entry)? {
+            break;
+        }
+    }
+    Ok(())
+}
+
+fn zip_entries(
+    path: &Path,
+    mut f: impl FnMut(ArchiveEntry, &mut dyn Read) -> io::Result<bool>,
+) -> io::Result<()> {
+    let mut zip = zip::ZipArchive::new(BufReader::new(File::open(path)?))?;
+    for i in 0..zip.len() {
+        let mut file = zip.by_index(i)?;
+        let Some(name) = normalize_name(file.name()) else {
+            continue;
+        };
+        let kind = if file.is_dir() {
+            EntryKind::Dir
+        } else if file.is_symlink() {
+            // The link target is stored as the member's data
+            let mut target = String::new();
+            file.read_to_string(&mut target)?;
+            EntryKind::Symlink(target)
+        } else {
+            EntryKind::File
+        };
+        let member = ArchiveEntry {
+            path: name,
+            size: if kind == EntryKind::File {
+                file.size()
+            } else {
+                0
+            },
+            mode: file.unix_mode().map(|m| m & 0o7777),
+            kind,
+        };
+        if !f(member, &mut file)? {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Unix mode stored in the high bits of a 7z member's attributes
+fn sevenz_mode(entry: &sevenz_rust::SevenZArchiveEntry) -> Option<u32> {
+    const UNIX_EXTENSION: u32 = 0x8000;
+    (entry.has_windows_attributes && entry.windows_attributes & UNIX_EXTENSION != 0)
+        .then_some(entry.windows_attributes >> 16)
+}
+
+fn sevenz_member(entry: &sevenz_rust::SevenZArchiveEntry) -> Option<ArchiveEntry> {
+    let path = normalize_name(entry.name())?;
+    let mode = sevenz_mode(entry);
+    let kind = if entry.is_directory() {
+        EntryKind::Dir
+    } else if mode.is_some_and(|m| m & libc::S_IFMT == libc::S_IFLNK) {
+        EntryKind::Symlink(String::new())
+    } else {
+        EntryKind::File
+    };
+    Some(ArchiveEntry {
+        path,
+        size: if kind == EntryKind::File {
+            entry.size()
+        } else {
+            0
+        },
+        mode: mode.map(|m| m & 0o7777),
+        kind,
+    })
+}
+
+fn sevenz_entries(
+    path: &Path,
+    mut f: impl FnMut(ArchiveEntry, &mut dyn Read) -> io::Result<bool>,
+) -> io::Result<()> {
+    let mut reader = sevenz_rust::SevenZReader::open(path, sevenz_rust::Password::empty())
+        .map_err(other_error)?;
+    // The callback's errors can't pass through the reader, so park them here
+    let mut failure = None;
+    let result = reader.for_each_entries(|entry, data| {
+        let Some(mut member) = sevenz_member(entry) else {
+            return Ok(true);
+        };
+        if let EntryKind::Symlink(target) = &mut member.kind
+            && let Err(e) = data.read_to_string(target)
+        {
+            failure = Some(e);
+            return Ok(false);
+        }
+        match f(member, data) {
+            Ok(go_on) => Ok(go_on),
+            Err(e) => {
+                failure = Some(e);
+                Ok(false)
+            }
+        }
+    });
+    match failure {
+        Some(e) => Err(e),
+        None => result.map_err(other_error),
+    }
+}
+
+/// Visit every member of the archive in archive order with a reader for its
+/// data. The callback returns false to stop early.
+pub fn for_each_entry(
+    path: &Path,
+    f: impl FnMut(ArchiveEntry, &mut dyn Read) -> io::Result<bool>,
+) -> io::Result<()> {
+    let format = ArchiveFormat::detect(path)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::Unsupported, "Unknown archive format"))?;
+    let open = || File::open(path).map(BufReader::new);
+    match format {
+        ArchiveFormat::Tar => tar_entries(open()?, f),
+        ArchiveFormat::TarGz => tar_entries(flate2::read::MultiGzDecoder::new(open()?), f),
+        ArchiveFormat::TarXz => tar_entries(xz2::read::XzDecoder::new_multi_decoder(open()?), f),
+        ArchiveFormat::TarZst => tar_entries(zstd::stream::read::Decoder::new(open()?)?, f),
+        ArchiveFormat::Zip => zip_entries(path, f),
+        ArchiveFormat::SevenZ => sevenz_entries(path, f),
+    }
+}
+
+/// All members of an archive, keyed by path
+pub struct ArchiveListing {
+    entries: BTreeMap<String, ArchiveEntry>,
+}
+
+impl ArchiveListing {
+    pub fn read(path: &Path) -> Result<Self, String> {
+        let mut listing = Self {
+            entries: BTreeMap::new(),
+        };
+        let result = if ArchiveFormat::detect(path) == Some(ArchiveFormat::SevenZ) {
+            // 7z keeps its listing in the header; no need to decompress
+            sevenz_rust::SevenZReader::open(path, sevenz_rust::Password::empty())
+                .map(|reader| {
+                    for member in reader.archive().files.iter().filter_map(sevenz_member) {
+                        listing.insert(member);
+                    }
+                })
+                .map_err(other_error)
+        } else {
+            for_each_entry(path, |member, _| {
+                listing.insert(member);
+                Ok(true)
+            })
+        };
+        result.map_err(|e| format!("Cannot read archive {}: {}", path.display(), e))?;
+        Ok(listing)
+    }
+
+    /// Add a member along with any parent directories not listed yet. A
+    /// member listed twice keeps its last version, as when extracting.
+    fn insert(&mut self, entry: ArchiveEntry) {
+        let mut dir = parent_of(&entry.path);
+        while !dir.is_empty() && !self.entries.contains_key(dir) {
+            self.entries
+                .insert(dir.to_string(), ArchiveEntry::dir(dir.to_string()));
+            dir = parent_of(dir);
+        }
+        // An implied directory keeps its place if the member is the directory itself
+        if entry.is_dir()
+            && let Some(existing) = self.entries.get_mut(&entry.path)
+            && existing.is_dir()
+        {
+            existing.mode = entry.mode.or(existing.mode);
+            return;
+        }
+        self.entries.insert(entry.path.clone(), entry);
+    }
+
+    pub fn get(&self, path: &str) -> Option<&ArchiveEntry> {
+        self.entries.get(path)
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &ArchiveEntry> {
+        self.entries.values()
+    }
+
+    /// Members directly inside `dir` ("" for the top level)
+    pub fn children<'a>(&'a self, dir: &'a str) -> impl Iterator<Item = &'a ArchiveEntry> {
+        self.under(dir).filter(move |e| parent_of(&e.path) == dir)
+    }
+
+    /// Members anywhere below `dir` ("" for the whole archive)
+    fn under<'a>(&'a self, dir: &'a str) -> impl Iterator<Item = &'a ArchiveEntry> {
+        let prefix = if dir.is_empty() {
+            String::new()
+        } else {
+            format!("{dir}/")
+        };
+        self.entries
+            .range(prefix.clone()..)
+            .take_while(move |(path, _)| path.starts_with(&prefix))
+            .map(|(_, entry)| entry)
+    }
+
+    /// Total size and number of the files at or below `path`
+    pub fn totals(&self, path: &str) -> (u64, u64) {
+        self.get(path)
+            .into_iter()
+            .chain(self.under(path))
+            .filter(|e| e.kind == EntryKind::File)
+            .fold((0, 0), |(bytes, files), e| (bytes + e.size, files + 1))
+    }
+}
+
+/// A pane showing a directory inside an archive
+pub struct ArchiveView {
+    /// The archive file itself
+    pub file: PathBuf,
+    /// Directory inside the archive being listed ("" for the top level)
+    pub dir: String,
+    /// None until the listing has been read
+    pub listing: Option<std::sync::Arc<ArchiveListing>>,
+}
+
+impl ArchiveView {
+    pub fn new(file: PathBuf) -> Self {
+        Self {
+            file,
+            dir: String::new(),
+            listing: None,
+        }
+    }
+
+    /// Path shown for the listed directory: the archive path followed by
+    /// the directory inside it
+    pub fn path(&self) -> PathBuf {
+        if self.dir.is_empty() {
+            self.file.clone()
+        } else {
+            self.file.join(&self.dir)
+        }
+    }
+
+    /// Go to the parent directory inside the archive. Returns false at the
+    /// top level.
+    pub fn up(&mut self) -> bool {
+        if self.dir.is_empty() {
+            return false;
+        }
+        self.dir = parent_of(&self.dir).to_string();
+        true
+    }
+
+    /// Member path of a listed entry's (virtual) path
+    pub fn member(&self, path: &Path) -> Option<String> {
+        let relative = path.strip_prefix(&self.file).ok()?;
+        normalize_name(&relative.to_string_lossy())
+    }
+}
+
+/// Private directory holding members extracted to be viewed, created on
+/// first use
+static VIEW_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Numbers the subdirectory of each extracted member
+static VIEW_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+fn view_dir() -> io::Result<PathBuf> {
+    let mut dir = VIEW_DIR.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(dir) = &*dir {
+        return Ok(dir.clone());
+    }
+    let created = util::private_temp_dir("rmc-archive-")?;
+    *dir = Some(created.clone());
+    Ok(created)
+}
+
+/// Delete the members extracted for viewing (on exit)
+pub fn remove_view_dir() {
+    if let Some(dir) = VIEW_DIR.lock().unwrap_or_else(|e| e.into_inner()).take() {
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
+
+/// Extract one member for viewing, reading at most `limit` bytes of it, and
+/// return the path of the copy. Every copy gets a subdirectory of its own in
+/// a private temporary directory, so members with the same name don't
+/// overwrite each other.
+pub fn extract_for_viewing(archive: &Path, member: &str, limit: u64) -> Result<PathBuf, String> {
+    let dir = view_dir()
+        .map(|dir| dir.join(VIEW_COUNT.fetch_add(1, Ordering::Relaxed).to_string()))
+        .and_then(|dir| std::fs::create_dir(&dir).map(|()| dir))
+        .map_err(|e| format!("Cannot create a temporary directory: {}", e))?;
+    let name = member.rsplit('/').next().unwrap_or(member);
+    let target = dir.join(name);
+
+    let mut found = false;
+    for_each_entry(archive, |entry, data| {
+        if entry.path != member || entry.kind != EntryKind::File {
+            return Ok(true);
+        }
+        let mut file = File::options().write(true).create_new(true).open(&target)?;
+        io::copy(&mut data.take(limit), &mut file)?;
+        found = true;
+        Ok(false)
+    })
+    .map_err(|e| format!("Cannot extract {}: {}", member, e))?;
+
+    if found {
+        Ok(target)
+    } else {
+        Err(format!("{} not found in {}", member, archive.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{io::Write, os::unix::fs::PermissionsExt};
+
+    use crate::test_util::unique_temp_dir;
+
+    fn paths<'a>(entries: impl Iterator<Item = &'a ArchiveEntry>) -> Vec<&'a str> {
+        entries.map(|e| e.path.as_str()).collect()
+    }
+
+    #[test]
+    fn lists_tar_gz_and_zip_archives_with_implied_directories() {
+        let dir = unique_temp_dir("archive");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let tar_path = dir.join("test.tar.gz");
+        let gz = flate2::write::GzEncoder::new(
+            File::create(&tar_path).unwrap(),
+            flate2::Compression::fast(),
+        );
+        let mut tar = tar::Builder::new(gz);
+        let mut header = tar::Header::new_gnu();
+        header.set_size(5);
+        header.set_mode(0o640);
+        tar.append_data(&mut header, "./docs/guide/intro.txt", &b"hello"[..])
+            .unwrap();
+        let mut header = tar::Header::new_gnu();
+        header.set_size(3);
+        tar.append_data(&mut header, "top.txt", &b"abc"[..])
+            .unwrap();
+        tar.into_inner().unwrap().finish().unwrap();
+
+        let listing = ArchiveListing::read(&tar_path).unwrap();
+        assert_eq!(
+            paths(listing.entries()),
+            ["docs", "docs/guide", "docs/guide/intro.txt", "top.txt"]
+        );
+        assert_eq!(paths(listing.children("")), ["docs", "top.txt"]);
+        assert_eq!(paths(listing.children("docs")), ["docs/guide"]);
+        assert_eq!(listing.totals("docs"), (5, 1));
+        assert_eq!(listing.totals(""), (8, 2));
+        assert_eq!(
+            listing.get("docs/guide/intro.txt").unwrap().mode,
+            Some(0o640)
+        );
+
+        let zip_path = dir.join("test.zip");
+        let mut zip = zip::ZipWriter::new(File::create(&zip_path).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        zip.add_directory("empty/", options).unwrap();
+        zip.start_file("src/main.rs", options).unwrap();
+        zip.write_all(b"fn main() {}").unwrap();
+        zip.start_file("../evil.txt", options).unwrap();
+        zip.write_all(b"outside").unwrap();
+        zip.finish().unwrap();
+
+        let listing = ArchiveListing::read(&zip_path).unwrap();
+        assert_eq!(paths(listing.entries()), ["empty", "src", "src/main.rs"]);
+        assert!(listing.get("empty").unwrap().is_dir());
+
+        let view = ArchiveView {
+            file: zip_path.clone(),
+            dir: "src".into(),
+            listing: None,
+        };
+        assert_eq!(view.path(), zip_path.join("src"));
+        assert_eq!(
+            view.member(&view.path().join("main.rs")),
+            Some("src/main.rs".into())
+        );
+        let copy = extract_for_viewing(&zip_path, "src/main.rs", 4).unwrap();
+        assert_eq!(std::fs::read(&copy).unwrap(), b"fn m");
+        // Viewing it again leaves the first copy alone
+        let again = extract_for_viewing(&zip_path, "src/main.rs", 100).unwrap();
+        assert_ne!(copy, again);
+        assert_eq!(std::fs::read(&copy).unwrap(), b"fn m");
+        assert_eq!(again.file_name(), copy.file_name());
+        let private = copy.parent().unwrap().parent().unwrap();
+        let mode = std::fs::metadata(private).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+        remove_view_dir();
+        assert!(!private.exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
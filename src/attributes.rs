@@ -0,0 +1,396 @@
+//! Permissions and ownership dialog (chmod/chown)
+//!
+//! The dialog starts from the first target's mode, owner and group. Only the
+//! parts the user edits are applied, so changing the owner of several files
+//! leaves their differing permissions alone. With the recursive option the
+//! change also reaches everything inside selected directories, optionally
+//! only files or only directories.
+
+use std::{
+    os::unix::fs::{MetadataExt, PermissionsExt},
+    path::{Path, PathBuf},
+};
+
+use walkdir::WalkDir;
+
+/// Mode bits behind the checkboxes, in focus order: rwx for owner, group
+/// and others, then setuid, setgid and sticky
+pub const MODE_BITS: [u32; 12] = [
+    0o400, 0o200, 0o100, 0o040, 0o020, 0o010, 0o004, 0o002, 0o001, 0o4000, 0o2000, 0o1000,
+];
+
+pub const FOCUS_OCTAL: usize = 0;
+/// Focus index of the first mode checkbox; the others follow `MODE_BITS`
+pub const FOCUS_FIRST_BIT: usize = 1;
+pub const FOCUS_OWNER: usize = 13;
+pub const FOCUS_GROUP: usize = 14;
+pub const FOCUS_RECURSIVE: usize = 15;
+pub const FOCUS_FILES: usize = 16;
+pub const FOCUS_DIRS: usize = 17;
+const FIELD_COUNT: usize = 18;
+
+/// `name:...:id:...` lines of /etc/passwd or /etc/group as (name, id)
+fn parse_id_db(text: &str) -> Vec<(String, u32)> {
+    text.lines()
+        .filter(|l| !l.starts_with('#'))
+        .filter_map(|line| {
+            let mut fields = line.split(':');
+            let name = fields.next()?;
+            let id = fields.nth(1)?.parse().ok()?;
+            Some((name.to_owned(), id))
+        })
+        .collect()
+}
+
+fn read_id_db(path: &str) -> Vec<(String, u32)> {
+    std::fs::read_to_string(path)
+        .map(|text| parse_id_db(&text))
+        .unwrap_or_default()
+}
+
+fn name_for_id(db: &[(String, u32)], id: u32) -> String {
+    db.iter()
+        .find(|(_, i)| *i == id)
+        .map(|(name, _)| name.clone())
+        .unwrap_or_else(|| id.to_string())
+}
+
+/// A user or group given by name or number
+fn resolve_id(db: &[(String, u32)], input: &str) -> Option<u32> {
+    let input = input.trim();
+    input
+        .parse()
+        .ok()
+        .or_else(|| db.iter().find(|(name, _)| name == input).map(|(_, id)| *id))
+}
+
+/// User name for a uid, or the number if it has no name
+pub fn user_name(uid: u32) -> String {
+    name_for_id(&read_id_db("/etc/passwd"), uid)
+}
+
+/// Group name for a gid, or the number if it has no name
+pub fn group_name(gid: u32) -> String {
+    name_for_id(&read_id_db("/etc/group"), gid)
+}
+
+/// Symbolic form of a mode, like `rwxr-sr-t`
+pub fn mode_string(mode: u32) -> String {
+    let mut out = String::with_capacity(9);
+    for (shift, special, special_char) in [(6, 0o4000, 's'), (3, 0o2000, 's'), (0, 0o1000, 't')] {
+        let bits = (mode >> shift) & 0o7;
+        out.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+        out.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+        out.push(match (bits & 0o1 != 0, mode & special != 0) {
+            (true, true) => special_char,
+            (false, true) => special_char.to_ascii_uppercase(),
+            (true, false) => 'x',
+            (false, false) => '-',
+        });
+    }
+    out
+}
+
+/// State of the attributes dialog
+#[derive(Clone)]
+pub struct AttrForm {
+    pub targets: Vec<PathBuf>,
+    /// Permission bits (lowest 12 bits of the mode)
+    pub mode: u32,
+    pub octal: String,
+    pub owner: String,
+    pub group: String,
+    pub recursive: bool,
+    /// When recursive, change files inside the selected directories
+    pub files: bool,
+    /// When recursive, change directories inside the selected directories
+    pub dirs: bool,
+    /// Whether any target is a directory (recursion is possible)
+    pub has_dirs: bool,
+    /// Index of the focused field (see the `FOCUS_*` constants)
+    pub focus: usize,
+    mode_edited: bool,
+    initial_owner: String,
+    initial_group: String,
+}
+
+impl AttrForm {
+    /// Start from the first target's current attributes. Links are not
+    /// followed, matching what `apply` changes.
+    pub fn new(targets: Vec<PathBuf>) -> Result<Self, String> {
+        let first = targets.first().ok_or("Nothing selected")?;
+        let metadata = std::fs::symlink_metadata(first)
+            .map_err(|e| format!("Cannot read attributes of {}: {}", first.display(), e))?;
+        let mode = metadata.mode() & 0o7777;
+        let owner = user_name(metadata.uid());
+        let group = group_name(metadata.gid());
+        let has_dirs = targets
+            .iter()
+            .any(|t| std::fs::symlink_metadata(t).is_ok_and(|m| m.is_dir()));
+
+        Ok(Self {
+            targets,
+            mode,
+            octal: format!("{:04o}", mode),
+            owner: owner.clone(),
+            group: group.clone(),
+            recursive: false,
+            files: true,
+            dirs: true,
+            has_dirs,
+            focus: FOCUS_FIRST_BIT,
+            mode_edited: false,
+            initial_owner: owner,
+            initial_group: group,
+        })
+    }
+
+    /// The focused text input, or None if a checkbox has focus
+    pub fn focused_text_mut(&mut self) -> Option<&mut String> {
+        match self.focus {
+            FOCUS_OCTAL => Some(&mut self.octal),
+            FOCUS_OWNER => Some(&mut self.owner),
+            FOCUS_GROUP => Some(&mut self.group),
+            _ => None,
+        }
+    }
+
+    /// Sync the mode after the octal input changed. Incomplete input keeps
+    /// the previous mode.
+    pub fn text_changed(&mut self) {
+        if self.focus == FOCUS_OCTAL
+            && let Ok(mode) = u32::from_str_radix(self.octal.trim(), 8)
+            && mode <= 0o7777
+        {
+            self.mode = mode;
+            self.mode_edited = true;
+        }
+    }
+
+    /// Whether the checkbox at a focus index is checked
+    pub fn checked(&self, idx: usize) -> bool {
+        match idx {
+            FOCUS_RECURSIVE => self.recursive,
+            FOCUS_FILES => self.files,
+            FOCUS_DIRS => self.dirs,
+            _ => idx
+                .checked_sub(FOCUS_FIRST_BIT)
+                .and_then(|i| MODE_BITS.get(i))
+                .is_some_and(|bit| self.mode & bit != 0),
+        }
+    }
+
+    /// Flip the focused checkbox
+    pub fn toggle_focused(&mut self) {
+        match self.focus {
+            FOCUS_RECURSIVE => self.recursive = !self.recursive,
+            FOCUS_FILES => self.files = !self.files,
+            FOCUS_DIRS => self.dirs = !self.dirs,
+            idx => {
+                if let Some(bit) = idx
+                    .checked_sub(FOCUS_FIRST_BIT)
+                    .and_then(|i| MODE_BITS.get(i))
+                {
+                    self.mode ^= bit;
+                    self.octal = format!("{:04o}", self.mode);
+                    self.mode_edited = true;
+                }
+            }
+        }
+    }
+
+    fn focusable(&self, idx: usize) -> bool {
+        self.has_dirs || idx < FOCUS_RECURSIVE
+    }
+
+    pub fn focus_next(&mut self) {
+        loop {
+            self.focus = (self.focus + 1) % FIELD_COUNT;
+            if self.focusable(self.focus) {
+                break;
+            }
+        }
+    }
+
+    pub fn focus_prev(&mut self) {
+        loop {
+            self.focus = (self.focus + FIELD_COUNT - 1) % FIELD_COUNT;
+            if self.focusable(self.focus) {
+                break;
+            }
+        }
+    }
+
+    /// The change to apply: only the edited parts of the form
+    pub fn compile(&self) -> Result<AttrChange, String> {
+        if u32::from_str_radix(self.octal.trim(), 8).map_or(true, |m| m > 0o7777) {
+            return Err(format!("Invalid octal mode: {}", self.octal));
+        }
+        let uid = if self.owner.trim() != self.initial_owner {
+            Some(
+                resolve_id(&read_id_db("/etc/passwd"), &self.owner)
+                    .ok_or_else(|| format!("Unknown user: {}", self.owner.trim()))?,
+            )
+        } else {
+            None
+        };
+        let gid = if self.group.trim() != self.initial_group {
+            Some(
+                resolve_id(&read_id_db("/etc/group"), &self.group)
+                    .ok_or_else(|| format!("Unknown group: {}", self.group.trim()))?,
+            )
+        } else {
+            None
+        };
+
+        Ok(AttrChange {
+            mode: self.mode_edited.then_some(self.mode),
+            uid,
+            gid,
+            recursive: self.recursive && self.has_dirs,
+            files: self.files,
+            dirs: self.dirs,
+        })
+    }
+}
+
+/// A permission/ownership change, ready to run as a job
+#[derive(Clone)]
+pub struct AttrChange {
+    pub mode: Option<u32>,
+    pub uid: Option<u32>,
+    pub gid: Option<u32>,
+    pub recursive: bool,
+    pub files: bool,
+    pub dirs: bool,
+}
+
+impl AttrChange {
+    pub fn is_empty(&self) -> bool {
+        self.mode.is_none() && self.uid.is_none() && self.gid.is_none()
+    }
+
+    /// Every path the change applies to: the targets themselves, plus their
+    /// contents (filtered by type) when recursive. Symlinks are not followed.
+    pub fn expand_targets(&self, targets: &[PathBuf]) -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+        for target in targets {
+            paths.push(target.clone());
+            let is_dir = std::fs::symlink_metadata(target).is_ok_and(|m| m.is_dir());
+            if !self.recursive || !is_dir {
+                continue;
+            }
+            for entry in WalkDir::new(target)
+                .min_depth(1)
+                .into_iter()
+                .filter_map(|e| e.ok())
+            {
+                let is_dir = entry.file_type().is_dir();
+                if (is_dir && self.dirs) || (!is_dir && self.files) {
+                    paths.push(entry.into_path());
+                }
+            }
+        }
+        paths
+    }
+
+    /// Apply the change to one path. The mode of symlinks can't be changed,
+    /// so they only get their ownership changed.
+    pub fn apply(&self, path: &Path) -> std::io::Result<()> {
+        let is_symlink = path.is_symlink();
+        if let Some(mode) = self.mode
+            && !is_symlink
+        {
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))?;
+        }
+        if self.uid.is_some() || self.gid.is_some() {
+            std::os::unix::fs::lchown(path, self.uid, self.gid)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_util::unique_temp_dir;
+
+    #[test]
+    fn mode_string_shows_special_bits() {
+        assert_eq!(mode_string(0o755), "rwxr-xr-x");
+        assert_eq!(mode_string(0o4755), "rwsr-xr-x");
+        assert_eq!(mode_string(0o2644), "rw-r-Sr--");
+        assert_eq!(mode_string(0o1777), "rwxrwxrwt");
+    }
+
+    #[test]
+    fn id_database_lookup() {
+        let db = parse_id_db(
+            "# comment\nroot:x:0:0:root:/root:/bin/sh\nalice:x:1000:1000::/home/alice:/bin/sh\n",
+        );
+        assert_eq!(name_for_id(&db, 1000), "alice");
+        assert_eq!(name_for_id(&db, 42), "42");
+        assert_eq!(resolve_id(&db, "root"), Some(0));
+        assert_eq!(resolve_id(&db, " 1234 "), Some(1234));
+        assert_eq!(resolve_id(&db, "nobody-here"), None);
+    }
+
+    #[test]
+    fn only_edited_parts_are_applied() {
+        let dir = unique_temp_dir("attrs");
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        std::fs::write(dir.join("sub/file"), "x").unwrap();
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let mut form = AttrForm::new(vec![dir.clone()]).unwrap();
+        assert_eq!(form.octal, "0755");
+        assert!(form.compile().unwrap().is_empty());
+
+        // Remove execute for others, then type the mode instead
+        form.focus = FOCUS_FIRST_BIT + 8;
+        form.toggle_focused();
+        assert_eq!(form.octal, "0754");
+        form.focus = FOCUS_OCTAL;
+        form.octal = "0750".to_owned();
+        form.text_changed();
+        form.recursive = true;
+        form.files = false;
+
+        let change = form.compile().unwrap();
+        assert_eq!(change.mode, Some(0o750));
+        assert!(change.uid.is_none() && change.gid.is_none());
+        // Files inside are left out
+        let targets = change.expand_targets(&form.targets);
+        assert_eq!(targets, [dir.clone(), dir.join("sub")]);
+
+        for target in &targets {
+            change.apply(target).unwrap();
+        }
+        let mode = std::fs::metadata(dir.join("sub")).unwrap().mode() & 0o7777;
+        assert_eq!(mode, 0o750);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn recursion_does_not_enter_a_selected_symlink() {
+        let dir = unique_temp_dir("attrs-link");
+        std::fs::create_dir_all(dir.join("real")).unwrap();
+        std::fs::write(dir.join("real/file"), "x").unwrap();
+        std::os::unix::fs::symlink("real", dir.join("link")).unwrap();
+
+        let change = AttrChange {
+            mode: Some(0o700),
+            uid: None,
+            gid: None,
+            recursive: true,
+            files: true,
+            dirs: true,
+        };
+        let link = dir.join("link");
+        assert_eq!(change.expand_targets(std::slice::from_ref(&link)), [link]);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,222 @@
+//! Directory hotlist (bookmarks)
+//!
+//! Bookmarks are stored one per line as `mark<TAB>name<TAB>path` in the XDG
+//! state directory, next to the state file. The mark column is empty for
+//! bookmarks without a single-key mark.
+
+use std::{
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::state::get_state_dir;
+
+#[derive(Clone)]
+pub struct Bookmark {
+    pub name: String,
+    pub path: PathBuf,
+    /// Single-letter mark for `m<letter>` / `'<letter>`
+    pub mark: Option<char>,
+}
+
+pub struct Bookmarks {
+    pub items: Vec<Bookmark>,
+    /// File the list is saved to after every change
+    file: PathBuf,
+}
+
+fn get_bookmarks_file_path() -> PathBuf {
+    get_state_dir().join("bookmarks")
+}
+
+/// Default bookmark name for a directory: its last component, or the full path for `/`
+fn default_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+impl Bookmarks {
+    pub fn load() -> Self {
+        Self::load_from(get_bookmarks_file_path())
+    }
+
+    fn load_from(path: PathBuf) -> Self {
+        let Ok(file) = std::fs::File::open(&path) else {
+            return Self {
+                items: Vec::new(),
+                file: path,
+            };
+        };
+
+        let items = BufReader::new(file)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| {
+                let mut fields = line.splitn(3, '\t');
+                let mark = fields.next()?.chars().next();
+                let name = fields.next()?.to_owned();
+                let path = PathBuf::from(fields.next()?);
+                Some(Bookmark { name, path, mark })
+            })
+            .collect();
+
+        Self { items, file: path }
+    }
+
+    pub fn save(&self) {
+        if let Some(parent) = self.file.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+
+        let Ok(file) = std::fs::File::create(&self.file) else {
+            return;
+        };
+
+        let mut writer = BufWriter::new(file);
+        for item in &self.items {
+            let mark = item.mark.map(String::from).unwrap_or_default();
+            // Tabs and newlines would break the line format
+            let name = item.name.replace(['\t', '\n'], " ");
+            let _ = writeln!(writer, "{}\t{}\t{}", mark, name, item.path.display());
+        }
+    }
+
+    /// Add a bookmark for `path` unless it is already bookmarked.
+    /// Returns the index of the (new or existing) bookmark.
+    pub fn add(&mut self, path: &Path) -> usize {
+        if let Some(idx) = self.items.iter().position(|b| b.path == path) {
+            return idx;
+        }
+        self.items.push(Bookmark {
+            name: default_name(path),
+            path: path.to_path_buf(),
+            mark: None,
+        });
+        self.save();
+        self.items.len() - 1
+    }
+
+    /// Assign a single-letter mark to `path`, taking it from any other bookmark
+    pub fn set_mark(&mut self, mark: char, path: &Path) {
+        for item in &mut self.items {
+            if item.mark == Some(mark) {
+                item.mark = None;
+            }
+        }
+        let idx = self.add(path);
+        self.items[idx].mark = Some(mark);
+        self.save();
+    }
+
+    pub fn find_mark(&self, mark: char) -> Option<&Bookmark> {
+        self.items.iter().find(|b| b.mark == Some(mark))
+    }
+
+    pub fn rename(&mut self, idx: usize, name: String) {
+        if let Some(item) = self.items.get_mut(idx) {
+            item.name = name;
+            self.save();
+        }
+    }
+
+    pub fn remove(&mut self, idx: usize) {
+        if idx < self.items.len() {
+            self.items.remove(idx);
+            self.save();
+        }
+    }
+
+    /// Move a bookmark one position up (`up == true`) or down.
+    /// Returns the new index.
+    pub fn move_item(&mut self, idx: usize, up: bool) -> usize {
+        let target = if up {
+            idx.checked_sub(1)
+        } else {
+            Some(idx + 1).filter(|&t| t < self.items.len())
+        };
+        match target {
+            Some(target) if idx < self.items.len() => {
+                self.items.swap(idx, target);
+                self.save();
+                target
+            }
+            _ => idx,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_util::unique_temp_dir;
+
+    fn marks(bookmarks: &Bookmarks) -> Vec<(&str, Option<char>)> {
+        bookmarks
+            .items
+            .iter()
+            .map(|b| (b.name.as_str(), b.mark))
+            .collect()
+    }
+
+    #[test]
+    fn marks_move_between_bookmarks() {
+        let dir = unique_temp_dir("bookmarks-marks");
+        let mut bookmarks = Bookmarks::load_from(dir.join("bookmarks"));
+
+        bookmarks.set_mark('a', Path::new("/srv/data"));
+        bookmarks.add(Path::new("/tmp"));
+        assert_eq!(marks(&bookmarks), [("data", Some('a')), ("tmp", None)]);
+        assert_eq!(
+            bookmarks.find_mark('a').map(|b| b.path.as_path()),
+            Some(Path::new("/srv/data"))
+        );
+
+        // Taking a mark clears it on its previous bookmark
+        bookmarks.set_mark('a', Path::new("/tmp"));
+        assert_eq!(marks(&bookmarks), [("data", None), ("tmp", Some('a'))]);
+        assert_eq!(
+            bookmarks.find_mark('a').map(|b| b.path.as_path()),
+            Some(Path::new("/tmp"))
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn move_item_stops_at_both_ends() {
+        let dir = unique_temp_dir("bookmarks-move");
+        let mut bookmarks = Bookmarks::load_from(dir.join("bookmarks"));
+        for path in ["/a", "/b", "/c"] {
+            bookmarks.add(Path::new(path));
+        }
+
+        assert_eq!(bookmarks.move_item(0, true), 0);
+        assert_eq!(bookmarks.move_item(2, false), 2);
+        assert_eq!(marks(&bookmarks), [("a", None), ("b", None), ("c", None)]);
+
+        assert_eq!(bookmarks.move_item(0, false), 1);
+        assert_eq!(bookmarks.move_item(2, true), 1);
+        assert_eq!(marks(&bookmarks), [("b", None), ("c", None), ("a", None)]);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn saved_bookmarks_load_back() {
+        let dir = unique_temp_dir("bookmarks-save");
+        let file = dir.join("bookmarks");
+        let mut bookmarks = Bookmarks::load_from(file.clone());
+        bookmarks.add(Path::new("/"));
+        bookmarks.set_mark('p', Path::new("/home/user/projects"));
+        // Tabs would split the line into the wrong fields
+        bookmarks.rename(1, "my\tprojects".to_owned());
+
+        let loaded = Bookmarks::load_from(file);
+        assert_eq!(marks(&loaded), [("/", None), ("my projects", Some('p'))]);
+        assert_eq!(loaded.items[1].path, Path::new("/home/user/projects"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,739 @@
+//! Multi-rename tool for the selected entries
+//!
+//! New names are built from a name mask and an extension mask, then passed
+//! through an optional search/replace and a case conversion. Masks may use
+//! these placeholders:
+//!
+//! - `[N]` name without extension, `[E]` extension (without the dot)
+//! - `[C]` counter, using the start, step and digit count from the dialog
+//! - `[D]` modification date as `YYYY-MM-DD`, `[T]` time as `HHMMSS` (UTC)
+//!
+//! The preview flags names that clash with each other or with entries that
+//! are not being renamed; the batch only runs when there are none.
+//!
+//! Names can also be edited as text in `$EDITOR` (like vidir): each entry is
+//! written as a `number<TAB>name` line and changed lines become renames.
+
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use regex::{NoExpand, Regex, RegexBuilder};
+
+use crate::util::utc_datetime;
+
+/// Counters are padded to at most this many digits
+const MAX_COUNTER_WIDTH: usize = 10;
+
+/// Labels of the dialog fields, in focus order. The first `TEXT_FIELD_COUNT`
+/// are text inputs, the rest are options.
+pub const FIELD_LABELS: [&str; 10] = [
+    "Name mask:",
+    "Extension mask:",
+    "Search for:",
+    "Replace with:",
+    "Counter start:",
+    "Counter step:",
+    "Counter digits:",
+    "Regular expression",
+    "Case sensitive",
+    "Case",
+];
+pub const TEXT_FIELD_COUNT: usize = 7;
+
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum CaseConversion {
+    #[default]
+    Keep,
+    Lower,
+    Upper,
+    /// First letter of each word upper case, the rest lower case
+    Title,
+}
+
+impl CaseConversion {
+    pub fn cycle(self) -> Self {
+        match self {
+            Self::Keep => Self::Lower,
+            Self::Lower => Self::Upper,
+            Self::Upper => Self::Title,
+            Self::Title => Self::Keep,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Keep => "unchanged",
+            Self::Lower => "lower",
+            Self::Upper => "UPPER",
+            Self::Title => "Title",
+        }
+    }
+
+    fn apply(self, name: &str) -> String {
+        match self {
+            Self::Keep => name.to_owned(),
+            Self::Lower => name.to_lowercase(),
+            Self::Upper => name.to_uppercase(),
+            Self::Title => {
+                let mut out = String::with_capacity(name.len());
+                let mut word_start = true;
+                for c in name.chars() {
+                    if word_start {
+                        out.extend(c.to_uppercase());
+                    } else {
+                        out.extend(c.to_lowercase());
+                    }
+                    word_start = !c.is_alphanumeric();
+                }
+                out
+            }
+        }
+    }
+}
+
+/// Raw contents of the bulk rename dialog
+#[derive(Clone)]
+pub struct RenameForm {
+    pub name_mask: String,
+    pub ext_mask: String,
+    pub search: String,
+    pub replace: String,
+    pub counter_start: String,
+    pub counter_step: String,
+    pub counter_width: String,
+    pub regex: bool,
+    pub case_sensitive: bool,
+    pub case: CaseConversion,
+    /// Index into `FIELD_LABELS` of the focused field
+    pub focus: usize,
+}
+
+impl Default for RenameForm {
+    fn default() -> Self {
+        Self {
+            name_mask: "[N]".to_owned(),
+            ext_mask: "[E]".to_owned(),
+            search: String::new(),
+            replace: String::new(),
+            counter_start: "1".to_owned(),
+            counter_step: "1".to_owned(),
+            counter_width: "1".to_owned(),
+            regex: false,
+            case_sensitive: false,
+            case: CaseConversion::Keep,
+            focus: 0,
+        }
+    }
+}
+
+impl RenameForm {
+    /// The focused text input, or None if an option has focus
+    pub fn focused_text_mut(&mut self) -> Option<&mut String> {
+        match self.focus {
+            0 => Some(&mut self.name_mask),
+            1 => Some(&mut self.ext_mask),
+            2 => Some(&mut self.search),
+            3 => Some(&mut self.replace),
+            4 => Some(&mut self.counter_start),
+            5 => Some(&mut self.counter_step),
+            6 => Some(&mut self.counter_width),
+            _ => None,
+        }
+    }
+
+    pub fn text(&self, idx: usize) -> &str {
+        match idx {
+            0 => &self.name_mask,
+            1 => &self.ext_mask,
+            2 => &self.search,
+            3 => &self.replace,
+            4 => &self.counter_start,
+            5 => &self.counter_step,
+            6 => &self.counter_width,
+            _ => "",
+        }
+    }
+
+    /// Flip the focused checkbox or cycle the case conversion
+    pub fn toggle_focused(&mut self) {
+        match self.focus {
+            7 => self.regex = !self.regex,
+            8 => self.case_sensitive = !self.case_sensitive,
+            9 => self.case = self.case.cycle(),
+            _ => {}
+        }
+    }
+
+    pub fn focus_next(&mut self) {
+        self.focus = (self.focus + 1) % FIELD_LABELS.len();
+    }
+
+    pub fn focus_prev(&mut self) {
+        self.focus = (self.focus + FIELD_LABELS.len() - 1) % FIELD_LABELS.len();
+    }
+
+    fn compile(&self) -> Result<RenameRule, String> {
+        let search = match self.search.as_str() {
+            "" => None,
+            pattern => {
+                let pattern = if self.regex {
+                    pattern.to_owned()
+                } else {
+                    regex::escape(pattern)
+                };
+                Some(
+                    RegexBuilder::new(&pattern)
+                        .case_insensitive(!self.case_sensitive)
+                        .build()
+                        .map_err(|e| format!("Invalid search regex: {}", e))?,
+                )
+            }
+        };
+        let number = |label: &str, text: &str| {
+            text.trim()
+                .parse::<u64>()
+                .map_err(|_| format!("{} must be a number", label))
+        };
+
+        Ok(RenameRule {
+            name_mask: self.name_mask.clone(),
+            ext_mask: self.ext_mask.clone(),
+            search,
+            replace: self.replace.clone(),
+            regex: self.regex,
+            counter_start: number("Counter start", &self.counter_start)?,
+            counter_step: number("Counter step", &self.counter_step)?,
+            counter_width: (number("Counter digits", &self.counter_width)? as usize)
+                .min(MAX_COUNTER_WIDTH),
+            case: self.case,
+        })
+    }
+}
+
+/// A validated form
+struct RenameRule {
+    name_mask: String,
+    ext_mask: String,
+    search: Option<Regex>,
+    replace: String,
+    /// Whether `replace` may refer to capture groups (`$1`)
+    regex: bool,
+    counter_start: u64,
+    counter_step: u64,
+    counter_width: usize,
+    case: CaseConversion,
+}
+
+/// What a placeholder can expand to for one entry
+struct MaskContext<'a> {
+    name: &'a str,
+    ext: &'a str,
+    counter: String,
+    modified: Option<SystemTime>,
+}
+
+/// Split a file name into name and extension. Leading dots (hidden files)
+/// don't start an extension, and directories have none.
+fn split_extension(file_name: &str, is_dir: bool) -> (&str, &str) {
+    if is_dir {
+        return (file_name, "");
+    }
+    match file_name.rfind('.') {
+        Some(idx) if idx > 0 && !file_name[..idx].chars().all(|c| c == '.') => {
+            (&file_name[..idx], &file_name[idx + 1..])
+        }
+        _ => (file_name, ""),
+    }
+}
+
+fn expand_mask(mask: &str, ctx: &MaskContext) -> Result<String, String> {
+    let mut out = String::with_capacity(mask.len());
+    let mut rest = mask;
+    while let Some(open) = rest.find('[') {
+        out.push_str(&rest[..open]);
+        let Some(close) = rest[open..].find(']') else {
+            return Err("Unclosed '[' in mask".to_owned());
+        };
+        let placeholder = &rest[open + 1..open + close];
+        match placeholder {
+            "N" => out.push_str(ctx.name),
+            "E" => out.push_str(ctx.ext),
+            "C" => out.push_str(&ctx.counter),
+            "D" | "T" => {
+                let (y, mo, d, h, mi, s) =
+                    utc_datetime(ctx.modified.unwrap_or(SystemTime::UNIX_EPOCH));
+                if placeholder == "D" {
+                    out.push_str(&format!("{:04}-{:02}-{:02}", y, mo, d));
+                } else {
+                    out.push_str(&format!("{:02}{:02}{:02}", h, mi, s));
+                }
+            }
+            other => return Err(format!("Unknown placeholder [{}]", other)),
+        }
+        rest = &rest[open + close + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// An entry to rename
+#[derive(Clone)]
+pub struct RenameSource {
+    pub path: PathBuf,
+    pub is_dir: bool,
+    pub modified: Option<SystemTime>,
+}
+
+impl RenameSource {
+    pub fn new(path: PathBuf, is_dir: bool) -> Self {
+        let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok();
+        Self {
+            path,
+            is_dir,
+            modified,
+        }
+    }
+
+    fn file_name(&self) -> String {
+        self.path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RenameStatus {
+    Unchanged,
+    Rename,
+    /// Another entry in the batch gets the same name
+    Duplicate,
+    /// An entry that is not being renamed already has this name
+    Exists,
+    /// Empty, `.`/`..`, or containing a path separator
+    Invalid,
+}
+
+impl RenameStatus {
+    pub fn is_conflict(self) -> bool {
+        matches!(self, Self::Duplicate | Self::Exists | Self::Invalid)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Unchanged | Self::Rename => "",
+            Self::Duplicate => "duplicate",
+            Self::Exists => "exists",
+            Self::Invalid => "invalid",
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct PlannedRename {
+    pub source: PathBuf,
+    pub old_name: String,
+    pub new_name: String,
+    pub status: RenameStatus,
+}
+
+/// Compute the new name of every source and check the result for conflicts
+fn plan(rule: &RenameRule, sources: &[RenameSource]) -> Result<Vec<PlannedRename>, String> {
+    let mut planned = Vec::with_capacity(sources.len());
+    for (i, source) in sources.iter().enumerate() {
+        let old_name = source.file_name();
+        let (name, ext) = split_extension(&old_name, source.is_dir);
+        let counter = rule.counter_start + rule.counter_step * i as u64;
+        let ctx = MaskContext {
+            name,
+            ext,
+            counter: format!("{:0width$}", counter, width = rule.counter_width),
+            modified: source.modified,
+        };
+
+        let new_base = expand_mask(&rule.name_mask, &ctx)?;
+        let new_ext = expand_mask(&rule.ext_mask, &ctx)?;
+        let mut new_name = if new_ext.is_empty() {
+            new_base
+        } else {
+            format!("{}.{}", new_base, new_ext)
+        };
+        if let Some(search) = &rule.search {
+            new_name = if rule.regex {
+                search.replace_all(&new_name, rule.replace.as_str())
+            } else {
+                search.replace_all(&new_name, NoExpand(&rule.replace))
+            }
+            .into_owned();
+        }
+        let new_name = rule.case.apply(&new_name);
+
+        let status = if new_name == old_name {
+            RenameStatus::Unchanged
+        } else {
+            RenameStatus::Rename
+        };
+        planned.push(PlannedRename {
+            source: source.path.clone(),
+            old_name,
+            new_name,
+            status,
+        });
+    }
+
+    mark_conflicts(&mut planned);
+    Ok(planned)
+}
+
+/// Flag invalid names, names used twice in the batch, and names taken by
+/// entries outside the batch
+fn mark_conflicts(planned: &mut [PlannedRename]) {
+    let target = |p: &PlannedRename| p.source.with_file_name(&p.new_name);
+    let sources: HashSet<&Path> = planned.iter().map(|p| p.source.as_path()).collect();
+    let mut counts: HashMap<PathBuf, usize> = HashMap::new();
+    for p in planned.iter() {
+        *counts.entry(target(p)).or_default() += 1;
+    }
+
+    let statuses: Vec<RenameStatus> = planned
+        .iter()
+        .map(|p| {
+            let to = target(p);
+            if p.new_name.is_empty()
+                || p.new_name == "."
+                || p.new_name == ".."
+                || p.new_name.contains(['/', '\0'])
+            {
+                RenameStatus::Invalid
+            } else if counts[&to] > 1 {
+                RenameStatus::Duplicate
+            } else if p.status == RenameStatus::Rename
+                && !sources.contains(to.as_path())
+                && to.symlink_metadata().is_ok()
+            {
+                RenameStatus::Exists
+            } else {
+                p.status
+            }
+        })
+        .collect();
+    for (p, status) in planned.iter_mut().zip(statuses) {
+        p.status = status;
+    }
+}
+
+/// State of the bulk rename dialog
+#[derive(Clone)]
+pub struct BulkRename {
+    pub form: RenameForm,
+    pub sources: Vec<RenameSource>,
+    /// New names for the current form, or why the form is invalid
+    pub preview: Result<Vec<PlannedRename>, String>,
+    /// First preview row shown
+    pub scroll: usize,
+}
+
+impl BulkRename {
+    pub fn new(form: RenameForm, sources: Vec<RenameSource>) -> Self {
+        let mut rename = Self {
+            form,
+            sources,
+            preview: Ok(Vec::new()),
+            scroll: 0,
+        };
+        rename.update_preview();
+        rename
+    }
+
+    /// Recompute the preview after the form changed
+    pub fn update_preview(&mut self) {
+        self.preview = self
+            .form
+            .compile()
+            .and_then(|rule| plan(&rule, &self.sources));
+    }
+
+    /// Number of entries that would be renamed and number of conflicts
+    pub fn summary(&self) -> (usize, usize) {
+        let Ok(planned) = &self.preview else {
+            return (0, 0);
+        };
+        let renames = planned
+            .iter()
+            .filter(|p| p.status == RenameStatus::Rename)
+            .count();
+        let conflicts = planned.iter().filter(|p| p.status.is_conflict()).count();
+        (renames, conflicts)
+    }
+
+    /// The `(from, to)` pairs to execute, or why the batch can't run
+    pub fn renames(&self) -> Result<Vec<(PathBuf, PathBuf)>, String> {
+        let planned = self.preview.as_ref().map_err(|e| e.clone())?;
+        let (renames, conflicts) = self.summary();
+        if conflicts > 0 {
+            return Err(format!("{} conflicting names, nothing renamed", conflicts));
+        }
+        if renames == 0 {
+            return Err("No names change".to_owned());
+        }
+        Ok(planned
+            .iter()
+            .filter(|p| p.status == RenameStatus::Rename)
+            .map(|p| (p.source.clone(), p.source.with_file_name(&p.new_name)))
+            .collect())
+    }
+}
+
+// ============================================================================
+// Renaming in $EDITOR
+// ============================================================================
+
+/// The listing opened in the editor: one `number<TAB>name` line per source
+pub fn editor_listing(sources: &[PathBuf]) -> Result<String, String> {
+    let mut listing = String::new();
+    for (i, path) in sources.iter().enumerate() {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        if name.contains('\n') {
+            return Err(format!("Can't edit a name with a line break: {:?}", name));
+        }
+        listing.push_str(&format!("{}\t{}\n", i + 1, name));
+    }
+    Ok(listing)
+}
+
+/// Parse an edited listing into the renames it asks for. Lines are matched
+/// to sources by number, so they may be reordered; deleted lines leave their
+/// entry alone. Fails on malformed lines and on conflicting names.
+pub fn parse_editor_listing(sources: &[PathBuf], text: &str) -> Result<Vec<PlannedRename>, String> {
+    let mut seen = vec![false; sources.len()];
+    let mut planned = Vec::new();
+    for (line_no, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let malformed = || format!("Line {}: expected <number><TAB><name>", line_no + 1);
+        let (number, new_name) = line.split_once('\t').ok_or_else(malformed)?;
+        let idx = match number.trim().parse::<usize>() {
+            Ok(n) if (1..=sources.len()).contains(&n) => n - 1,
+            _ => return Err(malformed()),
+        };
+        if std::mem::replace(&mut seen[idx], true) {
+            return Err(format!(
+                "Line {}: entry {} appears twice",
+                line_no + 1,
+                idx + 1
+            ));
+        }
+
+        let source = &sources[idx];
+        let old_name = source
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+        let status = if new_name == old_name {
+            RenameStatus::Unchanged
+        } else {
+            RenameStatus::Rename
+        };
+        planned.push(PlannedRename {
+            source: source.clone(),
+            old_name,
+            new_name: new_name.to_owned(),
+            status,
+        });
+    }
+
+    mark_conflicts(&mut planned);
+    if let Some(conflict) = planned.iter().find(|p| p.status.is_conflict()) {
+        return Err(format!(
+            "Nothing renamed: '{}' is {}",
+            conflict.new_name,
+            match conflict.status {
+                RenameStatus::Duplicate => "used more than once",
+                RenameStatus::Exists => "already taken",
+                _ => "not a valid name",
+            }
+        ));
+    }
+    planned.retain(|p| p.status == RenameStatus::Rename);
+    Ok(planned)
+}
+
+/// Number of swaps and longer cycles in a batch: chains of renames where
+/// each target is another entry's current name, leading back to the start
+pub fn count_cycles(renames: &[(PathBuf, PathBuf)]) -> usize {
+    let next: HashMap<&Path, &Path> = renames
+        .iter()
+        .map(|(from, to)| (from.as_path(), to.as_path()))
+        .collect();
+    let mut visited: HashSet<&Path> = HashSet::new();
+    let mut cycles = 0;
+    for (start, _) in renames {
+        let mut current = start.as_path();
+        let mut chain = Vec::new();
+        while visited.insert(current) {
+            chain.push(current);
+            match next.get(current) {
+                Some(&to) => current = to,
+                None => break,
+            }
+        }
+        // A cycle closes on a path first seen in this chain
+        if chain.contains(&current) && next.contains_key(current) {
+            cycles += 1;
+        }
+    }
+    cycles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{fs, time::UNIX_EPOCH};
+
+    use crate::test_util::unique_temp_dir;
+
+    fn new_names(rename: &BulkRename) -> Vec<String> {
+        match &rename.preview {
+            Ok(planned) => planned.iter().map(|p| p.new_name.clone()).collect(),
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    #[test]
+    fn masks_counters_replace_and_case() {
+        let sources: Vec<RenameSource> = ["IMG_001.JPG", "IMG_002.JPG", "notes.tar.gz"]
+            .iter()
+            .map(|name| RenameSource {
+                path: PathBuf::from("/nonexistent").join(name),
+                is_dir: false,
+                modified: Some(UNIX_EPOCH),
+            })
+            .collect();
+
+        let mut rename = BulkRename::new(RenameForm::default(), sources);
+        assert_eq!(
+            new_names(&rename),
+            ["IMG_001.JPG", "IMG_002.JPG", "notes.tar.gz"]
+        );
+        assert_eq!(rename.summary(), (0, 0));
+
+        rename.form.name_mask = "photo-[C]-[D]".to_owned();
+        rename.form.counter_width = "3".to_owned();
+        rename.form.counter_step = "5".to_owned();
+        rename.form.case = CaseConversion::Lower;
+        rename.update_preview();
+        assert_eq!(
+            new_names(&rename),
+            [
+                "photo-001-1970-01-01.jpg",
+                "photo-006-1970-01-01.jpg",
+                "photo-011-1970-01-01.gz"
+            ]
+        );
+
+        rename.form = RenameForm {
+            search: r"IMG_(\d+)".to_owned(),
+            replace: "pic$1".to_owned(),
+            regex: true,
+            ext_mask: "png".to_owned(),
+            ..RenameForm::default()
+        };
+        rename.update_preview();
+        assert_eq!(
+            new_names(&rename),
+            ["pic001.png", "pic002.png", "notes.tar.png"]
+        );
+
+        rename.form.name_mask = "[X]".to_owned();
+        rename.update_preview();
+        assert!(rename.preview.is_err());
+    }
+
+    #[test]
+    fn conflicts_block_the_batch() {
+        let root = unique_temp_dir("bulk-rename");
+        fs::create_dir_all(&root).unwrap();
+        for name in ["a.txt", "b.txt", "other.md"] {
+            fs::write(root.join(name), name).unwrap();
+        }
+        let sources = vec![
+            RenameSource::new(root.join("a.txt"), false),
+            RenameSource::new(root.join("b.txt"), false),
+        ];
+
+        // Both become "same.txt"
+        let form = RenameForm {
+            name_mask: "same".to_owned(),
+            ..RenameForm::default()
+        };
+        let rename = BulkRename::new(form, sources.clone());
+        assert_eq!(rename.summary(), (0, 2));
+        assert!(rename.renames().is_err());
+
+        // "other.md" is taken by an entry outside the batch
+        let form = RenameForm {
+            search: "a.txt".to_owned(),
+            replace: "other.md".to_owned(),
+            ..RenameForm::default()
+        };
+        let rename = BulkRename::new(form, sources.clone());
+        let planned = rename.preview.as_ref().unwrap();
+        assert_eq!(planned[0].status, RenameStatus::Exists);
+
+        // Swapping names within the batch is fine
+        let planned_swap = |from: &str, to: &str| PlannedRename {
+            source: root.join(from),
+            old_name: from.to_owned(),
+            new_name: to.to_owned(),
+            status: RenameStatus::Rename,
+        };
+        let mut planned = [
+            planned_swap("a.txt", "b.txt"),
+            planned_swap("b.txt", "a.txt"),
+        ];
+        mark_conflicts(&mut planned);
+        assert!(planned.iter().all(|p| p.status == RenameStatus::Rename));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn editor_listing_round_trip_with_swap() {
+        let dir = PathBuf::from("/nonexistent");
+        let sources = vec![dir.join("a"), dir.join("b"), dir.join("c")];
+        let listing = editor_listing(&sources).unwrap();
+        assert_eq!(listing, "1\ta\n2\tb\n3\tc\n");
+
+        // Swap a and b, drop the line for c
+        let planned = parse_editor_listing(&sources, "2\ta\n1\tb\n").unwrap();
+        let renames: Vec<(PathBuf, PathBuf)> = planned
+            .iter()
+            .map(|p| (p.source.clone(), p.source.with_file_name(&p.new_name)))
+            .collect();
+        assert_eq!(
+            renames,
+            [
+                (dir.join("b"), dir.join("a")),
+                (dir.join("a"), dir.join("b"))
+            ]
+        );
+        assert_eq!(count_cycles(&renames), 1);
+
+        // A plain rename is not a cycle
+        let planned = parse_editor_listing(&sources, "3\td\n").unwrap();
+        assert_eq!(planned.len(), 1);
+        assert_eq!(count_cycles(&[(dir.join("c"), dir.join("d"))]), 0);
+
+        assert!(parse_editor_listing(&sources, "1\tx\n2\tx\n").is_err());
+        assert!(parse_editor_listing(&sources, "1\tx\n1\ty\n").is_err());
+        assert!(parse_editor_listing(&sources, "9\tx\n").is_err());
+        assert!(parse_editor_listing(&sources, "no tab\n").is_err());
+    }
+}
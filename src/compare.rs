@@ -0,0 +1,461 @@
+//! Directory comparison between the two panes
+//!
+//! Compares the listings of two directories by name and, for files present on
+//! both sides, by size, modification time or contents. Differences at the top
+//! level are marked in the panes so F5 copies exactly what is missing or newer;
+//! the recursive mode also reports differences inside common subdirectories,
+//! and marks a subdirectory when anything inside it differs. Symlinks are
+//! compared by their target rather than followed.
+
+use std::{
+    collections::BTreeMap,
+    fs::{File, Metadata},
+    io::Read,
+    path::{Path, PathBuf},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver},
+    },
+    thread,
+    time::SystemTime,
+};
+
+use crate::util::COPY_BUFFER_SIZE;
+
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum CompareMethod {
+    /// Size and modification time
+    #[default]
+    Quick,
+    /// Size only
+    Size,
+    /// Byte-by-byte contents (slow on large trees)
+    Content,
+}
+
+impl CompareMethod {
+    pub fn cycle(self) -> Self {
+        match self {
+            Self::Quick => Self::Size,
+            Self::Size => Self::Content,
+            Self::Content => Self::Quick,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Quick => "size + time",
+            Self::Size => "size only",
+            Self::Content => "contents",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DiffKind {
+    OnlyLeft,
+    OnlyRight,
+    LeftNewer,
+    RightNewer,
+    /// Different, but neither side is newer (or one is a file, the other a directory)
+    Differs,
+}
+
+impl DiffKind {
+    /// Whether the left-side entry should be marked for copying
+    pub fn marks_left(self) -> bool {
+        matches!(self, Self::OnlyLeft | Self::LeftNewer | Self::Differs)
+    }
+
+    pub fn marks_right(self) -> bool {
+        matches!(self, Self::OnlyRight | Self::RightNewer | Self::Differs)
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Self::OnlyLeft => "<+",
+            Self::OnlyRight => "+>",
+            Self::LeftNewer => "< ",
+            Self::RightNewer => " >",
+            Self::Differs => "!=",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Difference {
+    /// Path relative to the compared directories
+    pub rel_path: PathBuf,
+    pub kind: DiffKind,
+}
+
+struct Comparer<'a> {
+    method: CompareMethod,
+    recursive: bool,
+    include_hidden: bool,
+    cancel: &'a AtomicBool,
+    differences: Vec<Difference>,
+}
+
+impl Comparer<'_> {
+    fn compare_level(&mut self, left: &Path, right: &Path, rel: &Path) {
+        let left_entries = read_level(left, self.include_hidden);
+        let right_entries = read_level(right, self.include_hidden);
+
+        let mut names: Vec<&String> = left_entries.keys().chain(right_entries.keys()).collect();
+        names.sort();
+        names.dedup();
+
+        for name in names {
+            if self.cancel.load(Ordering::Relaxed) {
+                return;
+            }
+            let rel_path = rel.join(name);
+            let kind = match (left_entries.get(name), right_entries.get(name)) {
+                (Some(_), None) => Some(DiffKind::OnlyLeft),
+                (None, Some(_)) => Some(DiffKind::OnlyRight),
+                (Some(l), Some(r)) if l.is_dir() && r.is_dir() => {
+                    if self.recursive {
+                        let first = self.differences.len();
+                        self.compare_level(&left.join(name), &right.join(name), &rel_path);
+                        // List the directory itself ahead of what differs inside it
+                        if let Some(kind) = combined_kind(&self.differences[first..]) {
+                            let dir = Difference { rel_path, kind };
+                            self.differences.insert(first, dir);
+                        }
+                    }
+                    continue;
+                }
+                (Some(l), Some(r)) if l.is_dir() != r.is_dir() => Some(DiffKind::Differs),
+                (Some(l), Some(r)) if l.is_symlink() || r.is_symlink() => {
+                    self.compare_links(&left.join(name), l, &right.join(name), r)
+                }
+                (Some(l), Some(r)) => self.compare_files(&left.join(name), l, &right.join(name), r),
+                (None, None) => None,
+            };
+            if let Some(kind) = kind {
+                self.differences.push(Difference { rel_path, kind });
+            }
+        }
+    }
+
+    fn compare_files(
+        &self,
+        left: &Path,
+        left_meta: &Metadata,
+        right: &Path,
+        right_meta: &Metadata,
+    ) -> Option<DiffKind> {
+        let same_size = left_meta.len() == right_meta.len();
+        let left_time = left_meta.modified().ok();
+        let right_time = right_meta.modified().ok();
+
+        let same = match self.method {
+            CompareMethod::Quick => same_size && left_time == right_time,
+            CompareMethod::Size => same_size,
+            CompareMethod::Content => same_size && same_contents(left, right, self.cancel),
+        };
+        (!same).then(|| newer_side(left_time, right_time))
+    }
+
+    /// Symlinks are the same when both point at the same target; they are
+    /// never followed
+    fn compare_links(
+        &self,
+        left: &Path,
+        left_meta: &Metadata,
+        right: &Path,
+        right_meta: &Metadata,
+    ) -> Option<DiffKind> {
+        let same = left_meta.is_symlink()
+            && right_meta.is_symlink()
+            && std::fs::read_link(left).ok() == std::fs::read_link(right).ok();
+        (!same).then(|| newer_side(left_meta.modified().ok(), right_meta.modified().ok()))
+    }
+}
+
+/// How a directory differs, given the differences found inside it: newer on
+/// one side when everything inside points the same way
+fn combined_kind(inside: &[Difference]) -> Option<DiffKind> {
+    if inside.is_empty() {
+        None
+    } else if inside.iter().all(|d| !d.kind.marks_right()) {
+        Some(DiffKind::LeftNewer)
+    } else if inside.iter().all(|d| !d.kind.marks_left()) {
+        Some(DiffKind::RightNewer)
+    } else {
+        Some(DiffKind::Differs)
+    }
+}
+
+fn newer_side(left: Option<SystemTime>, right: Option<SystemTime>) -> DiffKind {
+    match (left, right) {
+        (Some(l), Some(r)) if l > r => DiffKind::LeftNewer,
+        (Some(l), Some(r)) if l < r => DiffKind::RightNewer,
+        _ => DiffKind::Differs,
+    }
+}
+
+/// Entries of one directory by name. Symlinks are not followed, so a link
+/// to a parent directory cannot make a recursive comparison loop.
+fn read_level(dir: &Path, include_hidden: bool) -> BTreeMap<String, Metadata> {
+    let Ok(read_dir) = std::fs::read_dir(dir) else {
+        return BTreeMap::new();
+    };
+    read_dir
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().into_owned();
+            if !include_hidden && name.starts_with('.') {
+                return None;
+            }
+            let metadata = std::fs::symlink_metadata(e.path()).ok()?;
+            Some((name, metadata))
+        })
+        .collect()
+}
+
+fn same_contents(left: &Path, right: &Path, cancel: &AtomicBool) -> bool {
+    let (Ok(mut left), Ok(mut right)) = (File::open(left), File::open(right)) else {
+        return false;
+    };
+    let mut left_buf = vec![0u8; COPY_BUFFER_SIZE];
+    let mut right_buf = vec![0u8; COPY_BUFFER_SIZE];
+    loop {
+        if cancel.load(Ordering::Relaxed) {
+            return false;
+        }
+        let Ok(n) = read_full(&mut left, &mut left_buf) else {
+            return false;
+        };
+        let Ok(m) = read_full(&mut right, &mut right_buf) else {
+            return false;
+        };
+        if n != m || left_buf[..n] != right_buf[..m] {
+            return false;
+        }
+        if n == 0 {
+            return true;
+        }
+    }
+}
+
+/// Fill `buf` as far as possible, returning fewer bytes only at end of file
+fn read_full(file: &mut File, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match file.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+/// Compare two directories, returning differences in name order
+/// (depth-first for recursive comparisons)
+pub fn compare_dirs(
+    left: &Path,
+    right: &Path,
+    method: CompareMethod,
+    recursive: bool,
+    include_hidden: bool,
+    cancel: &AtomicBool,
+) -> Vec<Difference> {
+    let mut comparer = Comparer {
+        method,
+        recursive,
+        include_hidden,
+        cancel,
+        differences: Vec::new(),
+    };
+    comparer.compare_level(left, right, Path::new(""));
+    comparer.differences
+}
+
+/// A comparison running in a background thread
+pub struct CompareRun {
+    pub left: PathBuf,
+    pub right: PathBuf,
+    pub method: CompareMethod,
+    pub recursive: bool,
+    /// None while the comparison is running
+    pub differences: Option<Vec<Difference>>,
+    rx: Receiver<Vec<Difference>>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl CompareRun {
+    pub fn start(
+        left: PathBuf,
+        right: PathBuf,
+        method: CompareMethod,
+        recursive: bool,
+        include_hidden: bool,
+    ) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+
+        let worker_cancel = Arc::clone(&cancel);
+        let (l, r) = (left.clone(), right.clone());
+        thread::spawn(move || {
+            let differences =
+                compare_dirs(&l, &r, method, recursive, include_hidden, &worker_cancel);
+            let _ = tx.send(differences);
+        });
+
+        Self {
+            left,
+            right,
+            method,
+            recursive,
+            differences: None,
+            rx,
+            cancel,
+        }
+    }
+
+    /// Returns true when the comparison has just finished
+    pub fn poll(&mut self) -> bool {
+        if self.differences.is_some() {
+            return false;
+        }
+        match self.rx.try_recv() {
+            Ok(differences) => {
+                self.differences = Some(differences);
+                true
+            }
+            Err(mpsc::TryRecvError::Empty) => false,
+            Err(mpsc::TryRecvError::Disconnected) => {
+                self.differences = Some(Vec::new());
+                true
+            }
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Drop for CompareRun {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{
+        fs,
+        time::{Duration, UNIX_EPOCH},
+    };
+
+    use crate::test_util::unique_temp_dir;
+
+    fn write_with_mtime(path: &Path, contents: &str, secs: u64) {
+        fs::write(path, contents).unwrap();
+        let file = File::options().write(true).open(path).unwrap();
+        file.set_modified(UNIX_EPOCH + Duration::from_secs(secs))
+            .unwrap();
+    }
+
+    fn diff(rel: &str, kind: DiffKind) -> Difference {
+        Difference {
+            rel_path: PathBuf::from(rel),
+            kind,
+        }
+    }
+
+    #[test]
+    fn finds_missing_newer_and_nested_differences() {
+        let root = unique_temp_dir("compare");
+        let (left, right) = (root.join("left"), root.join("right"));
+        for dir in [&left, &right] {
+            fs::create_dir_all(dir.join("sub")).unwrap();
+        }
+        write_with_mtime(&left.join("same.txt"), "same", 1000);
+        write_with_mtime(&right.join("same.txt"), "same", 1000);
+        write_with_mtime(&left.join("only-left.txt"), "x", 1000);
+        write_with_mtime(&right.join("only-right.txt"), "x", 1000);
+        write_with_mtime(&left.join("edited.txt"), "new text", 2000);
+        write_with_mtime(&right.join("edited.txt"), "old", 1000);
+        // Same size and time, different contents
+        write_with_mtime(&left.join("sub/nested.txt"), "aaaa", 1000);
+        write_with_mtime(&right.join("sub/nested.txt"), "bbbb", 1000);
+
+        let no_cancel = AtomicBool::new(false);
+        let quick = compare_dirs(
+            &left,
+            &right,
+            CompareMethod::Quick,
+            false,
+            false,
+            &no_cancel,
+        );
+        assert_eq!(
+            quick,
+            [
+                diff("edited.txt", DiffKind::LeftNewer),
+                diff("only-left.txt", DiffKind::OnlyLeft),
+                diff("only-right.txt", DiffKind::OnlyRight),
+            ]
+        );
+
+        let quick_recursive =
+            compare_dirs(&left, &right, CompareMethod::Quick, true, false, &no_cancel);
+        assert_eq!(quick_recursive.len(), 3);
+
+        let content = compare_dirs(
+            &left,
+            &right,
+            CompareMethod::Content,
+            true,
+            false,
+            &no_cancel,
+        );
+        assert_eq!(
+            content[3..],
+            [
+                diff("sub", DiffKind::Differs),
+                diff("sub/nested.txt", DiffKind::Differs),
+            ]
+        );
+        assert_eq!(content.len(), 5);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn symlinks_are_compared_by_target_and_parents_follow_their_contents() {
+        let root = unique_temp_dir("compare-links");
+        let (left, right) = (root.join("left"), root.join("right"));
+        for dir in [&left, &right] {
+            fs::create_dir_all(dir.join("sub/deeper")).unwrap();
+            // A link back up would recurse forever if it were followed
+            std::os::unix::fs::symlink("..", dir.join("sub/up")).unwrap();
+        }
+        std::os::unix::fs::symlink("a", left.join("link")).unwrap();
+        std::os::unix::fs::symlink("b", right.join("link")).unwrap();
+        write_with_mtime(&left.join("sub/deeper/new.txt"), "x", 1000);
+
+        let no_cancel = AtomicBool::new(false);
+        let differences =
+            compare_dirs(&left, &right, CompareMethod::Quick, true, false, &no_cancel);
+        assert_eq!(differences.len(), 4);
+        assert_eq!(differences[0].rel_path, PathBuf::from("link"));
+        assert_eq!(
+            differences[1..],
+            [
+                diff("sub", DiffKind::LeftNewer),
+                diff("sub/deeper", DiffKind::LeftNewer),
+                diff("sub/deeper/new.txt", DiffKind::OnlyLeft),
+            ]
+        );
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,456 @@
+//! Creating archives from the selection
+//!
+//! The selected entries are packed into a tar (plain, gzip, xz or zstd
+//! compressed) or zip archive, in the other pane by default. Entries are
+//! stored relative to the directory they were selected in; symlinks are
+//! stored as links, not followed. `ArchiveWriter` adds one entry at a time
+//! and reads file data from any reader, so the background job can report
+//! progress and stop part way.
+
+use std::{
+    fs::{File, Metadata},
+    io::{self, BufWriter, Read, Write},
+    os::unix::fs::MetadataExt,
+    path::{Path, PathBuf},
+};
+
+use zip::write::SimpleFileOptions;
+
+use crate::util::utc_datetime;
+
+pub const FOCUS_NAME: usize = 0;
+pub const FOCUS_FORMAT: usize = 1;
+pub const FOCUS_LEVEL: usize = 2;
+const FIELD_COUNT: usize = 3;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CompressFormat {
+    Tar,
+    TarGz,
+    TarXz,
+    TarZst,
+    Zip,
+}
+
+impl CompressFormat {
+    pub const ALL: [Self; 5] = [Self::Tar, Self::TarGz, Self::TarXz, Self::TarZst, Self::Zip];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Tar => "tar",
+            Self::TarGz => "tar.gz",
+            Self::TarXz => "tar.xz",
+            Self::TarZst => "tar.zst",
+            Self::Zip => "zip",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Tar => ".tar",
+            Self::TarGz => ".tar.gz",
+            Self::TarXz => ".tar.xz",
+            Self::TarZst => ".tar.zst",
+            Self::Zip => ".zip",
+        }
+    }
+
+    /// (lowest, highest, default) compression level, or None if the format
+    /// does not compress
+    pub fn levels(self) -> Option<(u32, u32, u32)> {
+        match self {
+            Self::Tar => None,
+            Self::TarGz | Self::Zip => Some((1, 9, 6)),
+            Self::TarXz => Some((0, 9, 6)),
+            Self::TarZst => Some((1, 19, 3)),
+        }
+    }
+
+    fn default_level(self) -> u32 {
+        self.levels().map_or(0, |(_, _, default)| default)
+    }
+}
+
+/// Everything a compress job needs
+#[derive(Clone, Debug)]
+pub struct CompressOp {
+    pub sources: Vec<PathBuf>,
+    pub archive: PathBuf,
+    pub format: CompressFormat,
+    pub level: u32,
+}
+
+impl CompressOp {
+    pub fn description(&self) -> String {
+        let archive = self
+            .archive
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy();
+        match self.sources.as_slice() {
+            [single] => format!(
+                "Compressing '{}' to '{}'",
+                single.file_name().unwrap_or_default().to_string_lossy(),
+                archive
+            ),
+            sources => format!("Compressing {} items to '{}'", sources.len(), archive),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct CompressForm {
+    pub sources: Vec<PathBuf>,
+    /// Archive path; relative paths are taken from `dest_dir`
+    pub name: String,
+    pub format: CompressFormat,
+    pub level: u32,
+    pub focus: usize,
+    dest_dir: PathBuf,
+}
+
+impl CompressForm {
+    /// Pack `sources` into an archive in `dest_dir`, named after the single
+    /// source or else after the directory they are in
+    pub fn new(sources: Vec<PathBuf>, dest_dir: &Path) -> Self {
+        let stem = match sources.as_slice() {
+            [single] => single.file_name(),
+            [first, ..] => first.parent().and_then(Path::file_name),
+            [] => None,
+        }
+        .map_or_else(
+            || "archive".to_string(),
+            |n| n.to_string_lossy().into_owned(),
+        );
+        let format = CompressFormat::TarGz;
+        Self {
+            sources,
+            name: format!("{}{}", stem, format.extension()),
+            format,
+            level: format.default_level(),
+            focus: FOCUS_NAME,
+            dest_dir: dest_dir.to_path_buf(),
+        }
+    }
+
+    pub fn focused_text_mut(&mut self) -> Option<&mut String> {
+        (self.focus == FOCUS_NAME).then_some(&mut self.name)
+    }
+
+    pub fn focus_next(&mut self) {
+        self.focus = (self.focus + 1) % FIELD_COUNT;
+    }
+
+    pub fn focus_prev(&mut self) {
+        self.focus = (self.focus + FIELD_COUNT - 1) % FIELD_COUNT;
+    }
+
+    /// Step the focused choice: the next or previous format, or the level up
+    /// or down
+    pub fn adjust(&mut self, forward: bool) {
+        match self.focus {
+            FOCUS_FORMAT => {
+                let all = CompressFormat::ALL;
+                let idx = all.iter().position(|&f| f == self.format).unwrap_or(0);
+                let idx = if forward {
+                    (idx + 1) % all.len()
+                } else {
+                    (idx + all.len() - 1) % all.len()
+                };
+                self.set_format(all[idx]);
+            }
+            FOCUS_LEVEL => {
+                if let Some((low, high, _)) = self.format.levels() {
+                    self.level = if forward {
+                        (self.level + 1).min(high)
+                    } else {
+                        self.level.saturating_sub(1).max(low)
+                    };
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Switch formats, changing the name's extension along with it
+    fn set_format(&mut self, format: CompressFormat) {
+        if let Some(stem) = self.name.strip_suffix(self.format.extension()) {
+            self.name = format!("{}{}", stem, format.extension());
+        }
+        self.format = format;
+        self.level = format.default_level();
+    }
+
+    pub fn compile(&self) -> Result<CompressOp, String> {
+        if self.name.is_empty() {
+            return Err("Archive name is empty".to_string());
+        }
+        let archive = self.dest_dir.join(&self.name);
+        if archive.is_dir() {
+            return Err(format!("{} is a directory", archive.display()));
+        }
+        if self
+            .sources
+            .iter()
+            .any(|source| archive.starts_with(source))
+        {
+            return Err("Cannot write the archive inside what is being compressed".to_string());
+        }
+        Ok(CompressOp {
+            sources: self.sources.clone(),
+            archive,
+            format: self.format,
+            level: self.level,
+        })
+    }
+}
+
+/// Compressed output stream of a tar archive
+pub enum Encoder {
+    Plain(BufWriter<File>),
+    Gz(flate2::write::GzEncoder<BufWriter<File>>),
+    Xz(xz2::write::XzEncoder<BufWriter<File>>),
+    Zst(zstd::Encoder<'static, BufWriter<File>>),
+}
+
+impl Write for Encoder {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Plain(w) => w.write(buf),
+            Self::Gz(w) => w.write(buf),
+            Self::Xz(w) => w.write(buf),
+            Self::Zst(w) => w.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Plain(w) => w.flush(),
+            Self::Gz(w) => w.flush(),
+            Self::Xz(w) => w.flush(),
+            Self::Zst(w) => w.flush(),
+        }
+    }
+}
+
+impl Encoder {
+    /// Write the compressed stream's trailer and flush the file
+    fn finish(self) -> io::Result<()> {
+        let mut file = match self {
+            Self::Plain(w) => w,
+            Self::Gz(w) => w.finish()?,
+            Self::Xz(w) => w.finish()?,
+            Self::Zst(w) => w.finish()?,
+        };
+        file.flush()
+    }
+}
+
+fn other_error(e: impl ToString) -> io::Error {
+    io::Error::other(e.to_string())
+}
+
+/// Modification time of `metadata` as a zip timestamp (zip can't store
+/// dates before 1980)
+fn zip_time(metadata: &Metadata) -> zip::DateTime {
+    let Ok(modified) = metadata.modified() else {
+        return zip::DateTime::default();
+    };
+    let (year, month, day, hour, minute, second) = utc_datetime(modified);
+    u16::try_from(year)
+        .ok()
+        .and_then(|year| {
+            zip::DateTime::from_date_and_time(
+                year,
+                month as u8,
+                day as u8,
+                hour as u8,
+                minute as u8,
+                second as u8,
+            )
+            .ok()
+        })
+        .unwrap_or_default()
+}
+
+/// An archive being written, one entry at a time
+pub enum ArchiveWriter {
+    Tar(tar::Builder<Encoder>),
+    Zip {
+        writer: zip::ZipWriter<BufWriter<File>>,
+        level: u32,
+    },
+}
+
+impl ArchiveWriter {
+    pub fn create(path: &Path, format: CompressFormat, level: u32) -> io::Result<Self> {
+        let file = BufWriter::new(File::create(path)?);
+        let encoder = match format {
+            CompressFormat::Tar => Encoder::Plain(file),
+            CompressFormat::TarGz => Encoder::Gz(flate2::write::GzEncoder::new(
+                file,
+                flate2::Compression::new(level),
+            )),
+            CompressFormat::TarXz => Encoder::Xz(xz2::write::XzEncoder::new(file, level)),
+            CompressFormat::TarZst => Encoder::Zst(zstd::Encoder::new(file, level as i32)?),
+            CompressFormat::Zip => {
+                return Ok(Self::Zip {
+                    writer: zip::ZipWriter::new(file),
+                    level,
+                });
+            }
+        };
+        let mut builder = tar::Builder::new(encoder);
+        builder.follow_symlinks(false);
+        Ok(Self::Tar(builder))
+    }
+
+    fn zip_options(level: u32, metadata: &Metadata) -> SimpleFileOptions {
+        SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated)
+            .compression_level(Some(i64::from(level)))
+            .unix_permissions(metadata.mode())
+            .last_modified_time(zip_time(metadata))
+            .large_file(metadata.len() >= u64::from(u32::MAX))
+    }
+
+    fn tar_header(metadata: &Metadata) -> tar::Header {
+        let mut header = tar::Header::new_gnu();
+        header.set_metadata(metadata);
+        header
+    }
+
+    /// Add a directory entry; `name` is its `/`-separated path in the archive
+    pub fn add_dir(&mut self, name: &str, metadata: &Metadata) -> io::Result<()> {
+        match self {
+            Self::Tar(builder) => {
+                let mut header = Self::tar_header(metadata);
+                header.set_size(0);
+                builder.append_data(&mut header, name, io::empty())
+            }
+            Self::Zip { writer, level } => writer
+                .add_directory(name, Self::zip_options(*level, metadata))
+                .map_err(other_error),
+        }
+    }
+
+    /// Add a symlink pointing at `target`
+    pub fn add_symlink(
+        &mut self,
+        name: &str,
+        target: &Path,
+        metadata: &Metadata,
+    ) -> io::Result<()> {
+        match self {
+            Self::Tar(builder) => {
+                let mut header = Self::tar_header(metadata);
+                header.set_size(0);
+                builder.append_link(&mut header, name, target)
+            }
+            Self::Zip { writer, level } => writer
+                .add_symlink(
+                    name,
+                    target.to_string_lossy(),
+                    Self::zip_options(*level, metadata),
+                )
+                .map_err(other_error),
+        }
+    }
+
+    /// Add a regular file whose contents are read from `data`. The entry is
+    /// exactly `metadata.len()` bytes long even if the file changes size
+    /// while it is being read.
+    pub fn add_file(
+        &mut self,
+        name: &str,
+        metadata: &Metadata,
+        data: &mut dyn Read,
+    ) -> io::Result<()> {
+        let size = metadata.len();
+        let mut data = data.take(size).chain(io::repeat(0)).take(size);
+        match self {
+            Self::Tar(builder) => {
+                let mut header = Self::tar_header(metadata);
+                builder.append_data(&mut header, name, data)
+            }
+            Self::Zip { writer, level } => {
+                writer
+                    .start_file(name, Self::zip_options(*level, metadata))
+                    .map_err(other_error)?;
+                io::copy(&mut data, writer).map(|_| ())
+            }
+        }
+    }
+
+    /// Write the archive's trailer; the archive is incomplete until then
+    pub fn finish(self) -> io::Result<()> {
+        match self {
+            Self::Tar(builder) => builder.into_inner()?.finish(),
+            Self::Zip { writer, .. } => writer.finish().map_err(other_error)?.flush(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        archive::{ArchiveListing, EntryKind},
+        test_util::unique_temp_dir,
+    };
+
+    #[test]
+    fn written_archives_list_back_in_every_format() {
+        let dir = unique_temp_dir("compress");
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        std::fs::write(dir.join("src/main.rs"), b"fn main() {}").unwrap();
+        std::os::unix::fs::symlink("main.rs", dir.join("src/link.rs")).unwrap();
+
+        let mut form = CompressForm::new(vec![dir.join("src")], &dir);
+        assert_eq!(form.name, "src.tar.gz");
+        form.focus = FOCUS_FORMAT;
+        for format in CompressFormat::ALL {
+            form.set_format(format);
+            assert_eq!(form.name, format!("src{}", format.extension()));
+            let op = form.compile().unwrap();
+
+            let mut writer = ArchiveWriter::create(&op.archive, op.format, op.level).unwrap();
+            let src = std::fs::symlink_metadata(dir.join("src")).unwrap();
+            writer.add_dir("src", &src).unwrap();
+            let main = std::fs::symlink_metadata(dir.join("src/main.rs")).unwrap();
+            let mut data = File::open(dir.join("src/main.rs")).unwrap();
+            writer.add_file("src/main.rs", &main, &mut data).unwrap();
+            let link = std::fs::symlink_metadata(dir.join("src/link.rs")).unwrap();
+            writer
+                .add_symlink("src/link.rs", Path::new("main.rs"), &link)
+                .unwrap();
+            writer.finish().unwrap();
+
+            let listing = ArchiveListing::read(&op.archive).unwrap();
+            let file = listing.get("src/main.rs").unwrap();
+            assert_eq!((file.size, file.mode), (12, Some(main.mode() & 0o7777)));
+            assert_eq!(
+                listing.get("src/link.rs").unwrap().kind,
+                EntryKind::Symlink("main.rs".to_string())
+            );
+        }
+
+        // Levels stay within the format's range
+        form.set_format(CompressFormat::TarZst);
+        form.focus = FOCUS_LEVEL;
+        for _ in 0..30 {
+            form.adjust(true);
+        }
+        assert_eq!(form.level, 19);
+
+        // The archive can't go into a directory being compressed
+        let inside = CompressForm::new(vec![dir.join("src")], &dir.join("src"));
+        assert!(inside.compile().is_err());
+        let mut itself = CompressForm::new(vec![dir.join("src.zip")], &dir);
+        itself.name = "src.zip".to_string();
+        assert!(itself.compile().is_err());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,447 @@
+//! Two-file diff viewer
+//!
+//! Text files are diffed line by line and shown either side by side, with
+//! changed lines aligned, or as a unified diff. Binary files are compared as
+//! hex dumps aligned by offset, so a row differs when any of its bytes do.
+//! Each run of changed rows is a hunk; `n`/`p` jump between them.
+
+use std::{
+    ops::Range,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use similar::{Algorithm, DiffTag, capture_diff_slices_deadline};
+
+use crate::viewer::{HEX_BYTES_PER_LINE, Scrollable, hex_dump_line, is_likely_text};
+
+/// Files larger than this are not diffed
+const MAX_DIFF_FILE_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Give up on a minimal diff after this long and accept a coarser one
+const DIFF_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Lines kept visible above a hunk jumped to
+const HUNK_CONTEXT_LINES: usize = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RowKind {
+    Same,
+    /// Present on both sides with different contents
+    Changed,
+    /// Only on the left
+    Removed,
+    /// Only on the right
+    Added,
+}
+
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum DiffLayout {
+    #[default]
+    SideBySide,
+    Unified,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DiffLine {
+    /// 1-based line number (row number for hex dumps)
+    pub number: usize,
+    pub text: String,
+}
+
+#[derive(Clone)]
+pub struct SideRow {
+    pub kind: RowKind,
+    pub left: Option<DiffLine>,
+    pub right: Option<DiffLine>,
+}
+
+#[derive(Clone)]
+pub struct UnifiedRow {
+    /// `Same`, `Removed` or `Added`
+    pub kind: RowKind,
+    pub left_number: Option<usize>,
+    pub right_number: Option<usize>,
+    pub text: String,
+}
+
+/// A run of identical lines or a run of changes
+#[derive(Debug, PartialEq)]
+enum Block {
+    Same(Vec<(DiffLine, DiffLine)>),
+    Change {
+        removed: Vec<DiffLine>,
+        added: Vec<DiffLine>,
+    },
+}
+
+fn numbered(lines: &[String], range: Range<usize>) -> Vec<DiffLine> {
+    range
+        .map(|i| DiffLine {
+            number: i + 1,
+            text: lines[i].clone(),
+        })
+        .collect()
+}
+
+/// Line diff of two texts. Adjacent changes are merged into one block.
+fn diff_lines(left: &[String], right: &[String]) -> Vec<Block> {
+    let deadline = Instant::now() + DIFF_TIMEOUT;
+    let ops = capture_diff_slices_deadline(Algorithm::Myers, left, right, Some(deadline));
+
+    let mut blocks = Vec::new();
+    for op in ops {
+        let (old, new) = (op.old_range(), op.new_range());
+        if op.tag() == DiffTag::Equal {
+            let pairs = numbered(left, old).into_iter().zip(numbered(right, new));
+            blocks.push(Block::Same(pairs.collect()));
+        } else if let Some(Block::Change { removed, added }) = blocks.last_mut() {
+            removed.extend(numbered(left, old));
+            added.extend(numbered(right, new));
+        } else {
+            blocks.push(Block::Change {
+                removed: numbered(left, old),
+                added: numbered(right, new),
+            });
+        }
+    }
+    blocks
+}
+
+/// Hex dump diff aligned by offset: row `i` on both sides covers the same bytes
+fn diff_hex(left: &[u8], right: &[u8]) -> Vec<Block> {
+    let left_chunks: Vec<&[u8]> = left.chunks(HEX_BYTES_PER_LINE).collect();
+    let right_chunks: Vec<&[u8]> = right.chunks(HEX_BYTES_PER_LINE).collect();
+    let hex_line = |row: usize, chunk: &[u8]| DiffLine {
+        number: row + 1,
+        text: hex_dump_line(row * HEX_BYTES_PER_LINE, chunk),
+    };
+
+    let mut blocks = Vec::new();
+    for row in 0..left_chunks.len().max(right_chunks.len()) {
+        let l = left_chunks.get(row).map(|c| hex_line(row, c));
+        let r = right_chunks.get(row).map(|c| hex_line(row, c));
+        let same = left_chunks.get(row) == right_chunks.get(row);
+        match (blocks.last_mut(), same) {
+            (Some(Block::Same(pairs)), true) => pairs.extend(l.zip(r)),
+            (_, true) => blocks.push(Block::Same(l.into_iter().zip(r).collect())),
+            (Some(Block::Change { removed, added }), false) => {
+                removed.extend(l);
+                added.extend(r);
+            }
+            (_, false) => blocks.push(Block::Change {
+                removed: l.into_iter().collect(),
+                added: r.into_iter().collect(),
+            }),
+        }
+    }
+    blocks
+}
+
+/// Side-by-side rows, pairing removed and added lines of a change in order.
+/// Returns the rows and the row index where each hunk starts.
+fn side_by_side(blocks: Vec<Block>) -> (Vec<SideRow>, Vec<usize>) {
+    let mut rows = Vec::new();
+    let mut hunks = Vec::new();
+    for block in blocks {
+        match block {
+            Block::Same(pairs) => rows.extend(pairs.into_iter().map(|(l, r)| SideRow {
+                kind: RowKind::Same,
+                left: Some(l),
+                right: Some(r),
+            })),
+            Block::Change { removed, added } => {
+                hunks.push(rows.len());
+                let mut removed = removed.into_iter();
+                let mut added = added.into_iter();
+                loop {
+                    let (left, right) = (removed.next(), added.next());
+                    let kind = match (&left, &right) {
+                        (Some(_), Some(_)) => RowKind::Changed,
+                        (Some(_), None) => RowKind::Removed,
+                        (None, Some(_)) => RowKind::Added,
+                        (None, None) => break,
+                    };
+                    rows.push(SideRow { kind, left, right });
+                }
+            }
+        }
+    }
+    (rows, hunks)
+}
+
+/// Unified rows: each change lists its removed lines, then its added lines
+fn unified(blocks: &[Block]) -> (Vec<UnifiedRow>, Vec<usize>) {
+    let mut rows = Vec::new();
+    let mut hunks = Vec::new();
+    for block in blocks {
+        match block {
+            Block::Same(pairs) => rows.extend(pairs.iter().map(|(l, r)| UnifiedRow {
+                kind: RowKind::Same,
+                left_number: Some(l.number),
+                right_number: Some(r.number),
+                text: l.text.clone(),
+            })),
+            Block::Change { removed, added } => {
+                hunks.push(rows.len());
+                rows.extend(removed.iter().map(|l| UnifiedRow {
+                    kind: RowKind::Removed,
+                    left_number: Some(l.number),
+                    right_number: None,
+                    text: l.text.clone(),
+                }));
+                rows.extend(added.iter().map(|r| UnifiedRow {
+                    kind: RowKind::Added,
+                    left_number: None,
+                    right_number: Some(r.number),
+                    text: r.text.clone(),
+                }));
+            }
+        }
+    }
+    (rows, hunks)
+}
+
+fn read_for_diff(path: &Path) -> Result<Vec<u8>, String> {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let metadata = std::fs::metadata(path).map_err(|e| format!("{}: {}", name, e))?;
+    if metadata.len() > MAX_DIFF_FILE_SIZE {
+        return Err(format!(
+            "{} is too large to diff ({} bytes)",
+            name,
+            metadata.len()
+        ));
+    }
+    std::fs::read(path).map_err(|e| format!("{}: {}", name, e))
+}
+
+fn text_lines(bytes: &[u8]) -> Vec<String> {
+    String::from_utf8_lossy(bytes)
+        .lines()
+        .map(|s| s.to_owned())
+        .collect()
+}
+
+/// State for the diff viewer
+#[derive(Clone)]
+pub struct DiffViewer {
+    pub left_path: PathBuf,
+    pub right_path: PathBuf,
+    /// Compared as hex dumps because at least one file is binary
+    pub binary: bool,
+    pub layout: DiffLayout,
+    pub side_rows: Vec<SideRow>,
+    pub unified_rows: Vec<UnifiedRow>,
+    side_hunks: Vec<usize>,
+    unified_hunks: Vec<usize>,
+    pub scroll_offset: usize,
+    pub error: Option<String>,
+}
+
+impl DiffViewer {
+    pub fn new(left_path: PathBuf, right_path: PathBuf) -> Self {
+        let mut viewer = Self {
+            left_path,
+            right_path,
+            binary: false,
+            layout: DiffLayout::default(),
+            side_rows: Vec::new(),
+            unified_rows: Vec::new(),
+            side_hunks: Vec::new(),
+            unified_hunks: Vec::new(),
+            scroll_offset: 0,
+            error: None,
+        };
+        match (
+            read_for_diff(&viewer.left_path),
+            read_for_diff(&viewer.right_path),
+        ) {
+            (Ok(left), Ok(right)) => viewer.load(&left, &right),
+            (Err(e), _) | (_, Err(e)) => viewer.error = Some(e),
+        }
+        viewer
+    }
+
+    fn load(&mut self, left: &[u8], right: &[u8]) {
+        self.binary = !is_likely_text(left) || !is_likely_text(right);
+        let blocks = if self.binary {
+            diff_hex(left, right)
+        } else {
+            diff_lines(&text_lines(left), &text_lines(right))
+        };
+        (self.unified_rows, self.unified_hunks) = unified(&blocks);
+        (self.side_rows, self.side_hunks) = side_by_side(blocks);
+    }
+
+    fn hunks(&self) -> &[usize] {
+        match self.layout {
+            DiffLayout::SideBySide => &self.side_hunks,
+            DiffLayout::Unified => &self.unified_hunks,
+        }
+    }
+
+    pub fn hunk_count(&self) -> usize {
+        self.side_hunks.len()
+    }
+
+    /// Index of the last hunk at or above the top of the screen (counting
+    /// the context lines kept above a hunk jumped to)
+    pub fn current_hunk(&self) -> Option<usize> {
+        let offset = self.scroll_offset;
+        self.hunks()
+            .iter()
+            .rposition(|&start| start.saturating_sub(HUNK_CONTEXT_LINES) <= offset)
+    }
+
+    fn scroll_to_hunk(&mut self, idx: usize) {
+        if let Some(&start) = self.hunks().get(idx) {
+            self.scroll_offset = start.saturating_sub(HUNK_CONTEXT_LINES);
+        }
+    }
+
+    pub fn next_hunk(&mut self) {
+        let offset = self.scroll_offset;
+        if let Some(idx) = self
+            .hunks()
+            .iter()
+            .position(|&start| start.saturating_sub(HUNK_CONTEXT_LINES) > offset)
+        {
+            self.scroll_to_hunk(idx);
+        }
+    }
+
+    pub fn prev_hunk(&mut self) {
+        let offset = self.scroll_offset;
+        if let Some(idx) = self
+            .hunks()
+            .iter()
+            .rposition(|&start| start.saturating_sub(HUNK_CONTEXT_LINES) < offset)
+        {
+            self.scroll_to_hunk(idx);
+        }
+    }
+
+    /// Switch between side-by-side and unified, staying at the same hunk
+    pub fn toggle_layout(&mut self) {
+        let hunk = self.current_hunk();
+        self.layout = match self.layout {
+            DiffLayout::SideBySide => DiffLayout::Unified,
+            DiffLayout::Unified => DiffLayout::SideBySide,
+        };
+        match hunk {
+            Some(idx) => self.scroll_to_hunk(idx),
+            None => self.scroll_offset = 0,
+        }
+    }
+}
+
+impl Scrollable for DiffViewer {
+    fn line_count(&self) -> usize {
+        match self.layout {
+            DiffLayout::SideBySide => self.side_rows.len(),
+            DiffLayout::Unified => self.unified_rows.len(),
+        }
+    }
+
+    fn scroll_offset(&self) -> usize {
+        self.scroll_offset
+    }
+
+    fn set_scroll_offset(&mut self, offset: usize) {
+        self.scroll_offset = offset;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(str::to_owned).collect()
+    }
+
+    fn kinds(rows: &[SideRow]) -> Vec<RowKind> {
+        rows.iter().map(|r| r.kind).collect()
+    }
+
+    #[test]
+    fn side_by_side_aligns_changes_and_finds_hunks() {
+        let left = lines("a\nb\nc\nd\ne\n");
+        let right = lines("a\nB\nc\nd\nextra\ne\n");
+        let blocks = diff_lines(&left, &right);
+        let (unified_rows, unified_hunks) = unified(&blocks);
+        let (rows, hunks) = side_by_side(blocks);
+
+        use RowKind::*;
+        assert_eq!(kinds(&rows), [Same, Changed, Same, Same, Added, Same]);
+        assert_eq!(hunks, [1, 4]);
+        let changed = &rows[1];
+        assert_eq!(changed.left.as_ref().map(|l| l.text.as_str()), Some("b"));
+        assert_eq!(changed.right.as_ref().map(|r| r.text.as_str()), Some("B"));
+        assert_eq!(rows[4].right.as_ref().map(|r| r.number), Some(5));
+
+        let unified_kinds: Vec<RowKind> = unified_rows.iter().map(|r| r.kind).collect();
+        assert_eq!(
+            unified_kinds,
+            [Same, Removed, Added, Same, Same, Added, Same]
+        );
+        assert_eq!(unified_hunks, [1, 5]);
+    }
+
+    #[test]
+    fn hex_diff_is_aligned_by_offset() {
+        let left = vec![0u8; 40];
+        let mut right = left.clone();
+        right[20] = 0xff;
+        right.truncate(36);
+
+        let (rows, hunks) = side_by_side(diff_hex(&left, &right));
+        use RowKind::*;
+        // Rows of 16 bytes: the second differs, the third is shorter on the right
+        assert_eq!(kinds(&rows), [Same, Changed, Changed]);
+        assert_eq!(hunks, [1]);
+        assert!(
+            rows[1]
+                .right
+                .as_ref()
+                .is_some_and(|r| r.text.starts_with("00000010"))
+        );
+    }
+
+    #[test]
+    fn hunk_navigation_moves_between_changes() {
+        let left = lines(&"same\n".repeat(50));
+        let mut right = left.clone();
+        right[10] = "changed".to_owned();
+        right[40] = "changed".to_owned();
+
+        let mut viewer = DiffViewer {
+            left_path: PathBuf::new(),
+            right_path: PathBuf::new(),
+            binary: false,
+            layout: DiffLayout::default(),
+            side_rows: Vec::new(),
+            unified_rows: Vec::new(),
+            side_hunks: Vec::new(),
+            unified_hunks: Vec::new(),
+            scroll_offset: 0,
+            error: None,
+        };
+        viewer.load(left.join("\n").as_bytes(), right.join("\n").as_bytes());
+        assert_eq!(viewer.hunk_count(), 2);
+
+        viewer.next_hunk();
+        assert_eq!(viewer.scroll_offset, 10 - HUNK_CONTEXT_LINES);
+        viewer.next_hunk();
+        assert_eq!(viewer.scroll_offset, 40 - HUNK_CONTEXT_LINES);
+        viewer.next_hunk();
+        assert_eq!(viewer.scroll_offset, 40 - HUNK_CONTEXT_LINES);
+        viewer.prev_hunk();
+        assert_eq!(viewer.scroll_offset, 10 - HUNK_CONTEXT_LINES);
+
+        // Unified shows a removed and an added row per change
+        viewer.toggle_layout();
+        assert_eq!(viewer.line_count(), 52);
+        assert_eq!(viewer.scroll_offset, 10 - HUNK_CONTEXT_LINES);
+    }
+}
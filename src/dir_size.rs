@@ -0,0 +1,186 @@
+//! Recursive directory sizes with a shared cache
+//!
+//! Each directory is read once into a shallow summary: the total size of the
+//! regular files directly inside it, its hard-linked files and its
+//! subdirectories. Summaries are cached process-wide under the directory's
+//! (device, inode, mtime), which changes whenever an entry is added, removed
+//! or renamed, so recalculating a mostly unchanged tree only needs one stat
+//! per directory. A file growing in place does not touch its directory's
+//! mtime; such changes show up once the directory changes or the cache is
+//! cleared. Hard-linked files are counted once per calculation.
+
+use std::{
+    collections::{HashMap, HashSet},
+    os::unix::fs::MetadataExt,
+    path::{Path, PathBuf},
+    sync::{
+        Arc, LazyLock, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use crate::util::{SIZE_CACHE_MAX_DIRS, SIZE_PROGRESS_INTERVAL_MS};
+
+/// (device, inode, mtime seconds, mtime nanoseconds) of a directory
+type DirKey = (u64, u64, i64, i64);
+
+/// What a directory contains, not counting its subdirectories' contents
+struct Shallow {
+    /// Total size of regular files with a single link
+    files: u64,
+    /// (device, inode, size) of regular files with more than one link
+    links: Vec<(u64, u64, u64)>,
+    subdirs: Vec<PathBuf>,
+}
+
+static CACHE: LazyLock<Mutex<HashMap<DirKey, Arc<Shallow>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Read a directory's summary, or None if cancelled part way
+fn read_shallow(dir: &Path, cancel: &AtomicBool) -> Option<Shallow> {
+    let mut shallow = Shallow {
+        files: 0,
+        links: Vec::new(),
+        subdirs: Vec::new(),
+    };
+    let Ok(read_dir) = std::fs::read_dir(dir) else {
+        return Some(shallow);
+    };
+    for entry in read_dir.filter_map(|e| e.ok()) {
+        if cancel.load(Ordering::Relaxed) {
+            return None;
+        }
+        // Symlinks are neither followed nor counted
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            shallow.subdirs.push(entry.path());
+        } else if file_type.is_file()
+            && let Ok(metadata) = entry.metadata()
+        {
+            if metadata.nlink() > 1 {
+                shallow
+                    .links
+                    .push((metadata.dev(), metadata.ino(), metadata.len()));
+            } else {
+                shallow.files += metadata.len();
+            }
+        }
+    }
+    Some(shallow)
+}
+
+fn summary(dir: &Path, cancel: &AtomicBool) -> Option<Arc<Shallow>> {
+    let metadata = std::fs::symlink_metadata(dir).ok()?;
+    let key = (
+        metadata.dev(),
+        metadata.ino(),
+        metadata.mtime(),
+        metadata.mtime_nsec(),
+    );
+    if let Some(shallow) = CACHE.lock().ok()?.get(&key) {
+        return Some(Arc::clone(shallow));
+    }
+
+    let shallow = Arc::new(read_shallow(dir, cancel)?);
+    if let Ok(mut cache) = CACHE.lock() {
+        if cache.len() >= SIZE_CACHE_MAX_DIRS {
+            cache.clear();
+        }
+        cache.insert(key, Arc::clone(&shallow));
+    }
+    Some(shallow)
+}
+
+struct SizeWalk<'a, F> {
+    cancel: &'a AtomicBool,
+    /// (device, inode) of hard-linked files already counted
+    seen_links: HashSet<(u64, u64)>,
+    total: u64,
+    on_progress: F,
+    last_report: Instant,
+}
+
+impl<F: FnMut(u64)> SizeWalk<'_, F> {
+    fn walk(&mut self, dir: &Path) {
+        if self.cancel.load(Ordering::Relaxed) {
+            return;
+        }
+        let Some(shallow) = summary(dir, self.cancel) else {
+            return;
+        };
+        self.total += shallow.files;
+        for &(dev, ino, size) in &shallow.links {
+            if self.seen_links.insert((dev, ino)) {
+                self.total += size;
+            }
+        }
+        if self.last_report.elapsed() >= Duration::from_millis(SIZE_PROGRESS_INTERVAL_MS) {
+            (self.on_progress)(self.total);
+            self.last_report = Instant::now();
+        }
+        for subdir in &shallow.subdirs {
+            self.walk(subdir);
+        }
+    }
+}
+
+/// Total size of the regular files under `path`, reporting the running total
+/// to `on_progress` every so often. Stops early (with a partial total) once
+/// `cancel` is set.
+pub fn dir_size_with_progress(
+    path: &Path,
+    cancel: &AtomicBool,
+    on_progress: impl FnMut(u64),
+) -> u64 {
+    let mut walk = SizeWalk {
+        cancel,
+        seen_links: HashSet::new(),
+        total: 0,
+        on_progress,
+        last_report: Instant::now(),
+    };
+    walk.walk(path);
+    walk.total
+}
+
+/// Total size of the regular files under `path`. Stops early (with a partial
+/// total) once `cancel` is set.
+pub fn calculate_dir_size(path: &Path, cancel: &AtomicBool) -> u64 {
+    dir_size_with_progress(path, cancel, |_| {})
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+
+    use crate::test_util::unique_temp_dir;
+
+    #[test]
+    fn counts_hard_links_once_and_notices_changed_directories() {
+        let root = unique_temp_dir("dir-size");
+        fs::create_dir_all(root.join("a/b")).unwrap();
+        fs::write(root.join("a/one.bin"), vec![0u8; 100]).unwrap();
+        fs::write(root.join("a/b/two.bin"), vec![0u8; 20]).unwrap();
+        fs::hard_link(root.join("a/one.bin"), root.join("a/b/one-link.bin")).unwrap();
+        std::os::unix::fs::symlink(root.join("a"), root.join("loop")).unwrap();
+
+        let no_cancel = AtomicBool::new(false);
+        assert_eq!(calculate_dir_size(&root, &no_cancel), 120);
+        // The second calculation comes from the cache
+        assert_eq!(calculate_dir_size(&root, &no_cancel), 120);
+
+        fs::write(root.join("a/b/three.bin"), vec![0u8; 3]).unwrap();
+        assert_eq!(calculate_dir_size(&root, &no_cancel), 123);
+        assert_eq!(calculate_dir_size(&root.join("a/b"), &no_cancel), 123);
+
+        let cancelled = AtomicBool::new(true);
+        assert_eq!(calculate_dir_size(&root, &cancelled), 0);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
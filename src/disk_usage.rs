@@ -0,0 +1,431 @@
+//! Disk usage analyzer (ncdu-style)
+//!
+//! A background thread scans a directory tree into nested `DuNode`s holding
+//! the apparent and allocated size and file count of every entry. The view
+//! shows one directory at a time, largest entries first, and lets the user
+//! drill down, go back up and delete entries through the normal delete job.
+//! Like `du -x`, the scan stays on the filesystem it starts on and counts
+//! hard-linked files once.
+
+use std::{
+    collections::HashSet,
+    os::unix::fs::MetadataExt,
+    path::{Path, PathBuf},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        mpsc::{self, Receiver},
+    },
+    thread,
+};
+
+pub struct DuNode {
+    pub name: String,
+    pub is_dir: bool,
+    /// Sum of file lengths
+    pub apparent: u64,
+    /// Space actually allocated on disk
+    pub allocated: u64,
+    /// Number of files (non-directories) inside, or 1 for a file
+    pub files: u64,
+    /// The directory (or part of it) could not be read
+    pub error: bool,
+    pub children: Vec<DuNode>,
+}
+
+impl DuNode {
+    pub fn size(&self, allocated: bool) -> u64 {
+        if allocated {
+            self.allocated
+        } else {
+            self.apparent
+        }
+    }
+
+    /// Sort children (recursively) by size, largest first
+    fn sort(&mut self, allocated: bool) {
+        self.children.sort_by(|a, b| {
+            b.size(allocated)
+                .cmp(&a.size(allocated))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        for child in &mut self.children {
+            child.sort(allocated);
+        }
+    }
+}
+
+struct Scanner<'a> {
+    device: u64,
+    /// (device, inode) of hard-linked files already counted
+    seen_links: HashSet<(u64, u64)>,
+    scanned: &'a AtomicU64,
+    cancel: &'a AtomicBool,
+}
+
+impl Scanner<'_> {
+    fn scan(&mut self, path: &Path, name: String) -> DuNode {
+        let mut node = DuNode {
+            name,
+            is_dir: false,
+            apparent: 0,
+            allocated: 0,
+            files: 0,
+            error: false,
+            children: Vec::new(),
+        };
+        let Ok(metadata) = std::fs::symlink_metadata(path) else {
+            node.error = true;
+            return node;
+        };
+        self.scanned.fetch_add(1, Ordering::Relaxed);
+
+        let counted = metadata.is_dir()
+            || metadata.nlink() <= 1
+            || self.seen_links.insert((metadata.dev(), metadata.ino()));
+        if counted {
+            node.apparent = metadata.len();
+            node.allocated = metadata.blocks() * 512;
+        }
+        if !metadata.is_dir() {
+            node.files = 1;
+            return node;
+        }
+
+        node.is_dir = true;
+        // Mount points are shown, but not descended into
+        if metadata.dev() != self.device {
+            return node;
+        }
+        let Ok(read_dir) = std::fs::read_dir(path) else {
+            node.error = true;
+            return node;
+        };
+        for entry in read_dir {
+            if self.cancel.load(Ordering::Relaxed) {
+                break;
+            }
+            let Ok(entry) = entry else {
+                node.error = true;
+                continue;
+            };
+            let child = self.scan(
+                &entry.path(),
+                entry.file_name().to_string_lossy().into_owned(),
+            );
+            node.apparent += child.apparent;
+            node.allocated += child.allocated;
+            node.files += child.files;
+            node.error |= child.error;
+            node.children.push(child);
+        }
+        node
+    }
+}
+
+/// Scan `root` recursively, counting scanned entries in `scanned`
+pub fn scan_tree(root: &Path, scanned: &AtomicU64, cancel: &AtomicBool) -> DuNode {
+    let device = std::fs::symlink_metadata(root)
+        .map(|m| m.dev())
+        .unwrap_or(0);
+    let mut scanner = Scanner {
+        device,
+        seen_links: HashSet::new(),
+        scanned,
+        cancel,
+    };
+    scanner.scan(root, root.display().to_string())
+}
+
+pub struct DiskUsage {
+    pub root: PathBuf,
+    /// None while the scan is running
+    pub tree: Option<DuNode>,
+    /// Show allocated rather than apparent sizes
+    pub allocated: bool,
+    pub selected: usize,
+    /// Entry waiting for delete confirmation
+    pub deleting: Option<PathBuf>,
+    /// Child indices from the root down to the directory being viewed
+    stack: Vec<usize>,
+    scanned: Arc<AtomicU64>,
+    rx: Option<Receiver<DuNode>>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl DiskUsage {
+    pub fn start(root: PathBuf) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let scanned = Arc::new(AtomicU64::new(0));
+        let cancel = Arc::new(AtomicBool::new(false));
+
+        let worker_scanned = Arc::clone(&scanned);
+        let worker_cancel = Arc::clone(&cancel);
+        let worker_root = root.clone();
+        thread::spawn(move || {
+            let tree = scan_tree(&worker_root, &worker_scanned, &worker_cancel);
+            let _ = tx.send(tree);
+        });
+
+        Self {
+            root,
+            tree: None,
+            allocated: false,
+            selected: 0,
+            deleting: None,
+            stack: Vec::new(),
+            scanned,
+            rx: Some(rx),
+            cancel,
+        }
+    }
+
+    /// Returns true when the scan has just finished
+    pub fn poll(&mut self) -> bool {
+        let Some(rx) = &self.rx else {
+            return false;
+        };
+        match rx.try_recv() {
+            Ok(mut tree) => {
+                tree.sort(self.allocated);
+                self.tree = Some(tree);
+                self.rx = None;
+                true
+            }
+            Err(mpsc::TryRecvError::Empty) => false,
+            Err(mpsc::TryRecvError::Disconnected) => {
+                self.rx = None;
+                false
+            }
+        }
+    }
+
+    pub fn is_scanning(&self) -> bool {
+        self.rx.is_some()
+    }
+
+    pub fn scanned_count(&self) -> u64 {
+        self.scanned.load(Ordering::Relaxed)
+    }
+
+    /// Directory being viewed
+    pub fn current(&self) -> Option<&DuNode> {
+        let mut node = self.tree.as_ref()?;
+        for &idx in &self.stack {
+            node = node.children.get(idx)?;
+        }
+        Some(node)
+    }
+
+    pub fn current_path(&self) -> PathBuf {
+        let mut path = self.root.clone();
+        let mut node = self.tree.as_ref();
+        for &idx in &self.stack {
+            node = node.and_then(|n| n.children.get(idx));
+            if let Some(n) = node {
+                path.push(&n.name);
+            }
+        }
+        path
+    }
+
+    pub fn selected_node(&self) -> Option<&DuNode> {
+        self.current()?.children.get(self.selected)
+    }
+
+    pub fn selected_path(&self) -> Option<PathBuf> {
+        let node = self.selected_node()?;
+        Some(self.current_path().join(&node.name))
+    }
+
+    pub fn move_by(&mut self, delta: isize) {
+        let count = self.current().map_or(0, |n| n.children.len());
+        let last = count.saturating_sub(1) as isize;
+        self.selected = (self.selected as isize + delta).clamp(0, last) as usize;
+    }
+
+    pub fn move_to_end(&mut self) {
+        self.move_by(isize::MAX / 2);
+    }
+
+    /// Drill into the selected directory
+    pub fn enter(&mut self) {
+        if self.selected_node().is_some_and(|n| n.is_dir) {
+            self.stack.push(self.selected);
+            self.selected = 0;
+        }
+    }
+
+    /// Go back up, with the cursor on the directory just left. Returns false
+    /// at the top of the scan.
+    pub fn leave(&mut self) -> bool {
+        match self.stack.pop() {
+            Some(idx) => {
+                self.selected = idx;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Switch between apparent and allocated sizes, re-sorting by the new
+    /// size while staying on the same entries
+    pub fn toggle_size_kind(&mut self) {
+        self.allocated = !self.allocated;
+        let names = self.cursor_names();
+        if let Some(tree) = &mut self.tree {
+            tree.sort(self.allocated);
+        }
+        self.restore_cursor(&names);
+    }
+
+    /// Names from the root down to the cursor entry, to find the viewed
+    /// directory and cursor again after indices change
+    fn cursor_names(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        let mut node = self.tree.as_ref();
+        for &idx in self.stack.iter().chain(Some(&self.selected)) {
+            let Some(child) = node.and_then(|n| n.children.get(idx)) else {
+                break;
+            };
+            names.push(child.name.clone());
+            node = Some(child);
+        }
+        names
+    }
+
+    /// Go back to the entries named by `cursor_names`. If the cursor entry is
+    /// gone, the cursor stays on the same row of its directory; if a
+    /// directory on the way is gone, the view moves up to its parent.
+    fn restore_cursor(&mut self, names: &[String]) {
+        let Some(tree) = &self.tree else {
+            return;
+        };
+        let mut indices = Vec::new();
+        let mut node = tree;
+        for name in names {
+            let Some(idx) = node.children.iter().position(|c| &c.name == name) else {
+                break;
+            };
+            indices.push(idx);
+            node = &node.children[idx];
+        }
+        if indices.len() == names.len() && !names.is_empty() {
+            self.selected = indices.pop().unwrap_or(0);
+        } else if indices.len() + 1 < names.len() {
+            self.selected = 0;
+        }
+        self.stack = indices;
+        self.move_by(0);
+    }
+
+    /// Drop a deleted entry from the tree, subtracting its size from every
+    /// directory above it
+    pub fn remove_path(&mut self, path: &Path) {
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return;
+        };
+        let names = self.cursor_names();
+        let Some(tree) = &mut self.tree else {
+            return;
+        };
+        let mut indices = Vec::new();
+        let mut node = &*tree;
+        for name in relative.iter() {
+            let Some(idx) = node.children.iter().position(|c| *c.name == *name) else {
+                return;
+            };
+            indices.push(idx);
+            node = &node.children[idx];
+        }
+        remove_at(tree, &indices);
+        self.restore_cursor(&names);
+    }
+}
+
+/// Remove the node reached by following the child indices in `path`,
+/// updating the totals of `node` and the directories in between
+fn remove_at(node: &mut DuNode, path: &[usize]) -> Option<DuNode> {
+    let (&idx, rest) = path.split_first()?;
+    let removed = if rest.is_empty() {
+        (idx < node.children.len()).then(|| node.children.remove(idx))?
+    } else {
+        remove_at(node.children.get_mut(idx)?, rest)?
+    };
+    node.apparent = node.apparent.saturating_sub(removed.apparent);
+    node.allocated = node.allocated.saturating_sub(removed.allocated);
+    node.files = node.files.saturating_sub(removed.files);
+    Some(removed)
+}
+
+impl Drop for DiskUsage {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{fs, time::Duration};
+
+    use crate::test_util::unique_temp_dir;
+
+    #[test]
+    fn scans_sorts_drills_down_and_removes() {
+        let root = unique_temp_dir("disk-usage");
+        fs::create_dir_all(root.join("big/nested")).unwrap();
+        fs::create_dir_all(root.join("small")).unwrap();
+        fs::write(root.join("big/nested/a.bin"), vec![0u8; 5000]).unwrap();
+        fs::write(root.join("big/b.bin"), vec![0u8; 3000]).unwrap();
+        fs::write(root.join("small/c.txt"), "tiny").unwrap();
+        fs::write(root.join("loose.txt"), vec![0u8; 50_000]).unwrap();
+        // A hard link is only counted once
+        fs::hard_link(root.join("big/b.bin"), root.join("small/b-link.bin")).unwrap();
+
+        let mut du = DiskUsage::start(root.clone());
+        for _ in 0..200 {
+            if du.poll() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        let tree = du.tree.as_ref().expect("scan finished");
+        assert_eq!(tree.files, 5);
+        let names: Vec<&str> = tree.children.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["loose.txt", "big", "small"]);
+        assert_eq!(tree.children[1].files, 2);
+        let total = tree.apparent;
+
+        // Files can't be entered
+        du.enter();
+        assert_eq!(du.current_path(), root);
+        du.move_by(1);
+        du.enter();
+        assert_eq!(du.current_path(), root.join("big"));
+        assert_eq!(du.selected_path(), Some(root.join("big/nested")));
+        let nested = du.selected_node().map_or(0, |n| n.apparent);
+        du.remove_path(&root.join("big/nested"));
+        assert_eq!(du.selected_path(), Some(root.join("big/b.bin")));
+        assert!(du.leave());
+        assert!(!du.leave());
+        let tree = du.tree.as_ref().unwrap();
+        assert_eq!(tree.files, 4);
+        assert_eq!(tree.apparent, total - nested);
+
+        // Switching size kinds keeps the cursor on the same entry
+        du.move_to_end();
+        du.toggle_size_kind();
+        assert!(du.allocated);
+        assert_eq!(du.selected_node().map(|n| n.name.as_str()), Some("small"));
+
+        // Removing the directory being viewed moves the view up
+        du.enter();
+        du.remove_path(&root.join("small"));
+        assert_eq!(du.current_path(), root);
+        assert_eq!(du.tree.as_ref().unwrap().children.len(), 2);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,203 @@
+//! Extracting whole archives
+//!
+//! The archive under the cursor is unpacked into the other pane, or into a
+//! new subdirectory there named after the archive. Member names never
+//! contain `..` (see `archive::normalize_name`), but a member could still
+//! reach outside the target through a symlink, either one created by an
+//! earlier member or one already on disk. Extraction therefore never writes
+//! below a symlink and never creates a symlink pointing outside the target.
+//! Symlinks may point through other symlinks the archive created, so a link
+//! target is only accepted in a form that can't climb out of a directory it
+//! reached through one of them.
+
+use std::{
+    collections::HashSet,
+    path::{Component, Path, PathBuf},
+};
+
+pub const FOCUS_DEST: usize = 0;
+pub const FOCUS_SUBDIR: usize = 1;
+const FIELD_COUNT: usize = 2;
+
+/// Archive suffixes, longest first, stripped to name the subdirectory
+const ARCHIVE_SUFFIXES: [&str; 10] = [
+    ".tar.gz", ".tar.xz", ".tar.zst", ".tgz", ".txz", ".tzst", ".tar", ".zip", ".jar", ".7z",
+];
+
+/// File name of `archive` without its archive suffix
+pub fn archive_stem(archive: &Path) -> String {
+    let name = archive
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned();
+    let lower = name.to_lowercase();
+    ARCHIVE_SUFFIXES
+        .iter()
+        .find(|suffix| lower.ends_with(*suffix) && lower.len() > suffix.len())
+        .map_or(name.clone(), |suffix| {
+            name[..name.len() - suffix.len()].to_string()
+        })
+}
+
+/// Where the member at `relative` (a `/`-separated path) goes below `root`,
+/// or None if it would not stay inside: the path is not plain relative, or
+/// one of its parent directories is a symlink.
+pub fn safe_target(root: &Path, relative: &str) -> Option<PathBuf> {
+    let relative = Path::new(relative);
+    if !relative
+        .components()
+        .all(|c| matches!(c, Component::Normal(_)))
+    {
+        return None;
+    }
+    let mut dir = root.to_path_buf();
+    for component in relative.parent()?.components() {
+        dir.push(component);
+        if std::fs::symlink_metadata(&dir).is_ok_and(|m| m.file_type().is_symlink()) {
+            return None;
+        }
+    }
+    Some(root.join(relative))
+}
+
+/// Whether a symlink at `link` (relative to the extraction root `root`)
+/// pointing at `target` could resolve outside the root. The target may only
+/// climb with leading `..` components, along the link's real parent
+/// directories, and then descend: after descending into what may be a
+/// symlink, `..` would climb from wherever that symlink points. Descending
+/// through a symlink extraction did not create (one not in `created`) is
+/// refused as well, since it may point anywhere.
+pub fn link_escapes(root: &Path, link: &str, target: &str, created: &HashSet<PathBuf>) -> bool {
+    if target.starts_with('/') {
+        return true;
+    }
+    let mut dir = root.join(link);
+    dir.pop();
+    let mut depth = link.split('/').count() - 1;
+    let mut descended = false;
+    for part in target.split('/') {
+        match part {
+            "" | "." => {}
+            ".." if descended || depth == 0 => return true,
+            ".." => {
+                depth -= 1;
+                dir.pop();
+            }
+            _ => {
+                descended = true;
+                dir.push(part);
+                if std::fs::symlink_metadata(&dir).is_ok_and(|m| m.is_symlink())
+                    && !created.contains(&dir)
+                {
+                    return true;
+                }
+            }
+        }
+    }
+    false
+}
+
+#[derive(Clone)]
+pub struct ExtractForm {
+    pub archive: PathBuf,
+    /// Target directory; relative paths are taken from `base_dir`
+    pub dest: String,
+    /// Extract into a new subdirectory named after the archive
+    pub subdir: bool,
+    pub focus: usize,
+    base_dir: PathBuf,
+}
+
+impl ExtractForm {
+    pub fn new(archive: PathBuf, dest_dir: &Path) -> Self {
+        Self {
+            archive,
+            dest: dest_dir.to_string_lossy().into_owned(),
+            subdir: true,
+            focus: FOCUS_DEST,
+            base_dir: dest_dir.to_path_buf(),
+        }
+    }
+
+    pub fn subdir_name(&self) -> String {
+        archive_stem(&self.archive)
+    }
+
+    pub fn focused_text_mut(&mut self) -> Option<&mut String> {
+        (self.focus == FOCUS_DEST).then_some(&mut self.dest)
+    }
+
+    pub fn focus_next(&mut self) {
+        self.focus = (self.focus + 1) % FIELD_COUNT;
+    }
+
+    pub fn focus_prev(&mut self) {
+        self.focus = (self.focus + FIELD_COUNT - 1) % FIELD_COUNT;
+    }
+
+    /// Directory the archive's contents go into
+    pub fn compile(&self) -> Result<PathBuf, String> {
+        if self.dest.is_empty() {
+            return Err("Target directory is empty".to_string());
+        }
+        let mut dest = self.base_dir.join(&self.dest);
+        if self.subdir {
+            dest.push(self.subdir_name());
+        }
+        if dest.exists() && !dest.is_dir() {
+            return Err(format!("{} is not a directory", dest.display()));
+        }
+        Ok(dest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_util::unique_temp_dir;
+
+    #[test]
+    fn targets_and_links_stay_inside_the_root() {
+        let root = unique_temp_dir("extract-safe");
+        std::fs::create_dir_all(root.join("real")).unwrap();
+        std::os::unix::fs::symlink("/tmp", root.join("out")).unwrap();
+
+        assert_eq!(
+            safe_target(&root, "real/file"),
+            Some(root.join("real/file"))
+        );
+        // The symlink itself may be replaced, but not written through
+        assert_eq!(safe_target(&root, "out"), Some(root.join("out")));
+        assert_eq!(safe_target(&root, "out/file"), None);
+        assert_eq!(safe_target(&root, "/etc/passwd"), None);
+        assert_eq!(safe_target(&root, "a/../../b"), None);
+
+        let mut created = HashSet::new();
+        let escapes =
+            |link, target, created: &HashSet<PathBuf>| link_escapes(&root, link, target, created);
+        assert!(!escapes("lib.so", "lib.so.1", &created));
+        assert!(!escapes("a/b/link", "../../c", &created));
+        assert!(escapes("a/b/link", "../../../c", &created));
+        assert!(escapes("link", "/etc", &created));
+        assert!(escapes("a/link", "b/../../..", &created));
+        // A symlink that was already there may point anywhere
+        assert!(escapes("link", "out/file", &created));
+
+        // a/b/l1 -> ../.. is the root itself, so a/b/l1/.. is above it
+        std::fs::create_dir_all(root.join("real/b")).unwrap();
+        assert!(!escapes("real/b/l1", "../..", &created));
+        std::os::unix::fs::symlink("../..", root.join("real/b/l1")).unwrap();
+        created.insert(root.join("real/b/l1"));
+        assert!(escapes("l2", "real/b/l1/..", &created));
+        assert!(escapes("l2", "real/b/l1/../x", &created));
+        assert!(!escapes("l2", "real/b/l1/real", &created));
+
+        assert_eq!(archive_stem(Path::new("/x/site.Tar.GZ")), "site");
+        assert_eq!(archive_stem(Path::new("/x/v1.2.zip")), "v1.2");
+        assert_eq!(archive_stem(Path::new("/x/.tar")), ".tar");
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
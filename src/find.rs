@@ -0,0 +1,501 @@
+//! Recursive Find File search
+//!
+//! The dialog edits a `FindForm`; starting a search compiles it into a
+//! `FindQuery` and walks the tree in a background thread, streaming matches
+//! back over a channel so the results list fills in while the search runs.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver},
+    },
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use regex::{Regex, RegexBuilder, bytes};
+use walkdir::WalkDir;
+
+use crate::{
+    grep::search_file,
+    util::{expand_tilde, glob_match, parse_size},
+};
+
+/// Send a progress update every this many visited entries
+const PROGRESS_INTERVAL: usize = 256;
+
+/// Labels of the dialog fields, in focus order. The first `TEXT_FIELD_COUNT`
+/// are text inputs, the rest are checkboxes.
+pub const FIELD_LABELS: [&str; 9] = [
+    "Start at:",
+    "File name:",
+    "Content (regex):",
+    "Size (+10M / -1K):",
+    "Modified days (-7 / +30):",
+    "Name is regex",
+    "Case sensitive",
+    "Follow symlinks",
+    "Hidden files",
+];
+pub const TEXT_FIELD_COUNT: usize = 5;
+
+/// Raw contents of the Find File dialog
+#[derive(Clone)]
+pub struct FindForm {
+    pub start_dir: String,
+    pub name: String,
+    pub content: String,
+    pub size: String,
+    pub modified: String,
+    pub name_is_regex: bool,
+    pub case_sensitive: bool,
+    pub follow_symlinks: bool,
+    pub include_hidden: bool,
+    /// Index into `FIELD_LABELS` of the focused field
+    pub focus: usize,
+}
+
+impl Default for FindForm {
+    fn default() -> Self {
+        Self {
+            start_dir: ".".to_owned(),
+            name: "*".to_owned(),
+            content: String::new(),
+            size: String::new(),
+            modified: String::new(),
+            name_is_regex: false,
+            case_sensitive: false,
+            follow_symlinks: false,
+            include_hidden: false,
+            focus: 1,
+        }
+    }
+}
+
+impl FindForm {
+    /// The focused text input, or None if a checkbox has focus
+    pub fn focused_text_mut(&mut self) -> Option<&mut String> {
+        match self.focus {
+            0 => Some(&mut self.start_dir),
+            1 => Some(&mut self.name),
+            2 => Some(&mut self.content),
+            3 => Some(&mut self.size),
+            4 => Some(&mut self.modified),
+            _ => None,
+        }
+    }
+
+    pub fn text(&self, idx: usize) -> &str {
+        match idx {
+            0 => &self.start_dir,
+            1 => &self.name,
+            2 => &self.content,
+            3 => &self.size,
+            4 => &self.modified,
+            _ => "",
+        }
+    }
+
+    pub fn checked(&self, idx: usize) -> bool {
+        match idx {
+            5 => self.name_is_regex,
+            6 => self.case_sensitive,
+            7 => self.follow_symlinks,
+            8 => self.include_hidden,
+            _ => false,
+        }
+    }
+
+    /// Flip the focused checkbox
+    pub fn toggle_focused(&mut self) {
+        let flag = match self.focus {
+            5 => &mut self.name_is_regex,
+            6 => &mut self.case_sensitive,
+            7 => &mut self.follow_symlinks,
+            8 => &mut self.include_hidden,
+            _ => return,
+        };
+        *flag = !*flag;
+    }
+
+    pub fn focus_next(&mut self) {
+        self.focus = (self.focus + 1) % FIELD_LABELS.len();
+    }
+
+    pub fn focus_prev(&mut self) {
+        self.focus = (self.focus + FIELD_LABELS.len() - 1) % FIELD_LABELS.len();
+    }
+
+    /// Validate the form and build a query. Relative start directories are
+    /// resolved against `base` (the active pane's directory).
+    pub fn compile(&self, base: &Path) -> Result<FindQuery, String> {
+        let start_dir = match self.start_dir.trim() {
+            "" => base.to_path_buf(),
+            dir => base.join(expand_tilde(dir)),
+        };
+        let start_dir = start_dir.canonicalize().unwrap_or(start_dir);
+        if !start_dir.is_dir() {
+            return Err(format!("Not a directory: {}", start_dir.display()));
+        }
+
+        let name = self.name.trim();
+        let name = if self.name_is_regex {
+            let regex = RegexBuilder::new(name)
+                .case_insensitive(!self.case_sensitive)
+                .build()
+                .map_err(|e| format!("Invalid name regex: {}", e))?;
+            NameMatcher::Regex(regex)
+        } else {
+            NameMatcher::Glob {
+                pattern: if name.is_empty() { "*" } else { name }.to_owned(),
+                case_sensitive: self.case_sensitive,
+            }
+        };
+
+        let content = match self.content.as_str() {
+            "" => None,
+            pattern => Some(
+                bytes::RegexBuilder::new(pattern)
+                    .case_insensitive(!self.case_sensitive)
+                    .build()
+                    .map_err(|e| format!("Invalid content regex: {}", e))?,
+            ),
+        };
+
+        let size = parse_bound(&self.size, parse_size).map_err(|e| format!("Size: {}", e))?;
+        let modified = parse_bound(&self.modified, |s| s.parse::<u64>().ok())
+            .map_err(|e| format!("Modified: {}", e))?;
+
+        Ok(FindQuery {
+            start_dir,
+            name,
+            content,
+            size,
+            modified_days: modified,
+            follow_symlinks: self.follow_symlinks,
+            include_hidden: self.include_hidden,
+        })
+    }
+}
+
+/// A `+N` (at least) or `-N` (at most) filter
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Bound {
+    AtLeast(u64),
+    AtMost(u64),
+}
+
+impl Bound {
+    fn contains(self, value: u64) -> bool {
+        match self {
+            Bound::AtLeast(n) => value >= n,
+            Bound::AtMost(n) => value <= n,
+        }
+    }
+}
+
+fn parse_bound(input: &str, parse: impl Fn(&str) -> Option<u64>) -> Result<Option<Bound>, String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Ok(None);
+    }
+    let (ctor, rest): (fn(u64) -> Bound, _) = if let Some(rest) = input.strip_prefix('+') {
+        (Bound::AtLeast, rest)
+    } else if let Some(rest) = input.strip_prefix('-') {
+        (Bound::AtMost, rest)
+    } else {
+        return Err("use +N (at least) or -N (at most)".to_owned());
+    };
+    parse(rest)
+        .map(|n| Some(ctor(n)))
+        .ok_or_else(|| format!("invalid value '{}'", rest))
+}
+
+enum NameMatcher {
+    Glob {
+        pattern: String,
+        case_sensitive: bool,
+    },
+    Regex(Regex),
+}
+
+impl NameMatcher {
+    fn is_match(&self, name: &str) -> bool {
+        match self {
+            NameMatcher::Glob {
+                pattern,
+                case_sensitive,
+            } => glob_match(pattern, name, *case_sensitive),
+            NameMatcher::Regex(regex) => regex.is_match(name),
+        }
+    }
+}
+
+/// A validated search
+pub struct FindQuery {
+    pub start_dir: PathBuf,
+    name: NameMatcher,
+    content: Option<bytes::Regex>,
+    size: Option<Bound>,
+    modified_days: Option<Bound>,
+    follow_symlinks: bool,
+    include_hidden: bool,
+}
+
+impl FindQuery {
+    /// Directories can only match when no file-only filter is set
+    fn dirs_can_match(&self) -> bool {
+        self.content.is_none() && self.size.is_none()
+    }
+
+    fn matches(&self, entry: &walkdir::DirEntry, cancel: &AtomicBool) -> bool {
+        let name = entry.file_name().to_string_lossy();
+        if !self.name.is_match(&name) {
+            return false;
+        }
+
+        let is_dir = entry.file_type().is_dir();
+        if is_dir && !self.dirs_can_match() {
+            return false;
+        }
+
+        if self.size.is_some() || self.modified_days.is_some() {
+            let Ok(metadata) = entry.metadata() else {
+                return false;
+            };
+            if let Some(size) = self.size
+                && !size.contains(metadata.len())
+            {
+                return false;
+            }
+            if let Some(days) = self.modified_days {
+                let age_days = metadata
+                    .modified()
+                    .ok()
+                    .and_then(|m| SystemTime::now().duration_since(m).ok())
+                    .unwrap_or(Duration::ZERO)
+                    .as_secs()
+                    / (24 * 60 * 60);
+                if !days.contains(age_days) {
+                    return false;
+                }
+            }
+        }
+
+        match &self.content {
+            Some(regex) if !is_dir => file_contains(entry.path(), regex, cancel),
+            _ => true,
+        }
+    }
+
+    /// Walk the tree and call `on_match` for every matching path.
+    /// Returns the number of entries visited.
+    fn run(
+        &self,
+        cancel: &AtomicBool,
+        mut on_match: impl FnMut(PathBuf),
+        mut on_progress: impl FnMut(usize),
+    ) -> usize {
+        let include_hidden = self.include_hidden;
+        let walker = WalkDir::new(&self.start_dir)
+            .follow_links(self.follow_symlinks)
+            .min_depth(1)
+            .into_iter()
+            .filter_entry(move |e| {
+                include_hidden || !e.file_name().to_string_lossy().starts_with('.')
+            });
+
+        let mut scanned = 0;
+        for entry in walker.filter_map(|e| e.ok()) {
+            if cancel.load(Ordering::Relaxed) {
+                break;
+            }
+            scanned += 1;
+            if scanned % PROGRESS_INTERVAL == 0 {
+                on_progress(scanned);
+            }
+            if self.matches(&entry, cancel) {
+                on_match(entry.into_path());
+            }
+        }
+        scanned
+    }
+}
+
+/// Check whether a text file has a line matching `regex`. Binary files never match.
+fn file_contains(path: &Path, regex: &bytes::Regex, cancel: &AtomicBool) -> bool {
+    let mut found = false;
+    search_file(path, regex, cancel, |_, _| {
+        found = true;
+        false
+    });
+    found
+}
+
+enum FindUpdate {
+    Found(PathBuf),
+    Progress(usize),
+    Done(usize),
+}
+
+/// A running or finished search and the results received so far
+pub struct FindSearch {
+    /// The dialog contents that started this search, to pre-fill the next one
+    pub form: FindForm,
+    pub start_dir: PathBuf,
+    pub results: Vec<PathBuf>,
+    /// Entries visited so far
+    pub scanned: usize,
+    pub running: bool,
+    rx: Receiver<FindUpdate>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl FindSearch {
+    pub fn start(form: FindForm, query: FindQuery) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let start_dir = query.start_dir.clone();
+
+        let worker_cancel = Arc::clone(&cancel);
+        thread::spawn(move || {
+            let scanned = query.run(
+                &worker_cancel,
+                |path| {
+                    let _ = tx.send(FindUpdate::Found(path));
+                },
+                |scanned| {
+                    let _ = tx.send(FindUpdate::Progress(scanned));
+                },
+            );
+            let _ = tx.send(FindUpdate::Done(scanned));
+        });
+
+        Self {
+            form,
+            start_dir,
+            results: Vec::new(),
+            scanned: 0,
+            running: true,
+            rx,
+            cancel,
+        }
+    }
+
+    /// Collect results sent by the search thread since the last poll
+    pub fn poll(&mut self) {
+        loop {
+            match self.rx.try_recv() {
+                Ok(FindUpdate::Found(path)) => self.results.push(path),
+                Ok(FindUpdate::Progress(scanned)) => self.scanned = scanned,
+                Ok(FindUpdate::Done(scanned)) => {
+                    self.scanned = scanned;
+                    self.running = false;
+                }
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    self.running = false;
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Ask the search thread to stop; results found so far are kept
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Drop for FindSearch {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+
+    use crate::test_util::unique_temp_dir;
+
+    fn find(root: &Path, form: FindForm) -> Vec<String> {
+        let query = form.compile(root).unwrap();
+        let mut found = Vec::new();
+        query.run(&AtomicBool::new(false), |p| found.push(p), |_| {});
+        let mut names: Vec<String> = found
+            .iter()
+            .map(|p| p.strip_prefix(root).unwrap().display().to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn name_content_and_hidden_filters() {
+        let root = unique_temp_dir("find");
+        fs::create_dir_all(root.join("src/nested")).unwrap();
+        fs::create_dir_all(root.join(".git")).unwrap();
+        fs::write(root.join("src/main.rs"), "fn main() {\n    todo!()\n}\n").unwrap();
+        fs::write(root.join("src/nested/lib.rs"), "pub fn lib() {}\n").unwrap();
+        fs::write(root.join("src/notes.txt"), "TODO: write docs\n").unwrap();
+        fs::write(root.join("src/blob.rs"), b"todo\0binary").unwrap();
+        fs::write(root.join(".git/config.rs"), "todo").unwrap();
+
+        let by_name = FindForm {
+            name: "*.rs".to_owned(),
+            ..FindForm::default()
+        };
+        assert_eq!(
+            find(&root, by_name.clone()),
+            ["src/blob.rs", "src/main.rs", "src/nested/lib.rs"]
+        );
+
+        let with_hidden = FindForm {
+            include_hidden: true,
+            ..by_name.clone()
+        };
+        assert_eq!(find(&root, with_hidden).len(), 4);
+
+        // Case-insensitive content match; binary files are skipped
+        let by_content = FindForm {
+            name: "*".to_owned(),
+            content: "todo".to_owned(),
+            ..FindForm::default()
+        };
+        assert_eq!(find(&root, by_content), ["src/main.rs", "src/notes.txt"]);
+
+        let by_regex = FindForm {
+            name: r"^(main|lib)\.rs$".to_owned(),
+            name_is_regex: true,
+            ..FindForm::default()
+        };
+        assert_eq!(find(&root, by_regex), ["src/main.rs", "src/nested/lib.rs"]);
+
+        let by_size = FindForm {
+            size: "+20".to_owned(),
+            ..by_name
+        };
+        assert_eq!(find(&root, by_size), ["src/main.rs"]);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn bounds_need_a_sign() {
+        assert_eq!(
+            parse_bound("+10K", parse_size),
+            Ok(Some(Bound::AtLeast(10 * 1024)))
+        );
+        assert_eq!(
+            parse_bound("-7", |s| s.parse().ok()),
+            Ok(Some(Bound::AtMost(7)))
+        );
+        assert_eq!(parse_bound("", parse_size), Ok(None));
+        assert!(parse_bound("7", parse_size).is_err());
+    }
+}
@@ -0,0 +1,285 @@
+//! Ctrl+P fuzzy finder over the active pane's directory tree
+//!
+//! The tree is walked in a background thread that sends paths back in
+//! batches; the popup re-ranks everything received so far on each keystroke.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver},
+    },
+    thread,
+};
+
+use ignore::WalkBuilder;
+
+use crate::util::fuzzy_score;
+
+/// Paths are sent to the UI in batches of this size
+const WALK_BATCH_SIZE: usize = 512;
+
+/// Stop walking after this many paths so huge trees stay responsive
+const MAX_FINDER_PATHS: usize = 500_000;
+
+pub struct FinderItem {
+    /// Path relative to the finder root
+    pub rel_path: String,
+    pub is_dir: bool,
+}
+
+pub struct FuzzyFinder {
+    pub root: PathBuf,
+    pub query: String,
+    /// Skip files ignored by `.gitignore` and friends
+    pub respect_gitignore: bool,
+    show_hidden: bool,
+    pub items: Vec<FinderItem>,
+    /// Indices into `items` matching the query, best first
+    pub matches: Vec<usize>,
+    pub selected: usize,
+    pub walking: bool,
+    rx: Receiver<Vec<FinderItem>>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl FuzzyFinder {
+    pub fn new(root: PathBuf, show_hidden: bool, respect_gitignore: bool) -> Self {
+        let (rx, cancel) = start_walk(&root, show_hidden, respect_gitignore);
+        Self {
+            root,
+            query: String::new(),
+            respect_gitignore,
+            show_hidden,
+            items: Vec::new(),
+            matches: Vec::new(),
+            selected: 0,
+            walking: true,
+            rx,
+            cancel,
+        }
+    }
+
+    /// Take in paths found since the last poll and re-rank if there were any
+    pub fn poll(&mut self) {
+        let mut received = false;
+        loop {
+            match self.rx.try_recv() {
+                Ok(batch) => {
+                    self.items.extend(batch);
+                    received = true;
+                }
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    self.walking = false;
+                    break;
+                }
+            }
+        }
+        if received {
+            self.rank();
+        }
+    }
+
+    /// Recompute `matches` for the current query. The cursor goes back to the
+    /// best match only when the query changed.
+    fn rank(&mut self) {
+        if self.query.trim().is_empty() {
+            self.matches = (0..self.items.len()).collect();
+        } else {
+            let mut scored: Vec<(i64, usize)> = self
+                .items
+                .iter()
+                .enumerate()
+                .filter_map(|(i, item)| fuzzy_score(&self.query, &item.rel_path).map(|s| (s, i)))
+                .collect();
+            scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+            self.matches = scored.into_iter().map(|(_, i)| i).collect();
+        }
+        self.selected = self.selected.min(self.matches.len().saturating_sub(1));
+    }
+
+    pub fn set_query(&mut self, query: String) {
+        self.query = query;
+        self.selected = 0;
+        self.rank();
+    }
+
+    /// Toggle `.gitignore` handling and walk the tree again
+    pub fn toggle_gitignore(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+        self.respect_gitignore = !self.respect_gitignore;
+        let (rx, cancel) = start_walk(&self.root, self.show_hidden, self.respect_gitignore);
+        self.rx = rx;
+        self.cancel = cancel;
+        self.items.clear();
+        self.matches.clear();
+        self.selected = 0;
+        self.walking = true;
+    }
+
+    pub fn move_selection(&mut self, delta: isize) {
+        let max = self.matches.len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(delta).min(max);
+    }
+
+    /// Absolute path of the selected match
+    pub fn selected_path(&self) -> Option<PathBuf> {
+        let item = &self.items[*self.matches.get(self.selected)?];
+        Some(self.root.join(&item.rel_path))
+    }
+
+    pub fn selected_is_dir(&self) -> bool {
+        self.matches
+            .get(self.selected)
+            .is_some_and(|&i| self.items[i].is_dir)
+    }
+}
+
+impl Drop for FuzzyFinder {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+fn start_walk(
+    root: &Path,
+    show_hidden: bool,
+    respect_gitignore: bool,
+) -> (Receiver<Vec<FinderItem>>, Arc<AtomicBool>) {
+    let (tx, rx) = mpsc::channel();
+    let cancel = Arc::new(AtomicBool::new(false));
+
+    let root = root.to_path_buf();
+    let worker_cancel = Arc::clone(&cancel);
+    thread::spawn(move || {
+        let walker = WalkBuilder::new(&root)
+            .standard_filters(respect_gitignore)
+            .hidden(!show_hidden)
+            // Never descend into VCS metadata
+            .filter_entry(|e| e.file_name() != ".git")
+            .build();
+
+        let mut batch = Vec::with_capacity(WALK_BATCH_SIZE);
+        for (count, entry) in walker.filter_map(|e| e.ok()).skip(1).enumerate() {
+            if count >= MAX_FINDER_PATHS || worker_cancel.load(Ordering::Relaxed) {
+                break;
+            }
+            let Ok(rel_path) = entry.path().strip_prefix(&root) else {
+                continue;
+            };
+            batch.push(FinderItem {
+                rel_path: rel_path.to_string_lossy().into_owned(),
+                is_dir: entry.file_type().is_some_and(|t| t.is_dir()),
+            });
+            if batch.len() == WALK_BATCH_SIZE && tx.send(std::mem::take(&mut batch)).is_err() {
+                return;
+            }
+        }
+        let _ = tx.send(batch);
+    });
+
+    (rx, cancel)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{fs, time::Duration};
+
+    use crate::test_util::unique_temp_dir;
+
+    fn fixture(name: &str) -> PathBuf {
+        let root = unique_temp_dir(name);
+        for dir in [".git", ".config", "src/domain", "docs", "target"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        for file in [
+            ".config/main.toml",
+            "src/main.rs",
+            "src/domain/mod.rs",
+            "docs/maintenance.md",
+            "target/main.o",
+        ] {
+            fs::write(root.join(file), "").unwrap();
+        }
+        fs::write(root.join(".gitignore"), "target/\n").unwrap();
+        root
+    }
+
+    fn wait(finder: &mut FuzzyFinder) {
+        for _ in 0..200 {
+            finder.poll();
+            if !finder.walking {
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    fn matched(finder: &FuzzyFinder) -> Vec<&str> {
+        finder
+            .matches
+            .iter()
+            .map(|&i| finder.items[i].rel_path.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn ranks_file_name_matches_first() {
+        let root = fixture("finder-rank");
+        let mut finder = FuzzyFinder::new(root.clone(), false, true);
+        wait(&mut finder);
+
+        // Matches in the file name beat the same letters spread over a path
+        finder.set_query("main".to_owned());
+        assert_eq!(
+            matched(&finder),
+            [
+                "src/main.rs",
+                "docs/maintenance.md",
+                "src/domain",
+                "src/domain/mod.rs"
+            ]
+        );
+        assert_eq!(finder.selected_path(), Some(root.join("src/main.rs")));
+
+        finder.set_query("dmod".to_owned());
+        assert_eq!(matched(&finder), ["src/domain/mod.rs"]);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn hidden_and_ignored_files_follow_the_toggles() {
+        let root = fixture("finder-toggles");
+        let mut finder = FuzzyFinder::new(root.clone(), false, true);
+        wait(&mut finder);
+        finder.set_query("main".to_owned());
+        assert!(!matched(&finder).contains(&"target/main.o"));
+        assert!(!matched(&finder).contains(&".config/main.toml"));
+
+        finder.toggle_gitignore();
+        wait(&mut finder);
+        finder.set_query("main".to_owned());
+        assert!(matched(&finder).contains(&"target/main.o"));
+        assert!(!matched(&finder).contains(&".config/main.toml"));
+
+        let mut finder = FuzzyFinder::new(root.clone(), true, true);
+        wait(&mut finder);
+        finder.set_query("main".to_owned());
+        assert!(matched(&finder).contains(&".config/main.toml"));
+        assert!(!matched(&finder).contains(&"target/main.o"));
+        // VCS metadata stays out even with hidden files shown
+        assert!(
+            finder
+                .items
+                .iter()
+                .all(|i| !i.rel_path.starts_with(".git/"))
+        );
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,246 @@
+//! Frecency database for directory jumping (zoxide-style)
+//!
+//! Every visited directory gets a rank that grows with each visit; the score
+//! used for ordering weighs that rank by how recently the directory was last
+//! visited. Ranks are aged down once their total exceeds `MAX_TOTAL_RANK` so
+//! old favourites fade out. The database lives in the XDG state directory as
+//! `rank<TAB>last_access<TAB>path` lines.
+
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{state::get_state_dir, util::fuzzy_match};
+
+/// Once the ranks sum past this, all of them are scaled down
+const MAX_TOTAL_RANK: f64 = 10_000.0;
+
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+const WEEK: u64 = 7 * DAY;
+
+struct DirRecord {
+    rank: f64,
+    /// Seconds since the Unix epoch
+    last_access: u64,
+}
+
+#[derive(Default)]
+pub struct FrecencyDb {
+    dirs: HashMap<PathBuf, DirRecord>,
+    dirty: bool,
+}
+
+fn get_frecency_file_path() -> PathBuf {
+    get_state_dir().join("frecency")
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+impl DirRecord {
+    fn score(&self, now: u64) -> f64 {
+        let age = now.saturating_sub(self.last_access);
+        let weight = if age < HOUR {
+            4.0
+        } else if age < DAY {
+            2.0
+        } else if age < WEEK {
+            0.5
+        } else {
+            0.25
+        };
+        self.rank * weight
+    }
+}
+
+/// Check whether `path` matches all keywords (zoxide rules): keywords must
+/// appear in order, case-insensitively, and the last one must be in the final
+/// path component.
+fn matches_keywords(path: &Path, keywords: &[String]) -> bool {
+    let path_str = path.to_string_lossy().to_lowercase();
+
+    if let Some(last) = keywords.last() {
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        if !file_name.contains(last.as_str()) {
+            return false;
+        }
+    }
+
+    let mut rest = path_str.as_str();
+    for keyword in keywords {
+        match rest.find(keyword.as_str()) {
+            Some(idx) => rest = &rest[idx + keyword.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+impl FrecencyDb {
+    pub fn load() -> Self {
+        let Ok(file) = std::fs::File::open(get_frecency_file_path()) else {
+            return Self::default();
+        };
+
+        let dirs = BufReader::new(file)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| {
+                let mut fields = line.splitn(3, '\t');
+                let rank = fields.next()?.parse().ok()?;
+                let last_access = fields.next()?.parse().ok()?;
+                let path = PathBuf::from(fields.next()?);
+                Some((path, DirRecord { rank, last_access }))
+            })
+            .collect();
+
+        Self { dirs, dirty: false }
+    }
+
+    /// Write the database back if anything changed since it was loaded
+    pub fn save(&self) {
+        if !self.dirty {
+            return;
+        }
+        let path = get_frecency_file_path();
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+
+        let Ok(file) = std::fs::File::create(&path) else {
+            return;
+        };
+
+        let mut writer = BufWriter::new(file);
+        for (dir, record) in &self.dirs {
+            let _ = writeln!(
+                writer,
+                "{}\t{}\t{}",
+                record.rank,
+                record.last_access,
+                dir.display()
+            );
+        }
+    }
+
+    /// Record a visit to `path`
+    pub fn visit(&mut self, path: &Path) {
+        self.visit_at(path, now_secs());
+    }
+
+    fn visit_at(&mut self, path: &Path, now: u64) {
+        let record = self.dirs.entry(path.to_path_buf()).or_insert(DirRecord {
+            rank: 0.0,
+            last_access: now,
+        });
+        record.rank += 1.0;
+        record.last_access = now;
+        self.dirty = true;
+        self.age();
+    }
+
+    /// Scale all ranks down once they sum past the limit, forgetting directories
+    /// whose rank drops below 1
+    fn age(&mut self) {
+        let total: f64 = self.dirs.values().map(|r| r.rank).sum();
+        if total <= MAX_TOTAL_RANK {
+            return;
+        }
+        let factor = 0.9 * MAX_TOTAL_RANK / total;
+        self.dirs.retain(|_, record| {
+            record.rank *= factor;
+            record.rank >= 1.0
+        });
+    }
+
+    /// All directories matching the keywords, best score first. Directories
+    /// that no longer exist are included; callers check the ones they use.
+    pub fn query(&self, keywords: &[String]) -> Vec<PathBuf> {
+        self.query_at(keywords, now_secs())
+    }
+
+    fn query_at(&self, keywords: &[String], now: u64) -> Vec<PathBuf> {
+        let keywords: Vec<String> = keywords.iter().map(|k| k.to_lowercase()).collect();
+        let mut matches: Vec<(&PathBuf, f64)> = self
+            .dirs
+            .iter()
+            .filter(|(dir, _)| matches_keywords(dir, &keywords))
+            .map(|(dir, record)| (dir, record.score(now)))
+            .collect();
+        matches.sort_by(|a, b| b.1.total_cmp(&a.1));
+        matches.into_iter().map(|(dir, _)| dir.clone()).collect()
+    }
+
+    /// Directories ranked by score, best first (for the jump popup)
+    pub fn ranked(&self) -> Vec<PathBuf> {
+        self.query(&[])
+    }
+}
+
+/// Jump popup candidates that fuzzy-match `query`, keeping their frecency order
+pub fn filter_jump_candidates<'a>(candidates: &'a [PathBuf], query: &str) -> Vec<&'a PathBuf> {
+    candidates
+        .iter()
+        .filter(|p| fuzzy_match(query, &p.to_string_lossy()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keywords(s: &str) -> Vec<String> {
+        s.split_whitespace().map(str::to_owned).collect()
+    }
+
+    #[test]
+    fn keyword_matching_follows_zoxide_rules() {
+        let path = Path::new("/home/user/projects/rmc/src");
+        assert!(matches_keywords(path, &keywords("proj src")));
+        assert!(matches_keywords(path, &keywords("sr")));
+        // Out of order
+        assert!(!matches_keywords(path, &keywords("src proj")));
+        // Last keyword must match the last component
+        assert!(!matches_keywords(path, &keywords("rmc")));
+    }
+
+    #[test]
+    fn recent_visits_outrank_old_frequent_ones() {
+        let dir = std::env::temp_dir();
+        let other = dir.parent().unwrap_or(&dir).to_path_buf();
+        let now = 10 * WEEK;
+
+        let mut db = FrecencyDb::default();
+        // Visited three times, but a month ago
+        for _ in 0..3 {
+            db.visit_at(&other, now - 4 * WEEK);
+        }
+        // Visited once, just now
+        db.visit_at(&dir, now);
+
+        assert_eq!(db.query_at(&[], now), vec![dir, other]);
+    }
+
+    #[test]
+    fn aging_forgets_rarely_used_directories() {
+        let mut db = FrecencyDb::default();
+        let now = WEEK;
+        db.visit_at(Path::new("/rare"), now);
+        for _ in 0..(MAX_TOTAL_RANK as usize) {
+            db.visit_at(Path::new("/common"), now);
+        }
+        assert!(!db.dirs.contains_key(Path::new("/rare")));
+        assert!(db.dirs[Path::new("/common")].rank <= MAX_TOTAL_RANK);
+    }
+}
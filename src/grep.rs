@@ -0,0 +1,501 @@
+//! Recursive content search (grep)
+//!
+//! Like Find File, the dialog edits a `GrepForm` that compiles into a
+//! `GrepQuery`, and a background thread streams matching lines back to the
+//! results list.
+
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, Read},
+    path::{Path, PathBuf},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver},
+    },
+    thread,
+};
+
+use regex::bytes::{Regex, RegexBuilder};
+use walkdir::WalkDir;
+
+use crate::util::{expand_tilde, glob_match};
+
+/// Bytes inspected when deciding whether a file is binary
+const BINARY_CHECK_BYTES: usize = 8 * 1024;
+
+/// Stop searching once this many lines have matched
+const MAX_GREP_MATCHES: usize = 10_000;
+
+/// Longest snippet kept for a matching line, in characters
+const MAX_SNIPPET_CHARS: usize = 200;
+
+/// Send a progress update every this many searched files
+const PROGRESS_INTERVAL: usize = 64;
+
+/// Labels of the dialog fields, in focus order. The first `TEXT_FIELD_COUNT`
+/// are text inputs, the rest are options toggled with Space.
+pub const FIELD_LABELS: [&str; 8] = [
+    "Start at:",
+    "Pattern:",
+    "Include files:",
+    "Exclude:",
+    "Case",
+    "Literal string",
+    "Whole words",
+    "Hidden files",
+];
+pub const TEXT_FIELD_COUNT: usize = 4;
+
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum CaseMode {
+    /// Case-insensitive unless the pattern contains an uppercase letter
+    #[default]
+    Smart,
+    Sensitive,
+    Insensitive,
+}
+
+impl CaseMode {
+    pub fn cycle(self) -> Self {
+        match self {
+            Self::Smart => Self::Sensitive,
+            Self::Sensitive => Self::Insensitive,
+            Self::Insensitive => Self::Smart,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Smart => "smart",
+            Self::Sensitive => "sensitive",
+            Self::Insensitive => "insensitive",
+        }
+    }
+}
+
+/// Raw contents of the grep dialog
+#[derive(Clone)]
+pub struct GrepForm {
+    pub start_dir: String,
+    pub pattern: String,
+    /// Space/comma separated globs; empty searches every file
+    pub include: String,
+    /// Space/comma separated globs for files and directories to skip
+    pub exclude: String,
+    pub case: CaseMode,
+    pub literal: bool,
+    pub whole_word: bool,
+    pub include_hidden: bool,
+    /// Index into `FIELD_LABELS` of the focused field
+    pub focus: usize,
+}
+
+impl Default for GrepForm {
+    fn default() -> Self {
+        Self {
+            start_dir: ".".to_owned(),
+            pattern: String::new(),
+            include: String::new(),
+            exclude: "target node_modules".to_owned(),
+            case: CaseMode::default(),
+            literal: false,
+            whole_word: false,
+            include_hidden: false,
+            focus: 1,
+        }
+    }
+}
+
+impl GrepForm {
+    /// The focused text input, or None if an option has focus
+    pub fn focused_text_mut(&mut self) -> Option<&mut String> {
+        match self.focus {
+            0 => Some(&mut self.start_dir),
+            1 => Some(&mut self.pattern),
+            2 => Some(&mut self.include),
+            3 => Some(&mut self.exclude),
+            _ => None,
+        }
+    }
+
+    pub fn text(&self, idx: usize) -> &str {
+        match idx {
+            0 => &self.start_dir,
+            1 => &self.pattern,
+            2 => &self.include,
+            3 => &self.exclude,
+            _ => "",
+        }
+    }
+
+    pub fn checked(&self, idx: usize) -> bool {
+        match idx {
+            5 => self.literal,
+            6 => self.whole_word,
+            7 => self.include_hidden,
+            _ => false,
+        }
+    }
+
+    /// Flip (or cycle, for the case mode) the focused option
+    pub fn toggle_focused(&mut self) {
+        let flag = match self.focus {
+            4 => {
+                self.case = self.case.cycle();
+                return;
+            }
+            5 => &mut self.literal,
+            6 => &mut self.whole_word,
+            7 => &mut self.include_hidden,
+            _ => return,
+        };
+        *flag = !*flag;
+    }
+
+    pub fn focus_next(&mut self) {
+        self.focus = (self.focus + 1) % FIELD_LABELS.len();
+    }
+
+    pub fn focus_prev(&mut self) {
+        self.focus = (self.focus + FIELD_LABELS.len() - 1) % FIELD_LABELS.len();
+    }
+
+    /// Validate the form and build a query. Relative start directories are
+    /// resolved against `base` (the active pane's directory).
+    pub fn compile(&self, base: &Path) -> Result<GrepQuery, String> {
+        let start_dir = match self.start_dir.trim() {
+            "" => base.to_path_buf(),
+            dir => base.join(expand_tilde(dir)),
+        };
+        let start_dir = start_dir.canonicalize().unwrap_or(start_dir);
+        if !start_dir.is_dir() {
+            return Err(format!("Not a directory: {}", start_dir.display()));
+        }
+
+        if self.pattern.is_empty() {
+            return Err("Enter a pattern to search for".to_owned());
+        }
+        let mut pattern = if self.literal {
+            regex::escape(&self.pattern)
+        } else {
+            self.pattern.clone()
+        };
+        if self.whole_word {
+            pattern = format!(r"\b(?:{})\b", pattern);
+        }
+        let case_insensitive = match self.case {
+            CaseMode::Smart => !self.pattern.chars().any(char::is_uppercase),
+            CaseMode::Sensitive => false,
+            CaseMode::Insensitive => true,
+        };
+        let regex = RegexBuilder::new(&pattern)
+            .case_insensitive(case_insensitive)
+            .build()
+            .map_err(|e| format!("Invalid pattern: {}", e))?;
+
+        Ok(GrepQuery {
+            start_dir,
+            regex,
+            include: split_globs(&self.include),
+            exclude: split_globs(&self.exclude),
+            include_hidden: self.include_hidden,
+        })
+    }
+}
+
+fn split_globs(input: &str) -> Vec<String> {
+    input
+        .split([' ', ','])
+        .filter(|g| !g.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+/// A matching line
+pub struct GrepMatch {
+    pub path: PathBuf,
+    /// 1-based line number
+    pub line: usize,
+    /// The line with surrounding whitespace trimmed, shortened if very long
+    pub text: String,
+}
+
+/// A validated search
+pub struct GrepQuery {
+    pub start_dir: PathBuf,
+    regex: Regex,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    include_hidden: bool,
+}
+
+impl GrepQuery {
+    fn is_excluded(&self, name: &str) -> bool {
+        (!self.include_hidden && name.starts_with('.'))
+            || self.exclude.iter().any(|g| glob_match(g, name, true))
+    }
+
+    fn is_included(&self, name: &str) -> bool {
+        self.include.is_empty() || self.include.iter().any(|g| glob_match(g, name, true))
+    }
+
+    /// Search every included text file, calling `on_file_matches` with the
+    /// matches of each file that has any. Returns the number of files searched.
+    fn run(
+        &self,
+        cancel: &AtomicBool,
+        mut on_file_matches: impl FnMut(Vec<GrepMatch>),
+        mut on_progress: impl FnMut(usize),
+    ) -> usize {
+        let walker = WalkDir::new(&self.start_dir)
+            .min_depth(1)
+            .into_iter()
+            .filter_entry(|e| !self.is_excluded(&e.file_name().to_string_lossy()));
+
+        let mut files = 0;
+        let mut total_matches = 0;
+        for entry in walker.filter_map(|e| e.ok()) {
+            if cancel.load(Ordering::Relaxed) || total_matches >= MAX_GREP_MATCHES {
+                break;
+            }
+            if !entry.file_type().is_file()
+                || !self.is_included(&entry.file_name().to_string_lossy())
+            {
+                continue;
+            }
+
+            files += 1;
+            if files % PROGRESS_INTERVAL == 0 {
+                on_progress(files);
+            }
+
+            let mut matches = Vec::new();
+            search_file(entry.path(), &self.regex, cancel, |line, text| {
+                matches.push(GrepMatch {
+                    path: entry.path().to_path_buf(),
+                    line,
+                    text: snippet(text),
+                });
+                total_matches + matches.len() < MAX_GREP_MATCHES
+            });
+            if !matches.is_empty() {
+                total_matches += matches.len();
+                on_file_matches(matches);
+            }
+        }
+        files
+    }
+}
+
+fn snippet(line: &[u8]) -> String {
+    let text = String::from_utf8_lossy(line);
+    let text = text.trim();
+    if text.chars().count() > MAX_SNIPPET_CHARS {
+        let truncated: String = text.chars().take(MAX_SNIPPET_CHARS).collect();
+        format!("{}…", truncated)
+    } else {
+        text.to_owned()
+    }
+}
+
+/// Call `on_match` with the 1-based number and contents of every line of
+/// `path` matching `regex`, until it returns false. Binary files (NUL bytes
+/// near the start) are skipped.
+pub fn search_file(
+    path: &Path,
+    regex: &Regex,
+    cancel: &AtomicBool,
+    mut on_match: impl FnMut(usize, &[u8]) -> bool,
+) {
+    let Ok(file) = File::open(path) else {
+        return;
+    };
+    let mut reader = BufReader::new(file);
+
+    let mut head = Vec::with_capacity(BINARY_CHECK_BYTES);
+    if (&mut reader)
+        .take(BINARY_CHECK_BYTES as u64)
+        .read_to_end(&mut head)
+        .is_err()
+        || head.contains(&0)
+    {
+        return;
+    }
+
+    let mut reader = head.chain(reader);
+    let mut line = Vec::new();
+    let mut line_number = 0;
+    loop {
+        line.clear();
+        match reader.read_until(b'\n', &mut line) {
+            Ok(0) | Err(_) => return,
+            Ok(_) => {
+                line_number += 1;
+                if regex.is_match(&line) && !on_match(line_number, &line) {
+                    return;
+                }
+                if cancel.load(Ordering::Relaxed) {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+enum GrepUpdate {
+    Found(Vec<GrepMatch>),
+    Progress(usize),
+    Done(usize),
+}
+
+/// A running or finished content search and the matches received so far
+pub struct GrepSearch {
+    /// The dialog contents that started this search, to pre-fill the next one
+    pub form: GrepForm,
+    pub start_dir: PathBuf,
+    pub matches: Vec<GrepMatch>,
+    /// Files searched so far
+    pub files_searched: usize,
+    pub running: bool,
+    rx: Receiver<GrepUpdate>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl GrepSearch {
+    pub fn start(form: GrepForm, query: GrepQuery) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let start_dir = query.start_dir.clone();
+
+        let worker_cancel = Arc::clone(&cancel);
+        thread::spawn(move || {
+            let files = query.run(
+                &worker_cancel,
+                |matches| {
+                    let _ = tx.send(GrepUpdate::Found(matches));
+                },
+                |files| {
+                    let _ = tx.send(GrepUpdate::Progress(files));
+                },
+            );
+            let _ = tx.send(GrepUpdate::Done(files));
+        });
+
+        Self {
+            form,
+            start_dir,
+            matches: Vec::new(),
+            files_searched: 0,
+            running: true,
+            rx,
+            cancel,
+        }
+    }
+
+    /// Collect matches sent by the search thread since the last poll
+    pub fn poll(&mut self) {
+        loop {
+            match self.rx.try_recv() {
+                Ok(GrepUpdate::Found(matches)) => self.matches.extend(matches),
+                Ok(GrepUpdate::Progress(files)) => self.files_searched = files,
+                Ok(GrepUpdate::Done(files)) => {
+                    self.files_searched = files;
+                    self.running = false;
+                }
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    self.running = false;
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Whether the search stopped at the match limit
+    pub fn hit_limit(&self) -> bool {
+        self.matches.len() >= MAX_GREP_MATCHES
+    }
+
+    /// Ask the search thread to stop; matches found so far are kept
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Drop for GrepSearch {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+
+    use crate::test_util::unique_temp_dir;
+
+    fn grep(root: &Path, form: GrepForm) -> Vec<String> {
+        let query = form.compile(root).unwrap();
+        let mut found = Vec::new();
+        query.run(&AtomicBool::new(false), |m| found.extend(m), |_| {});
+        let mut lines: Vec<String> = found
+            .iter()
+            .map(|m| {
+                let rel = m.path.strip_prefix(root).unwrap().display();
+                format!("{}:{}:{}", rel, m.line, m.text)
+            })
+            .collect();
+        lines.sort();
+        lines
+    }
+
+    #[test]
+    fn globs_case_and_binary_skipping() {
+        let root = unique_temp_dir("grep");
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join("target")).unwrap();
+        fs::write(
+            root.join("src/main.rs"),
+            "fn main() {\n    let Foo = foo();\n}\n",
+        )
+        .unwrap();
+        fs::write(root.join("src/notes.txt"), "foo bar\n").unwrap();
+        fs::write(root.join("src/data.bin"), b"foo\0\x01").unwrap();
+        fs::write(root.join("target/out.rs"), "foo\n").unwrap();
+
+        let form = GrepForm {
+            pattern: "foo".to_owned(),
+            ..GrepForm::default()
+        };
+        // Smart case: lowercase pattern matches both cases; target/ is excluded
+        assert_eq!(
+            grep(&root, form.clone()),
+            ["src/main.rs:2:let Foo = foo();", "src/notes.txt:1:foo bar"]
+        );
+
+        let rs_only = GrepForm {
+            include: "*.rs".to_owned(),
+            exclude: String::new(),
+            ..form.clone()
+        };
+        assert_eq!(
+            grep(&root, rs_only),
+            ["src/main.rs:2:let Foo = foo();", "target/out.rs:1:foo"]
+        );
+
+        let sensitive_word = GrepForm {
+            pattern: "Foo".to_owned(),
+            whole_word: true,
+            ..form
+        };
+        assert_eq!(
+            grep(&root, sensitive_word),
+            ["src/main.rs:2:let Foo = foo();"]
+        );
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,114 @@
+//! Per-pane directory history with browser-style back/forward navigation
+
+use std::path::{Path, PathBuf};
+
+/// Maximum number of directories kept in a pane's history
+const MAX_HISTORY: usize = 100;
+
+#[derive(Clone, Default)]
+pub struct DirHistory {
+    /// Visited directories, oldest first
+    entries: Vec<PathBuf>,
+    /// Index of the current directory in `entries`
+    pos: usize,
+}
+
+impl DirHistory {
+    /// Rebuild a history from saved entries, positioned at the newest one
+    pub fn from_entries(mut entries: Vec<PathBuf>) -> Self {
+        if entries.len() > MAX_HISTORY {
+            entries.drain(..entries.len() - MAX_HISTORY);
+        }
+        let pos = entries.len().saturating_sub(1);
+        Self { entries, pos }
+    }
+
+    /// Record a newly visited directory. Drops any forward history, like a browser.
+    pub fn visit(&mut self, path: &Path) {
+        if self.entries.get(self.pos).is_some_and(|p| p == path) {
+            return;
+        }
+        if !self.entries.is_empty() {
+            self.entries.truncate(self.pos + 1);
+        }
+        self.entries.push(path.to_path_buf());
+        if self.entries.len() > MAX_HISTORY {
+            self.entries.remove(0);
+        }
+        self.pos = self.entries.len() - 1;
+    }
+
+    /// Step back, returning the directory to go to
+    pub fn back(&mut self) -> Option<PathBuf> {
+        self.pos = self.pos.checked_sub(1)?;
+        Some(self.entries[self.pos].clone())
+    }
+
+    /// Step forward, returning the directory to go to
+    pub fn forward(&mut self) -> Option<PathBuf> {
+        if self.pos + 1 >= self.entries.len() {
+            return None;
+        }
+        self.pos += 1;
+        Some(self.entries[self.pos].clone())
+    }
+
+    /// The directory visited before the current one (for `cd -`)
+    pub fn previous(&self) -> Option<&PathBuf> {
+        self.pos.checked_sub(1).and_then(|i| self.entries.get(i))
+    }
+
+    /// All entries, oldest first (for the state file)
+    pub fn entries(&self) -> &[PathBuf] {
+        &self.entries
+    }
+
+    /// Distinct directories, most recently visited first
+    pub fn recent(&self) -> Vec<PathBuf> {
+        let mut recent: Vec<PathBuf> = Vec::new();
+        for path in self.entries.iter().rev() {
+            if !recent.contains(path) {
+                recent.push(path.clone());
+            }
+        }
+        recent
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn p(s: &str) -> PathBuf {
+        PathBuf::from(s)
+    }
+
+    #[test]
+    fn back_forward_and_branching() {
+        let mut history = DirHistory::default();
+        history.visit(&p("/a"));
+        history.visit(&p("/b"));
+        history.visit(&p("/c"));
+        history.visit(&p("/c")); // repeated visits are ignored
+
+        assert_eq!(history.back(), Some(p("/b")));
+        assert_eq!(history.back(), Some(p("/a")));
+        assert_eq!(history.back(), None);
+        assert_eq!(history.forward(), Some(p("/b")));
+
+        // Visiting from the middle drops the forward entries
+        history.visit(&p("/d"));
+        assert_eq!(history.forward(), None);
+        assert_eq!(history.entries(), [p("/a"), p("/b"), p("/d")]);
+        assert_eq!(history.previous(), Some(&p("/b")));
+    }
+
+    #[test]
+    fn recent_is_distinct_newest_first() {
+        let mut history = DirHistory::default();
+        for dir in ["/a", "/b", "/a", "/c"] {
+            history.visit(&p(dir));
+        }
+        assert_eq!(history.recent(), [p("/c"), p("/a"), p("/b")]);
+    }
+}
@@ -4,24 +4,43 @@
 
 use std::{
     env,
+    io::Write,
     path::{Path, PathBuf},
     time::Instant,
 };
 
 use crossterm::{
+    ExecutableCommand,
     event::{KeyCode, KeyModifiers, MouseButton, MouseEventKind},
     terminal::{EnterAlternateScreen, LeaveAlternateScreen},
-    ExecutableCommand,
 };
 use ratatui::DefaultTerminal;
 
 use crate::{
-    dialog::{handle_yes_no_keys, DialogResult},
+    App, SidePanel, UIMode,
+    archive::{self, ArchiveFormat},
+    attributes::AttrForm,
+    bulk_rename::{
+        BulkRename, RenameForm, RenameSource, count_cycles, editor_listing, parse_editor_listing,
+    },
+    compare::{CompareMethod, CompareRun},
+    compress::CompressForm,
+    dialog::{DialogResult, handle_yes_no_keys},
+    diff::DiffViewer,
+    disk_usage::DiskUsage,
+    extract::ExtractForm,
+    find::{FindForm, FindSearch},
+    finder::FuzzyFinder,
+    frecency::filter_jump_candidates,
+    grep::{GrepForm, GrepSearch},
     job::{ConflictResolution, JobId, JobStatus, JobType},
-    pane::{Entry, Pane},
-    util::{PAGE_SCROLL_SIZE, RENAME_DIALOG_TIMEOUT_SECS},
-    viewer::{FileViewer, ViewMode},
-    App, UIMode,
+    links::{LinkForm, LinkKind},
+    mounts,
+    pane::{Entry, Pane, PaneState},
+    properties::PropertiesView,
+    tree::DirTree,
+    util::{self, PAGE_SCROLL_SIZE, RENAME_DIALOG_TIMEOUT_SECS},
+    viewer::{FileViewer, MAX_FILE_SIZE, Scrollable, ViewMode},
 };
 
 impl App {
@@ -51,10 +70,12 @@ impl App {
             }
             UIMode::ConfirmDelete { .. } => {
                 // Take the entries out temporarily to avoid borrow issues
-                if let UIMode::ConfirmDelete { entries, .. } =
-                    std::mem::replace(&mut self.ui_mode, UIMode::Normal)
+                if let UIMode::ConfirmDelete {
+                    entries,
+                    has_job_conflict,
+                } = std::mem::replace(&mut self.ui_mode, UIMode::Normal)
                 {
-                    self.handle_confirm_delete(key, entries);
+                    self.handle_confirm_delete(key, entries, has_job_conflict);
                 }
             }
             UIMode::MkdirInput { input } => {
@@ -81,12 +102,144 @@ impl App {
                 let query = query.clone();
                 self.handle_search(key, modifiers, query);
             }
+            UIMode::SelectGroup { .. } => {
+                if let UIMode::SelectGroup {
+                    input,
+                    select,
+                    files_only,
+                    case_sensitive,
+                } = std::mem::take(&mut self.ui_mode)
+                {
+                    self.handle_select_group(
+                        key,
+                        modifiers,
+                        input,
+                        select,
+                        files_only,
+                        case_sensitive,
+                    );
+                }
+            }
+            UIMode::Hotlist { selected, rename } => {
+                let selected = *selected;
+                let rename = rename.clone();
+                self.handle_hotlist(key, selected, rename);
+            }
+            UIMode::DirHistory { selected } => {
+                let selected = *selected;
+                self.handle_dir_history(key, selected);
+            }
+            UIMode::Mounts { .. } => {
+                if let UIMode::Mounts {
+                    mounts,
+                    selected,
+                    show_all,
+                } = std::mem::take(&mut self.ui_mode)
+                {
+                    self.handle_mounts(key, mounts, selected, show_all);
+                }
+            }
+            UIMode::FrecencyJump { .. } => {
+                if let UIMode::FrecencyJump {
+                    query,
+                    candidates,
+                    selected,
+                } = std::mem::take(&mut self.ui_mode)
+                {
+                    self.handle_frecency_jump(key, query, candidates, selected);
+                }
+            }
+            UIMode::FindFile { .. } => {
+                if let UIMode::FindFile { form } = std::mem::take(&mut self.ui_mode) {
+                    self.handle_find_file(key, form);
+                }
+            }
+            UIMode::FindResults { selected } => {
+                let selected = *selected;
+                self.handle_find_results(key, selected);
+            }
+            UIMode::GrepForm { .. } => {
+                if let UIMode::GrepForm { form } = std::mem::take(&mut self.ui_mode) {
+                    self.handle_grep_form(key, form);
+                }
+            }
+            UIMode::GrepResults { selected } => {
+                let selected = *selected;
+                self.handle_grep_results(key, selected);
+            }
+            UIMode::CompareDirs { method, recursive } => {
+                let (method, recursive) = (*method, *recursive);
+                self.handle_compare_dirs(key, method, recursive);
+            }
+            UIMode::CompareResults { selected } => {
+                let selected = *selected;
+                self.handle_compare_results(key, selected);
+            }
+            UIMode::FuzzyFinder => {
+                self.handle_fuzzy_finder(key, modifiers);
+            }
+            UIMode::MarkPending { set } => {
+                let set = *set;
+                self.handle_mark_pending(key, set);
+            }
             UIMode::FileViewer { .. } => {
                 // Use take pattern to avoid cloning the potentially huge FileViewer
-                if let UIMode::FileViewer { viewer } =
+                if let UIMode::FileViewer { viewer, return_to } =
                     std::mem::replace(&mut self.ui_mode, UIMode::Normal)
                 {
-                    self.handle_file_viewer(key, viewer);
+                    self.handle_file_viewer(key, viewer, return_to);
+                }
+            }
+            UIMode::Attributes { form } => {
+                let form = form.clone();
+                self.handle_attributes(key, form);
+            }
+            UIMode::DiskUsage => {
+                self.handle_disk_usage(key);
+            }
+            UIMode::Properties => {
+                if matches!(
+                    key,
+                    KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') | KeyCode::Char('i')
+                ) {
+                    self.properties = None;
+                    self.ui_mode = UIMode::Normal;
+                }
+            }
+            UIMode::CtrlXPrefix => {
+                self.handle_ctrl_x_prefix(key, modifiers);
+            }
+            UIMode::LinkInput { form } => {
+                let form = form.clone();
+                self.handle_link_input(key, modifiers, form);
+            }
+            UIMode::Compress { form } => {
+                let form = form.clone();
+                self.handle_compress_input(key, form);
+            }
+            UIMode::Extract { form } => {
+                let form = form.clone();
+                self.handle_extract_input(key, form);
+            }
+            UIMode::ConfirmBatchRename { .. } => {
+                if let UIMode::ConfirmBatchRename { renames, cycles } =
+                    std::mem::replace(&mut self.ui_mode, UIMode::Normal)
+                {
+                    self.handle_confirm_batch_rename(key, renames, cycles);
+                }
+            }
+            UIMode::BulkRename { .. } => {
+                if let UIMode::BulkRename { rename } =
+                    std::mem::replace(&mut self.ui_mode, UIMode::Normal)
+                {
+                    self.handle_bulk_rename(key, rename);
+                }
+            }
+            UIMode::DiffViewer { .. } => {
+                if let UIMode::DiffViewer { viewer } =
+                    std::mem::replace(&mut self.ui_mode, UIMode::Normal)
+                {
+                    self.handle_diff_viewer(key, viewer);
                 }
             }
         }
@@ -99,6 +252,13 @@ impl App {
         modifiers: KeyModifiers,
         terminal: &mut DefaultTerminal,
     ) -> std::io::Result<()> {
+        if self.active_pane().tree.is_some() && self.handle_tree_key(key, modifiers) {
+            return Ok(());
+        }
+        if self.active_pane().archive.is_some() && self.handle_archive_key(key, modifiers) {
+            return Ok(());
+        }
+
         // Handle Ctrl+S for search
         if modifiers.contains(KeyModifiers::CONTROL) && key == KeyCode::Char('s') {
             self.ui_mode = UIMode::Search {
@@ -107,6 +267,114 @@ impl App {
             return Ok(());
         }
 
+        // Tab management, hotlist and Find File
+        if modifiers.contains(KeyModifiers::CONTROL) {
+            match key {
+                KeyCode::Char('f') => {
+                    self.open_find_dialog();
+                    return Ok(());
+                }
+                KeyCode::Char('g') => {
+                    let form = self
+                        .grep
+                        .as_ref()
+                        .map(|g| g.form.clone())
+                        .unwrap_or_default();
+                    self.ui_mode = UIMode::GrepForm { form };
+                    return Ok(());
+                }
+                KeyCode::Char('p') => {
+                    let pane = self.active_pane();
+                    self.finder = Some(FuzzyFinder::new(pane.path.clone(), pane.show_hidden, true));
+                    self.ui_mode = UIMode::FuzzyFinder;
+                    return Ok(());
+                }
+                // Terminals report Ctrl+\ as Ctrl+4
+                KeyCode::Char('\\') | KeyCode::Char('4') => {
+                    self.ui_mode = UIMode::Hotlist {
+                        selected: 0,
+                        rename: None,
+                    };
+                    return Ok(());
+                }
+                KeyCode::Char('t') => {
+                    self.open_tab();
+                    return Ok(());
+                }
+                KeyCode::Char('z') => {
+                    self.undo_batch_rename();
+                    return Ok(());
+                }
+                KeyCode::Char('x') => {
+                    self.ui_mode = UIMode::CtrlXPrefix;
+                    return Ok(());
+                }
+                KeyCode::Char('w') => {
+                    let (tabs, live) = self.active_tabs_mut();
+                    tabs.close(live);
+                    return Ok(());
+                }
+                KeyCode::PageDown => {
+                    let (tabs, live) = self.active_tabs_mut();
+                    tabs.next(live);
+                    return Ok(());
+                }
+                KeyCode::PageUp => {
+                    let (tabs, live) = self.active_tabs_mut();
+                    tabs.prev(live);
+                    return Ok(());
+                }
+                _ => {}
+            }
+        }
+        // History navigation
+        if modifiers.contains(KeyModifiers::ALT) {
+            match key {
+                KeyCode::Left => {
+                    self.history_step(true);
+                    return Ok(());
+                }
+                KeyCode::Right => {
+                    self.history_step(false);
+                    return Ok(());
+                }
+                KeyCode::Char('h') | KeyCode::Char('H') => {
+                    self.ui_mode = UIMode::DirHistory { selected: 0 };
+                    return Ok(());
+                }
+                // Alt+F1/F2 pick a filesystem for the left/right pane, as in MC
+                KeyCode::F(1) | KeyCode::F(2) => {
+                    self.active_pane = if key == KeyCode::F(1) {
+                        Pane::Left
+                    } else {
+                        Pane::Right
+                    };
+                    self.open_mounts(false);
+                    return Ok(());
+                }
+                KeyCode::Char('r') => {
+                    if let Err(msg) = self.rename_in_editor(terminal) {
+                        self.error_message = Some((msg, Instant::now()));
+                    }
+                    return Ok(());
+                }
+                // Alt+? is Find File in Midnight Commander
+                KeyCode::Char('?') => {
+                    self.open_find_dialog();
+                    return Ok(());
+                }
+                _ => {}
+            }
+        }
+        if modifiers.contains(KeyModifiers::ALT)
+            && let KeyCode::Char(c @ '1'..='9') = key
+        {
+            let idx = c as usize - '1' as usize;
+            let (tabs, live) = self.active_tabs_mut();
+            tabs.switch_to(live, idx);
+            return Ok(());
+        }
+
         match key {
             KeyCode::Char('q') | KeyCode::Esc => {
                 if self.job_manager.active_job_count() > 0 {
@@ -121,8 +389,12 @@ impl App {
             KeyCode::PageUp => self.active_pane_mut().page_up(PAGE_SCROLL_SIZE),
             KeyCode::PageDown => self.active_pane_mut().page_down(PAGE_SCROLL_SIZE),
             KeyCode::Enter | KeyCode::Right | KeyCode::Char('l') => {
+                let before = self.active_pane().path.clone();
                 if let Err(msg) = self.active_pane_mut().enter_selected() {
                     self.error_message = Some((msg, Instant::now()));
+                } else if self.active_pane().path != before {
+                    // Enter on a plain file doesn't go anywhere
+                    self.record_visit();
                 }
             }
             KeyCode::Left | KeyCode::Char('h') => {
@@ -143,12 +415,31 @@ impl App {
             KeyCode::Char('*') => {
                 self.active_pane_mut().select_all();
             }
+            KeyCode::Char('+') | KeyCode::Char('-') => {
+                self.ui_mode = UIMode::SelectGroup {
+                    input: "*".to_owned(),
+                    select: key == KeyCode::Char('+'),
+                    files_only: false,
+                    case_sensitive: false,
+                };
+            }
+            KeyCode::Char('I') => {
+                self.active_pane_mut().invert_selection();
+            }
+            KeyCode::Char('E') => {
+                self.active_pane_mut().select_same_extension();
+            }
             KeyCode::Delete | KeyCode::F(8) => {
                 self.initiate_delete();
             }
             KeyCode::F(3) => {
                 self.view_selected();
             }
+            KeyCode::Char('D') => {
+                if let Err(msg) = self.diff_selected() {
+                    self.error_message = Some((msg, Instant::now()));
+                }
+            }
             KeyCode::F(4) => {
                 if let Err(msg) = self.edit_selected(terminal) {
                     self.error_message = Some((msg, Instant::now()));
@@ -168,9 +459,43 @@ impl App {
             KeyCode::F(2) => {
                 self.initiate_rename();
             }
+            KeyCode::Char('R') => {
+                self.open_bulk_rename();
+            }
+            KeyCode::Char('i') => {
+                self.open_properties();
+            }
+            KeyCode::Char('A') => {
+                self.open_attributes();
+            }
             KeyCode::Char('U') => {
                 self.swap_panes();
             }
+            KeyCode::Char('m') => {
+                self.ui_mode = UIMode::MarkPending { set: true };
+            }
+            KeyCode::Char('\'') => {
+                self.ui_mode = UIMode::MarkPending { set: false };
+            }
+            KeyCode::Char('z') => {
+                self.open_frecency_jump();
+            }
+            KeyCode::Char('=') => {
+                let (method, recursive) = self
+                    .compare
+                    .as_ref()
+                    .map(|c| (c.method, c.recursive))
+                    .unwrap_or_default();
+                self.ui_mode = UIMode::CompareDirs { method, recursive };
+            }
+            KeyCode::Char(']') => {
+                let (tabs, live) = self.active_tabs_mut();
+                tabs.next(live);
+            }
+            KeyCode::Char('[') => {
+                let (tabs, live) = self.active_tabs_mut();
+                tabs.prev(live);
+            }
             KeyCode::Char(':') => {
                 self.ui_mode = UIMode::CommandLine {
                     input: String::new(),
@@ -181,7 +506,260 @@ impl App {
         Ok(())
     }
 
+    pub fn handle_disk_usage(&mut self, key: KeyCode) {
+        let Some(du) = &mut self.disk_usage else {
+            self.ui_mode = UIMode::Normal;
+            return;
+        };
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.disk_usage = None;
+                self.ui_mode = UIMode::Normal;
+            }
+            KeyCode::Up | KeyCode::Char('k') => du.move_by(-1),
+            KeyCode::Down | KeyCode::Char('j') => du.move_by(1),
+            KeyCode::PageUp => du.move_by(-(PAGE_SCROLL_SIZE as isize)),
+            KeyCode::PageDown => du.move_by(PAGE_SCROLL_SIZE as isize),
+            KeyCode::Home => du.selected = 0,
+            KeyCode::End => du.move_to_end(),
+            KeyCode::Enter | KeyCode::Right | KeyCode::Char('l') => du.enter(),
+            KeyCode::Left | KeyCode::Backspace | KeyCode::Char('h') => {
+                du.leave();
+            }
+            KeyCode::Char('a') => du.toggle_size_kind(),
+            KeyCode::Char('r') => {
+                let root = du.root.clone();
+                self.disk_usage = Some(DiskUsage::start(root));
+            }
+            KeyCode::Char('d') | KeyCode::Delete | KeyCode::F(8) => {
+                let Some(node) = du.selected_node() else {
+                    return;
+                };
+                let is_dir = node.is_dir;
+                let Some(path) = du.selected_path() else {
+                    return;
+                };
+                let entry = Entry {
+                    name: path
+                        .file_name()
+                        .unwrap_or_default()
+                        .to_string_lossy()
+                        .into_owned(),
+                    path: path.clone(),
+                    is_dir,
+                    size: None,
+                };
+                let canonical = path.canonicalize().unwrap_or_else(|_| path.clone());
+                let has_job_conflict = self
+                    .job_manager
+                    .paths_conflict_with_active_jobs(&[canonical]);
+                du.deleting = Some(path);
+                self.ui_mode = UIMode::ConfirmDelete {
+                    entries: vec![entry],
+                    has_job_conflict,
+                };
+            }
+            _ => {}
+        }
+    }
+
+    /// Drop entries from the disk usage tree once their delete job has
+    /// completed; a failed or cancelled delete leaves the tree alone
+    pub fn poll_disk_usage_deletes(&mut self) {
+        if self.disk_usage_deletes.is_empty() {
+            return;
+        }
+        let mut deleted = Vec::new();
+        let jobs = &self.job_manager;
+        self.disk_usage_deletes.retain(|(job_id, path)| {
+            match jobs.get_job(*job_id).map(|j| &j.status) {
+                Some(JobStatus::Running { .. } | JobStatus::Visible | JobStatus::Paused) => true,
+                Some(JobStatus::Completed) => {
+                    deleted.push(path.clone());
+                    false
+                }
+                _ => false,
+            }
+        });
+        if let Some(du) = &mut self.disk_usage {
+            for path in &deleted {
+                du.remove_path(path);
+            }
+        }
+    }
+
+    /// Switch the active pane between its listing and a directory tree
+    /// rooted at its current directory
+    fn toggle_tree(&mut self) {
+        let pane = self.active_pane_mut();
+        if pane.tree.take().is_none() {
+            pane.tree = Some(DirTree::new(pane.path.clone(), pane.show_hidden));
+        }
+    }
+
+    /// Keys of a pane in tree mode. Returns false for keys left to the
+    /// normal handler: modified keys, switching panes, the job list, the
+    /// command line and quitting. Other keys are swallowed so they never act
+    /// on the hidden listing.
+    fn handle_tree_key(&mut self, key: KeyCode, modifiers: KeyModifiers) -> bool {
+        if !modifiers.difference(KeyModifiers::SHIFT).is_empty()
+            || matches!(
+                key,
+                KeyCode::Tab
+                    | KeyCode::Esc
+                    | KeyCode::Char('q')
+                    | KeyCode::Char('J')
+                    | KeyCode::Char(':')
+            )
+        {
+            return false;
+        }
+        let Some(tree) = self.active_pane_mut().tree.as_mut() else {
+            return false;
+        };
+        let before = tree.selected_path().to_path_buf();
+        match key {
+            KeyCode::Up | KeyCode::Char('k') => tree.move_by(-1),
+            KeyCode::Down | KeyCode::Char('j') => tree.move_by(1),
+            KeyCode::PageUp => tree.move_by(-(PAGE_SCROLL_SIZE as isize)),
+            KeyCode::PageDown => tree.move_by(PAGE_SCROLL_SIZE as isize),
+            KeyCode::Home => tree.list_state.select(Some(0)),
+            KeyCode::End => tree.move_to_end(),
+            KeyCode::Right | KeyCode::Char('l') => tree.expand_or_enter(),
+            KeyCode::Left | KeyCode::Char('h') => tree.collapse_or_leave(),
+            // The other pane already lists the directory; go work in it
+            KeyCode::Enter => self.toggle_pane(),
+            _ => {}
+        }
+        if self
+            .active_pane()
+            .tree
+            .as_ref()
+            .is_some_and(|t| t.selected_path() != before)
+        {
+            self.follow_tree();
+        }
+        true
+    }
+
+    /// Keys of a pane browsing an archive. Viewing and copying out work on
+    /// the archive's members; commands that would change files are refused,
+    /// as archives are browsed read-only. Returns false for keys left to the
+    /// normal handler.
+    fn handle_archive_key(&mut self, key: KeyCode, modifiers: KeyModifiers) -> bool {
+        let ctrl = modifiers.contains(KeyModifiers::CONTROL);
+        let alt = modifiers.contains(KeyModifiers::ALT);
+        match key {
+            KeyCode::F(3) => self.view_archive_member(),
+            KeyCode::F(5) => self.copy_out_of_archive(),
+            KeyCode::F(2)
+            | KeyCode::F(4)
+            | KeyCode::F(6)
+            | KeyCode::F(7)
+            | KeyCode::F(8)
+            | KeyCode::Delete => self.refuse_in_archive(),
+            KeyCode::Char('x') if ctrl => self.refuse_in_archive(),
+            KeyCode::Char('r') if alt => self.refuse_in_archive(),
+            KeyCode::Char('R' | 'A' | 'D' | '=') if !ctrl && !alt => self.refuse_in_archive(),
+            _ => return false,
+        }
+        true
+    }
+
+    fn refuse_in_archive(&mut self) {
+        self.error_message = Some(("Archives are read-only".to_owned(), Instant::now()));
+    }
+
+    /// Open the archive member under the cursor in the viewer, via a
+    /// temporary copy
+    fn view_archive_member(&mut self) {
+        let pane = self.active_pane();
+        let (Some(view), Some(entry)) = (&pane.archive, pane.selected_entry()) else {
+            return;
+        };
+        if entry.is_dir {
+            return;
+        }
+        let Some(member) = view.member(&entry.path) else {
+            return;
+        };
+        // One byte past the limit lets the viewer tell the copy was cut short
+        let limit = MAX_FILE_SIZE as u64 + 1;
+        match archive::extract_for_viewing(&view.file, &member, limit) {
+            Ok(copy) => {
+                self.ui_mode = UIMode::FileViewer {
+                    viewer: Box::new(FileViewer::new(copy)),
+                    return_to: None,
+                };
+            }
+            Err(msg) => self.error_message = Some((msg, Instant::now())),
+        }
+    }
+
+    /// Copy the selected archive members into the other pane's directory
+    fn copy_out_of_archive(&mut self) {
+        let (source, dest) = match self.active_pane {
+            Pane::Left => (&self.left, &self.right),
+            Pane::Right => (&self.right, &self.left),
+        };
+        if dest.archive.is_some() {
+            self.error_message = Some(("Cannot copy into an archive".to_owned(), Instant::now()));
+            return;
+        }
+        // Nothing to copy until the listing has been read
+        let Some((view, listing)) = source
+            .archive
+            .as_ref()
+            .and_then(|v| Some((v, v.listing.clone()?)))
+        else {
+            return;
+        };
+        let members: Vec<String> = source
+            .selected_entries()
+            .iter()
+            .filter(|e| e.name != "..")
+            .filter_map(|e| view.member(&e.path))
+            .collect();
+        if members.is_empty() {
+            return;
+        }
+        let (file, base, dest_dir) = (view.file.clone(), view.dir.clone(), dest.path.clone());
+
+        self.active_pane_mut().selected.clear();
+        self.job_manager
+            .start_extract_job(file, Some(listing), members, base, dest_dir);
+    }
+
+    /// Open a new tab in the active pane showing the same directory
+    fn open_tab(&mut self) {
+        let current = self.active_pane();
+        let (path, show_hidden, size_mode) =
+            (current.path.clone(), current.show_hidden, current.size_mode);
+
+        match PaneState::new(path) {
+            Ok(mut tab) => {
+                if tab.show_hidden != show_hidden || tab.size_mode != size_mode {
+                    tab.show_hidden = show_hidden;
+                    tab.size_mode = size_mode;
+                    let _ = tab.load_entries();
+                }
+                let (tabs, live) = self.active_tabs_mut();
+                tabs.open(live, tab);
+            }
+            Err(e) => {
+                self.error_message = Some((format!("Cannot open tab: {}", e), Instant::now()));
+            }
+        }
+    }
+
     fn navigate_to_parent(&mut self) {
+        // In a panelized listing, going up returns to the search root
+        if self.active_pane_mut().exit_panel() {
+            return;
+        }
+        if self.active_pane_mut().archive_up() {
+            return;
+        }
         let pane = self.active_pane_mut();
         if let Some(parent) = pane.path.parent().map(|p| p.to_path_buf()) {
             let old_path = pane.path.clone();
@@ -205,7 +783,61 @@ impl App {
                 self.error_message = Some((msg, Instant::now()));
             } else {
                 self.active_pane_mut().list_state.select(Some(0));
+                self.record_visit();
+            }
+        }
+    }
+
+    /// Record the active pane's current directory in its history and the
+    /// frecency database. Called after every user navigation.
+    fn record_visit(&mut self) {
+        let pane = self.active_pane_mut();
+        // Paths inside archives can't be revisited as directories
+        if pane.archive.is_some() {
+            return;
+        }
+        let path = pane.path.clone();
+        pane.history.visit(&path);
+        self.frecency.visit(&path);
+    }
+
+    /// Open the frecency jump popup, leaving out the current directory
+    fn open_frecency_jump(&mut self) {
+        let current = &self.active_pane().path;
+        let candidates = self
+            .frecency
+            .ranked()
+            .into_iter()
+            .filter(|p| p != current)
+            .collect();
+        self.ui_mode = UIMode::FrecencyJump {
+            query: String::new(),
+            candidates,
+            selected: 0,
+        };
+    }
+
+    /// Go back or forward in the active pane's directory history
+    fn history_step(&mut self, back: bool) {
+        let pane = self.active_pane_mut();
+        let target = if back {
+            pane.history.back()
+        } else {
+            pane.history.forward()
+        };
+        let Some(target) = target else {
+            return;
+        };
+
+        if let Err(msg) = self.open_directory(target) {
+            // Undo the step so the history stays in sync with the pane
+            let pane = self.active_pane_mut();
+            if back {
+                pane.history.forward();
+            } else {
+                pane.history.back();
             }
+            self.error_message = Some((msg, Instant::now()));
         }
     }
 
@@ -216,19 +848,15 @@ impl App {
             KeyCode::Char('J') | KeyCode::Esc => {
                 self.ui_mode = UIMode::Normal;
             }
-            KeyCode::Up | KeyCode::Char('k') => {
-                if selected > 0 {
-                    self.ui_mode = UIMode::JobList {
-                        selected: selected - 1,
-                    };
-                }
+            KeyCode::Up | KeyCode::Char('k') if selected > 0 => {
+                self.ui_mode = UIMode::JobList {
+                    selected: selected - 1,
+                };
             }
-            KeyCode::Down | KeyCode::Char('j') => {
-                if selected < job_count.saturating_sub(1) {
-                    self.ui_mode = UIMode::JobList {
-                        selected: selected + 1,
-                    };
-                }
+            KeyCode::Down | KeyCode::Char('j') if selected < job_count.saturating_sub(1) => {
+                self.ui_mode = UIMode::JobList {
+                    selected: selected + 1,
+                };
             }
             KeyCode::Char('K') => {
                 // Kill selected job
@@ -262,6 +890,574 @@ impl App {
         }
     }
 
+    pub fn handle_hotlist(&mut self, key: KeyCode, selected: usize, rename: Option<String>) {
+        // Renaming the selected bookmark
+        if let Some(mut input) = rename {
+            match key {
+                KeyCode::Enter => {
+                    if !input.is_empty() {
+                        self.bookmarks.rename(selected, input);
+                    }
+                    self.ui_mode = UIMode::Hotlist {
+                        selected,
+                        rename: None,
+                    };
+                }
+                KeyCode::Esc => {
+                    self.ui_mode = UIMode::Hotlist {
+                        selected,
+                        rename: None,
+                    };
+                }
+                KeyCode::Backspace => {
+                    input.pop();
+                    self.ui_mode = UIMode::Hotlist {
+                        selected,
+                        rename: Some(input),
+                    };
+                }
+                KeyCode::Char(c) => {
+                    input.push(c);
+                    self.ui_mode = UIMode::Hotlist {
+                        selected,
+                        rename: Some(input),
+                    };
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        let count = self.bookmarks.items.len();
+        let selected = match key {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.ui_mode = UIMode::Normal;
+                return;
+            }
+            KeyCode::Enter => {
+                self.ui_mode = UIMode::Normal;
+                if let Some(bookmark) = self.bookmarks.items.get(selected) {
+                    let path = bookmark.path.clone();
+                    if let Err(msg) = self.change_directory(path) {
+                        self.error_message = Some((msg, Instant::now()));
+                    }
+                }
+                return;
+            }
+            KeyCode::Up | KeyCode::Char('k') => selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => (selected + 1).min(count.saturating_sub(1)),
+            KeyCode::Char('a') | KeyCode::Insert => {
+                let path = self.active_pane().path.clone();
+                self.bookmarks.add(&path)
+            }
+            KeyCode::Char('r') | KeyCode::F(2) => {
+                if let Some(bookmark) = self.bookmarks.items.get(selected) {
+                    self.ui_mode = UIMode::Hotlist {
+                        selected,
+                        rename: Some(bookmark.name.clone()),
+                    };
+                }
+                return;
+            }
+            KeyCode::Char('d') | KeyCode::Delete => {
+                self.bookmarks.remove(selected);
+                selected.min(self.bookmarks.items.len().saturating_sub(1))
+            }
+            KeyCode::Char('K') => self.bookmarks.move_item(selected, true),
+            KeyCode::Char('J') => self.bookmarks.move_item(selected, false),
+            _ => selected,
+        };
+
+        self.ui_mode = UIMode::Hotlist {
+            selected,
+            rename: None,
+        };
+    }
+
+    pub fn handle_dir_history(&mut self, key: KeyCode, selected: usize) {
+        let recent = self.active_pane().history.recent();
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.ui_mode = UIMode::Normal;
+            }
+            KeyCode::Enter => {
+                self.ui_mode = UIMode::Normal;
+                if let Some(path) = recent.get(selected).cloned()
+                    && let Err(msg) = self.change_directory(path)
+                {
+                    self.error_message = Some((msg, Instant::now()));
+                }
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.ui_mode = UIMode::DirHistory {
+                    selected: selected.saturating_sub(1),
+                };
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.ui_mode = UIMode::DirHistory {
+                    selected: (selected + 1).min(recent.len().saturating_sub(1)),
+                };
+            }
+            _ => {}
+        }
+    }
+
+    /// Open the mount list with the cursor on the active pane's filesystem
+    fn open_mounts(&mut self, show_all: bool) {
+        let list = mounts::list_mounts(show_all);
+        let selected = mounts::mount_for(&list, &self.active_pane().path)
+            .and_then(|current| list.iter().position(|m| *m == current))
+            .unwrap_or(0);
+        self.mount_spaces = Some(mounts::query_spaces(&list));
+        self.ui_mode = UIMode::Mounts {
+            mounts: list.into_iter().map(|m| (m, None)).collect(),
+            selected,
+            show_all,
+        };
+    }
+
+    /// Fill in the space figures of the open mount list as they arrive
+    pub fn poll_mount_spaces(&mut self) {
+        let Some(rx) = &self.mount_spaces else {
+            return;
+        };
+        let UIMode::Mounts { mounts, .. } = &mut self.ui_mode else {
+            self.mount_spaces = None;
+            return;
+        };
+        for (mount_point, space) in rx.try_iter() {
+            for (mount, slot) in mounts.iter_mut() {
+                if mount.mount_point == mount_point {
+                    *slot = Some(space);
+                }
+            }
+        }
+    }
+
+    pub fn handle_mounts(
+        &mut self,
+        key: KeyCode,
+        mounts: Vec<(mounts::Mount, Option<mounts::DiskSpace>)>,
+        selected: usize,
+        show_all: bool,
+    ) {
+        let last = mounts.len().saturating_sub(1);
+        let selected = match key {
+            KeyCode::Esc | KeyCode::Char('q') => return,
+            KeyCode::Enter => {
+                if let Some((mount, _)) = mounts.get(selected)
+                    && let Err(msg) = self.change_directory(mount.mount_point.clone())
+                {
+                    self.error_message = Some((msg, Instant::now()));
+                }
+                return;
+            }
+            KeyCode::Char('a') => {
+                self.open_mounts(!show_all);
+                return;
+            }
+            KeyCode::Up | KeyCode::Char('k') => selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => (selected + 1).min(last),
+            KeyCode::Home | KeyCode::Char('g') => 0,
+            KeyCode::End | KeyCode::Char('G') => last,
+            _ => selected,
+        };
+        self.ui_mode = UIMode::Mounts {
+            mounts,
+            selected,
+            show_all,
+        };
+    }
+
+    pub fn handle_frecency_jump(
+        &mut self,
+        key: KeyCode,
+        mut query: String,
+        candidates: Vec<PathBuf>,
+        selected: usize,
+    ) {
+        let match_count = filter_jump_candidates(&candidates, &query).len();
+        let mut selected = selected;
+        match key {
+            KeyCode::Esc => return,
+            KeyCode::Enter => {
+                let target = filter_jump_candidates(&candidates, &query)
+                    .get(selected)
+                    .map(|p| p.to_path_buf());
+                if let Some(path) = target
+                    && let Err(msg) = self.change_directory(path)
+                {
+                    self.error_message = Some((msg, Instant::now()));
+                }
+                return;
+            }
+            KeyCode::Up => selected = selected.saturating_sub(1),
+            KeyCode::Down => selected = (selected + 1).min(match_count.saturating_sub(1)),
+            KeyCode::Backspace => {
+                query.pop();
+                selected = 0;
+            }
+            KeyCode::Char(c) => {
+                query.push(c);
+                selected = 0;
+            }
+            _ => {}
+        }
+        self.ui_mode = UIMode::FrecencyJump {
+            query,
+            candidates,
+            selected,
+        };
+    }
+
+    /// Open the Find File dialog, pre-filled from the previous search
+    fn open_find_dialog(&mut self) {
+        let form = self
+            .find
+            .as_ref()
+            .map(|f| f.form.clone())
+            .unwrap_or_default();
+        self.ui_mode = UIMode::FindFile { form };
+    }
+
+    pub fn handle_find_file(&mut self, key: KeyCode, mut form: FindForm) {
+        match key {
+            KeyCode::Esc => return,
+            KeyCode::Enter => {
+                match form.compile(&self.active_pane().path) {
+                    Ok(query) => {
+                        self.find = Some(FindSearch::start(form, query));
+                        self.ui_mode = UIMode::FindResults { selected: 0 };
+                    }
+                    Err(msg) => {
+                        self.error_message = Some((msg, Instant::now()));
+                        self.ui_mode = UIMode::FindFile { form };
+                    }
+                }
+                return;
+            }
+            KeyCode::Tab | KeyCode::Down => form.focus_next(),
+            KeyCode::BackTab | KeyCode::Up => form.focus_prev(),
+            KeyCode::Backspace => {
+                if let Some(text) = form.focused_text_mut() {
+                    text.pop();
+                }
+            }
+            KeyCode::Char(c) => match form.focused_text_mut() {
+                Some(text) => text.push(c),
+                None if c == ' ' => form.toggle_focused(),
+                None => {}
+            },
+            _ => {}
+        }
+        self.ui_mode = UIMode::FindFile { form };
+    }
+
+    pub fn handle_find_results(&mut self, key: KeyCode, selected: usize) {
+        let Some(find) = &self.find else {
+            self.ui_mode = UIMode::Normal;
+            return;
+        };
+        let count = find.results.len();
+        let current = find.results.get(selected).cloned();
+
+        let mut selected = selected;
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                find.cancel();
+                self.ui_mode = UIMode::Normal;
+                return;
+            }
+            KeyCode::Char('s') => find.cancel(),
+            KeyCode::Char('n') => {
+                find.cancel();
+                self.open_find_dialog();
+                return;
+            }
+            KeyCode::Up | KeyCode::Char('k') => selected = selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                selected = (selected + 1).min(count.saturating_sub(1));
+            }
+            KeyCode::PageUp => selected = selected.saturating_sub(PAGE_SCROLL_SIZE),
+            KeyCode::PageDown => {
+                selected = (selected + PAGE_SCROLL_SIZE).min(count.saturating_sub(1));
+            }
+            KeyCode::Home => selected = 0,
+            KeyCode::End => selected = count.saturating_sub(1),
+            // Go to the file: open its directory with the cursor on it
+            KeyCode::Enter => {
+                let Some(path) = current else {
+                    return;
+                };
+                self.ui_mode = UIMode::Normal;
+                let dir = path.parent().unwrap_or(&path).to_path_buf();
+                match self.change_directory(dir) {
+                    Ok(()) => self.active_pane_mut().select_path(&path),
+                    Err(msg) => self.error_message = Some((msg, Instant::now())),
+                }
+                return;
+            }
+            KeyCode::F(3) | KeyCode::Char('v') => {
+                if let Some(path) = current.filter(|p| !p.is_dir()) {
+                    self.ui_mode = UIMode::FileViewer {
+                        viewer: Box::new(FileViewer::new(path)),
+                        return_to: Some(Box::new(UIMode::FindResults { selected })),
+                    };
+                }
+                return;
+            }
+            // Load the results into the active pane as a virtual listing
+            KeyCode::Char('p') => {
+                find.cancel();
+                let root = find.start_dir.clone();
+                let results = find.results.clone();
+                self.ui_mode = UIMode::Normal;
+                self.active_pane_mut().panelize(root, results);
+                return;
+            }
+            _ => {}
+        }
+        self.ui_mode = UIMode::FindResults { selected };
+    }
+
+    pub fn handle_grep_form(&mut self, key: KeyCode, mut form: GrepForm) {
+        match key {
+            KeyCode::Esc => return,
+            KeyCode::Enter => {
+                match form.compile(&self.active_pane().path) {
+                    Ok(query) => {
+                        self.grep = Some(GrepSearch::start(form, query));
+                        self.ui_mode = UIMode::GrepResults { selected: 0 };
+                    }
+                    Err(msg) => {
+                        self.error_message = Some((msg, Instant::now()));
+                        self.ui_mode = UIMode::GrepForm { form };
+                    }
+                }
+                return;
+            }
+            KeyCode::Tab | KeyCode::Down => form.focus_next(),
+            KeyCode::BackTab | KeyCode::Up => form.focus_prev(),
+            KeyCode::Backspace => {
+                if let Some(text) = form.focused_text_mut() {
+                    text.pop();
+                }
+            }
+            KeyCode::Char(c) => match form.focused_text_mut() {
+                Some(text) => text.push(c),
+                None if c == ' ' => form.toggle_focused(),
+                None => {}
+            },
+            _ => {}
+        }
+        self.ui_mode = UIMode::GrepForm { form };
+    }
+
+    pub fn handle_grep_results(&mut self, key: KeyCode, selected: usize) {
+        let Some(grep) = &self.grep else {
+            self.ui_mode = UIMode::Normal;
+            return;
+        };
+        let count = grep.matches.len();
+        let current = grep.matches.get(selected).map(|m| (m.path.clone(), m.line));
+
+        let mut selected = selected;
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                grep.cancel();
+                self.ui_mode = UIMode::Normal;
+                return;
+            }
+            KeyCode::Char('s') => grep.cancel(),
+            KeyCode::Char('n') => {
+                grep.cancel();
+                self.ui_mode = UIMode::GrepForm {
+                    form: grep.form.clone(),
+                };
+                return;
+            }
+            KeyCode::Up | KeyCode::Char('k') => selected = selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                selected = (selected + 1).min(count.saturating_sub(1));
+            }
+            KeyCode::PageUp => selected = selected.saturating_sub(PAGE_SCROLL_SIZE),
+            KeyCode::PageDown => {
+                selected = (selected + PAGE_SCROLL_SIZE).min(count.saturating_sub(1));
+            }
+            KeyCode::Home => selected = 0,
+            KeyCode::End => selected = count.saturating_sub(1),
+            // View the file at the matching line, coming back here afterwards
+            KeyCode::Enter | KeyCode::F(3) => {
+                if let Some((path, line)) = current {
+                    let mut viewer = FileViewer::new(path);
+                    viewer.goto_line(line);
+                    self.ui_mode = UIMode::FileViewer {
+                        viewer: Box::new(viewer),
+                        return_to: Some(Box::new(UIMode::GrepResults { selected })),
+                    };
+                }
+                return;
+            }
+            // Go to the file in the active pane
+            KeyCode::Char('o') => {
+                let Some((path, _)) = current else {
+                    return;
+                };
+                self.ui_mode = UIMode::Normal;
+                let dir = path.parent().unwrap_or(&path).to_path_buf();
+                match self.change_directory(dir) {
+                    Ok(()) => self.active_pane_mut().select_path(&path),
+                    Err(msg) => self.error_message = Some((msg, Instant::now())),
+                }
+                return;
+            }
+            _ => {}
+        }
+        self.ui_mode = UIMode::GrepResults { selected };
+    }
+
+    pub fn handle_compare_dirs(&mut self, key: KeyCode, method: CompareMethod, recursive: bool) {
+        match key {
+            KeyCode::Esc => self.ui_mode = UIMode::Normal,
+            KeyCode::Char('m') => {
+                self.ui_mode = UIMode::CompareDirs {
+                    method: method.cycle(),
+                    recursive,
+                };
+            }
+            KeyCode::Char('r') => {
+                self.ui_mode = UIMode::CompareDirs {
+                    method,
+                    recursive: !recursive,
+                };
+            }
+            KeyCode::Enter => {
+                if self.left.panel.is_some() || self.right.panel.is_some() {
+                    self.ui_mode = UIMode::Normal;
+                    self.error_message = Some((
+                        "Cannot compare a panelized listing".to_owned(),
+                        Instant::now(),
+                    ));
+                    return;
+                }
+                // Hidden files only take part if both panes show them
+                let include_hidden = self.left.show_hidden && self.right.show_hidden;
+                self.compare = Some(CompareRun::start(
+                    self.left.path.clone(),
+                    self.right.path.clone(),
+                    method,
+                    recursive,
+                    include_hidden,
+                ));
+                self.ui_mode = UIMode::CompareResults { selected: 0 };
+            }
+            _ => {}
+        }
+    }
+
+    pub fn handle_compare_results(&mut self, key: KeyCode, selected: usize) {
+        let Some(run) = &self.compare else {
+            self.ui_mode = UIMode::Normal;
+            return;
+        };
+        let count = run.differences.as_ref().map_or(0, |d| d.len());
+
+        let selected = match key {
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => {
+                // Drop an unfinished comparison so partial results never get marked
+                if run.differences.is_none() {
+                    self.compare = None;
+                }
+                self.ui_mode = UIMode::Normal;
+                return;
+            }
+            KeyCode::Up | KeyCode::Char('k') => selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => (selected + 1).min(count.saturating_sub(1)),
+            KeyCode::PageUp => selected.saturating_sub(PAGE_SCROLL_SIZE),
+            KeyCode::PageDown => (selected + PAGE_SCROLL_SIZE).min(count.saturating_sub(1)),
+            _ => selected,
+        };
+        self.ui_mode = UIMode::CompareResults { selected };
+    }
+
+    pub fn handle_fuzzy_finder(&mut self, key: KeyCode, modifiers: KeyModifiers) {
+        let Some(finder) = &mut self.finder else {
+            self.ui_mode = UIMode::Normal;
+            return;
+        };
+
+        let ctrl = modifiers.contains(KeyModifiers::CONTROL);
+        match key {
+            KeyCode::Esc => {
+                self.finder = None;
+                self.ui_mode = UIMode::Normal;
+            }
+            KeyCode::Up => finder.move_selection(-1),
+            KeyCode::Down => finder.move_selection(1),
+            KeyCode::Char('p') if ctrl => finder.move_selection(-1),
+            KeyCode::Char('n') if ctrl => finder.move_selection(1),
+            KeyCode::PageUp => finder.move_selection(-(PAGE_SCROLL_SIZE as isize)),
+            KeyCode::PageDown => finder.move_selection(PAGE_SCROLL_SIZE as isize),
+            KeyCode::Char('g') if ctrl => finder.toggle_gitignore(),
+            // Open in the viewer, coming back to the finder afterwards
+            _ if key == KeyCode::F(3) || (ctrl && key == KeyCode::Char('v')) => {
+                if let Some(path) = finder.selected_path().filter(|_| !finder.selected_is_dir()) {
+                    self.ui_mode = UIMode::FileViewer {
+                        viewer: Box::new(FileViewer::new(path)),
+                        return_to: Some(Box::new(UIMode::FuzzyFinder)),
+                    };
+                }
+            }
+            // Go to the entry's directory with the cursor on it
+            KeyCode::Enter => {
+                let Some(path) = finder.selected_path() else {
+                    return;
+                };
+                self.finder = None;
+                self.ui_mode = UIMode::Normal;
+                let dir = path.parent().unwrap_or(&path).to_path_buf();
+                match self.change_directory(dir) {
+                    Ok(()) => self.active_pane_mut().select_path(&path),
+                    Err(msg) => self.error_message = Some((msg, Instant::now())),
+                }
+            }
+            KeyCode::Backspace => {
+                let mut query = finder.query.clone();
+                query.pop();
+                finder.set_query(query);
+            }
+            KeyCode::Char(c) if !ctrl => {
+                let query = format!("{}{}", finder.query, c);
+                finder.set_query(query);
+            }
+            _ => {}
+        }
+    }
+
+    /// Handle the letter after `m` (set mark) or `'` (jump to mark)
+    pub fn handle_mark_pending(&mut self, key: KeyCode, set: bool) {
+        self.ui_mode = UIMode::Normal;
+
+        let KeyCode::Char(mark) = key else {
+            return;
+        };
+        if !mark.is_ascii_alphanumeric() {
+            return;
+        }
+
+        if set {
+            let path = self.active_pane().path.clone();
+            self.bookmarks.set_mark(mark, &path);
+        } else if let Some(bookmark) = self.bookmarks.find_mark(mark) {
+            let path = bookmark.path.clone();
+            if let Err(msg) = self.change_directory(path) {
+                self.error_message = Some((msg, Instant::now()));
+            }
+        } else {
+            self.error_message = Some((format!("Mark '{}' not set", mark), Instant::now()));
+        }
+    }
+
     pub fn handle_confirm_overwrite(&mut self, key: KeyCode, job_id: JobId) {
         let resolution = match key {
             KeyCode::Char('o') => Some(ConflictResolution::Overwrite),
@@ -306,8 +1502,30 @@ impl App {
         };
     }
 
-    pub fn handle_confirm_delete(&mut self, key: KeyCode, entries: Vec<Entry>) {
+    pub fn handle_confirm_delete(
+        &mut self,
+        key: KeyCode,
+        entries: Vec<Entry>,
+        has_job_conflict: bool,
+    ) {
+        // Deleting from the disk usage view returns there
+        let from_disk_usage = self
+            .disk_usage
+            .as_ref()
+            .is_some_and(|du| du.deleting.is_some());
         match handle_yes_no_keys(key) {
+            DialogResult::Accept if from_disk_usage => {
+                let paths: Vec<PathBuf> = entries.iter().map(|e| e.path.clone()).collect();
+                if let Some(du) = &mut self.disk_usage
+                    && let Some(path) = du.deleting.take()
+                {
+                    let parent_dir = path.parent().unwrap_or(Path::new("/")).to_path_buf();
+                    // The entry leaves the tree once the job has deleted it
+                    let job_id = self.job_manager.start_delete_job(paths, parent_dir);
+                    self.disk_usage_deletes.push((job_id, path));
+                }
+                self.ui_mode = UIMode::DiskUsage;
+            }
             DialogResult::Accept => {
                 // Get parent directory for refresh after deletion
                 let parent_dir = self.active_pane().path.clone();
@@ -322,6 +1540,12 @@ impl App {
                 self.active_pane_mut().selected.clear();
                 self.ui_mode = UIMode::Normal;
             }
+            DialogResult::Reject if from_disk_usage => {
+                if let Some(du) = &mut self.disk_usage {
+                    du.deleting = None;
+                }
+                self.ui_mode = UIMode::DiskUsage;
+            }
             DialogResult::Reject => {
                 self.ui_mode = UIMode::Normal;
             }
@@ -329,7 +1553,7 @@ impl App {
                 // Put the entries back
                 self.ui_mode = UIMode::ConfirmDelete {
                     entries,
-                    has_job_conflict: false, // Recalculate if needed
+                    has_job_conflict,
                 };
             }
         }
@@ -389,6 +1613,55 @@ impl App {
         }
     }
 
+    pub fn handle_select_group(
+        &mut self,
+        key: KeyCode,
+        modifiers: KeyModifiers,
+        mut input: String,
+        select: bool,
+        mut files_only: bool,
+        mut case_sensitive: bool,
+    ) {
+        match key {
+            KeyCode::Char('f') if modifiers.contains(KeyModifiers::CONTROL) => {
+                files_only = !files_only;
+            }
+            KeyCode::Char('t') if modifiers.contains(KeyModifiers::CONTROL) => {
+                case_sensitive = !case_sensitive;
+            }
+            KeyCode::Enter => {
+                if !input.is_empty() {
+                    let matched = self.active_pane_mut().select_by_pattern(
+                        &input,
+                        files_only,
+                        case_sensitive,
+                        select,
+                    );
+                    if matched == 0 {
+                        self.error_message =
+                            Some((format!("No entries match '{}'", input), Instant::now()));
+                    }
+                }
+                return;
+            }
+            KeyCode::Esc => return,
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(c) => {
+                input.push(c);
+            }
+            _ => {}
+        }
+
+        self.ui_mode = UIMode::SelectGroup {
+            input,
+            select,
+            files_only,
+            case_sensitive,
+        };
+    }
+
     fn search_jump(&mut self, query: &str) {
         let pane = self.active_pane_mut();
         let query_lower = query.to_lowercase();
@@ -477,9 +1750,15 @@ impl App {
             _ => return,
         };
 
+        // Panelized entries are named by relative path; rename the file name only
+        let input = entry
+            .path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or(entry.name);
         self.ui_mode = UIMode::RenameInput {
             original: entry.path,
-            input: entry.name,
+            input,
         };
     }
 
@@ -532,6 +1811,377 @@ impl App {
         }
     }
 
+    fn open_attributes(&mut self) {
+        let targets: Vec<PathBuf> = self
+            .active_pane()
+            .selected_entries()
+            .iter()
+            .filter(|e| e.name != "..")
+            .map(|e| e.path.clone())
+            .collect();
+        if targets.is_empty() {
+            return;
+        }
+        match AttrForm::new(targets) {
+            Ok(form) => self.ui_mode = UIMode::Attributes { form },
+            Err(msg) => self.error_message = Some((msg, Instant::now())),
+        }
+    }
+
+    pub fn handle_attributes(&mut self, key: KeyCode, mut form: AttrForm) {
+        match key {
+            KeyCode::Esc => {
+                self.ui_mode = UIMode::Normal;
+                return;
+            }
+            KeyCode::Enter => {
+                match form.compile() {
+                    Ok(change) if change.is_empty() => self.ui_mode = UIMode::Normal,
+                    Ok(change) => {
+                        let parent_dir = self.active_pane().path.clone();
+                        self.active_pane_mut().selected.clear();
+                        self.job_manager
+                            .start_attributes_job(form.targets, change, parent_dir);
+                        self.ui_mode = UIMode::Normal;
+                    }
+                    Err(msg) => {
+                        self.error_message = Some((msg, Instant::now()));
+                        self.ui_mode = UIMode::Attributes { form };
+                    }
+                }
+                return;
+            }
+            KeyCode::Tab | KeyCode::Down | KeyCode::Right => form.focus_next(),
+            KeyCode::BackTab | KeyCode::Up | KeyCode::Left => form.focus_prev(),
+            KeyCode::Backspace => {
+                if let Some(text) = form.focused_text_mut() {
+                    text.pop();
+                    form.text_changed();
+                }
+            }
+            KeyCode::Char(c) => match form.focused_text_mut() {
+                Some(text) => {
+                    text.push(c);
+                    form.text_changed();
+                }
+                None if c == ' ' || c == 'x' => form.toggle_focused(),
+                None => {}
+            },
+            _ => {}
+        }
+        self.ui_mode = UIMode::Attributes { form };
+    }
+
+    fn open_properties(&mut self) {
+        let Some(entry) = self.active_pane().selected_entry() else {
+            return;
+        };
+        // ".." shows the current directory
+        let path = if entry.name == ".." {
+            self.active_pane().path.clone()
+        } else {
+            entry.path.clone()
+        };
+        self.properties = Some(PropertiesView::start(path));
+        self.ui_mode = UIMode::Properties;
+    }
+
+    /// Second key of a Ctrl+X sequence, as in Midnight Commander
+    pub fn handle_ctrl_x_prefix(&mut self, key: KeyCode, modifiers: KeyModifiers) {
+        self.ui_mode = UIMode::Normal;
+        match key {
+            KeyCode::Char('s') if modifiers.contains(KeyModifiers::CONTROL) => {
+                self.open_link_dialog(LinkKind::EditSymlink);
+            }
+            KeyCode::Char('s') => self.open_link_dialog(LinkKind::Symlink),
+            KeyCode::Char('l') => self.open_link_dialog(LinkKind::Hardlink),
+            KeyCode::Char('c') => self.open_compress_dialog(),
+            KeyCode::Char('x') => self.open_extract_dialog(),
+            KeyCode::Char('i') => self.side_panel = self.side_panel.toggle(SidePanel::Info),
+            KeyCode::Char('q') => self.side_panel = self.side_panel.toggle(SidePanel::QuickView),
+            KeyCode::Char('t') => self.toggle_tree(),
+            KeyCode::Char('m') => self.open_mounts(false),
+            KeyCode::Char('u') => {
+                self.disk_usage = Some(DiskUsage::start(self.active_pane().path.clone()));
+                self.ui_mode = UIMode::DiskUsage;
+            }
+            _ => {}
+        }
+    }
+
+    fn open_link_dialog(&mut self, kind: LinkKind) {
+        let entry = match self.active_pane().selected_entry() {
+            Some(e) if e.name != ".." => e.clone(),
+            _ => return,
+        };
+        let form = match kind {
+            LinkKind::EditSymlink => LinkForm::edit_symlink(&entry.path),
+            LinkKind::Hardlink if entry.is_dir => Err("Cannot hard link a directory".to_string()),
+            _ => Ok(LinkForm::new(kind, &entry.path, &self.other_pane().path)),
+        };
+        match form {
+            Ok(form) => self.ui_mode = UIMode::LinkInput { form },
+            Err(msg) => self.error_message = Some((msg, Instant::now())),
+        }
+    }
+
+    pub fn handle_link_input(&mut self, key: KeyCode, modifiers: KeyModifiers, mut form: LinkForm) {
+        match key {
+            KeyCode::Esc => {
+                self.ui_mode = UIMode::Normal;
+                return;
+            }
+            KeyCode::Enter => {
+                match form.compile() {
+                    Ok(op) => {
+                        let parent_dir = op.link().parent().unwrap_or(Path::new("/")).to_path_buf();
+                        self.job_manager.start_link_job(op, parent_dir);
+                        self.ui_mode = UIMode::Normal;
+                    }
+                    Err(msg) => {
+                        self.error_message = Some((msg, Instant::now()));
+                        self.ui_mode = UIMode::LinkInput { form };
+                    }
+                }
+                return;
+            }
+            KeyCode::Tab | KeyCode::Down => form.focus_next(),
+            KeyCode::BackTab | KeyCode::Up => form.focus_prev(),
+            KeyCode::Char('r') if modifiers.contains(KeyModifiers::CONTROL) => {
+                form.toggle_relative()
+            }
+            KeyCode::Backspace => {
+                if let Some(text) = form.focused_text_mut() {
+                    text.pop();
+                }
+            }
+            KeyCode::Char(c) => match form.focused_text_mut() {
+                Some(text) => text.push(c),
+                None if c == ' ' => form.toggle_relative(),
+                None => {}
+            },
+            _ => {}
+        }
+        self.ui_mode = UIMode::LinkInput { form };
+    }
+
+    fn open_compress_dialog(&mut self) {
+        if self.active_pane().archive.is_some() || self.other_pane().archive.is_some() {
+            self.refuse_in_archive();
+            return;
+        }
+        let sources: Vec<PathBuf> = self
+            .active_pane()
+            .selected_entries()
+            .iter()
+            .filter(|e| e.name != "..")
+            .map(|e| e.path.clone())
+            .collect();
+        if sources.is_empty() {
+            return;
+        }
+        let form = CompressForm::new(sources, &self.other_pane().path);
+        self.ui_mode = UIMode::Compress { form };
+    }
+
+    pub fn handle_compress_input(&mut self, key: KeyCode, mut form: CompressForm) {
+        match key {
+            KeyCode::Esc => {
+                self.ui_mode = UIMode::Normal;
+                return;
+            }
+            KeyCode::Enter => {
+                match form.compile() {
+                    Ok(op) => {
+                        self.active_pane_mut().selected.clear();
+                        self.job_manager.start_compress_job(op);
+                        self.ui_mode = UIMode::Normal;
+                    }
+                    Err(msg) => {
+                        self.error_message = Some((msg, Instant::now()));
+                        self.ui_mode = UIMode::Compress { form };
+                    }
+                }
+                return;
+            }
+            KeyCode::Tab | KeyCode::Down => form.focus_next(),
+            KeyCode::BackTab | KeyCode::Up => form.focus_prev(),
+            KeyCode::Backspace => {
+                if let Some(text) = form.focused_text_mut() {
+                    text.pop();
+                }
+            }
+            KeyCode::Left => form.adjust(false),
+            KeyCode::Right => form.adjust(true),
+            KeyCode::Char(c) => match form.focused_text_mut() {
+                Some(text) => text.push(c),
+                None if c == ' ' || c == '+' => form.adjust(true),
+                None if c == '-' => form.adjust(false),
+                None => {}
+            },
+            _ => {}
+        }
+        self.ui_mode = UIMode::Compress { form };
+    }
+
+    fn open_extract_dialog(&mut self) {
+        let Some(entry) = self
+            .active_pane()
+            .selected_entry()
+            .filter(|e| !e.is_dir && ArchiveFormat::detect(&e.path).is_some())
+            .cloned()
+        else {
+            self.error_message = Some(("Not an archive".to_owned(), Instant::now()));
+            return;
+        };
+        if self.other_pane().archive.is_some() {
+            self.refuse_in_archive();
+            return;
+        }
+        let form = ExtractForm::new(entry.path, &self.other_pane().path);
+        self.ui_mode = UIMode::Extract { form };
+    }
+
+    pub fn handle_extract_input(&mut self, key: KeyCode, mut form: ExtractForm) {
+        match key {
+            KeyCode::Esc => {
+                self.ui_mode = UIMode::Normal;
+                return;
+            }
+            KeyCode::Enter => {
+                match form.compile() {
+                    Ok(dest_dir) => {
+                        self.job_manager.start_extract_job(
+                            form.archive,
+                            None,
+                            Vec::new(),
+                            String::new(),
+                            dest_dir,
+                        );
+                        self.ui_mode = UIMode::Normal;
+                    }
+                    Err(msg) => {
+                        self.error_message = Some((msg, Instant::now()));
+                        self.ui_mode = UIMode::Extract { form };
+                    }
+                }
+                return;
+            }
+            KeyCode::Tab | KeyCode::Down => form.focus_next(),
+            KeyCode::BackTab | KeyCode::Up => form.focus_prev(),
+            KeyCode::Backspace => {
+                if let Some(text) = form.focused_text_mut() {
+                    text.pop();
+                }
+            }
+            KeyCode::Char(c) => match form.focused_text_mut() {
+                Some(text) => text.push(c),
+                None if c == ' ' => form.subdir = !form.subdir,
+                None => {}
+            },
+            _ => {}
+        }
+        self.ui_mode = UIMode::Extract { form };
+    }
+
+    fn open_bulk_rename(&mut self) {
+        let sources: Vec<RenameSource> = self
+            .active_pane()
+            .selected_entries()
+            .iter()
+            .filter(|e| e.name != "..")
+            .map(|e| RenameSource::new(e.path.clone(), e.is_dir))
+            .collect();
+        if sources.is_empty() {
+            return;
+        }
+        let rename = BulkRename::new(RenameForm::default(), sources);
+        self.ui_mode = UIMode::BulkRename {
+            rename: Box::new(rename),
+        };
+    }
+
+    pub fn handle_bulk_rename(&mut self, key: KeyCode, mut rename: Box<BulkRename>) {
+        let rows = rename.preview.as_ref().map_or(0, |p| p.len());
+        match key {
+            KeyCode::Esc => return,
+            KeyCode::Enter => match rename.renames() {
+                Ok(renames) => {
+                    self.start_batch_rename(renames);
+                    return;
+                }
+                Err(msg) => self.error_message = Some((msg, Instant::now())),
+            },
+            KeyCode::Tab | KeyCode::Down => rename.form.focus_next(),
+            KeyCode::BackTab | KeyCode::Up => rename.form.focus_prev(),
+            KeyCode::PageUp => rename.scroll = rename.scroll.saturating_sub(PAGE_SCROLL_SIZE),
+            KeyCode::PageDown => {
+                rename.scroll = (rename.scroll + PAGE_SCROLL_SIZE).min(rows.saturating_sub(1));
+            }
+            KeyCode::Backspace => {
+                if let Some(text) = rename.form.focused_text_mut() {
+                    text.pop();
+                    rename.update_preview();
+                }
+            }
+            KeyCode::Char(c) => match rename.form.focused_text_mut() {
+                Some(text) => {
+                    text.push(c);
+                    rename.update_preview();
+                }
+                None if c == ' ' => {
+                    rename.form.toggle_focused();
+                    rename.update_preview();
+                }
+                None => {}
+            },
+            _ => {}
+        }
+        self.ui_mode = UIMode::BulkRename { rename };
+    }
+
+    /// Run a batch of renames in the active pane as one job. It can be undone
+    /// once it has finished without errors.
+    fn start_batch_rename(&mut self, renames: Vec<(PathBuf, PathBuf)>) {
+        let parent_dir = self.active_pane().path.clone();
+        self.active_pane_mut().selected.clear();
+        let job_id = self
+            .job_manager
+            .start_batch_rename_job(renames.clone(), parent_dir);
+        self.pending_batch_rename = Some((job_id, renames));
+    }
+
+    /// Remember the running batch rename for undo once it has completed; a
+    /// failed or cancelled batch renamed only part of the list and is dropped
+    pub fn poll_batch_rename(&mut self) {
+        let Some((job_id, _)) = &self.pending_batch_rename else {
+            return;
+        };
+        match self.job_manager.get_job(*job_id).map(|j| &j.status) {
+            Some(JobStatus::Running { .. } | JobStatus::Visible | JobStatus::Paused) => {}
+            Some(JobStatus::Completed) => {
+                self.last_batch_rename = self.pending_batch_rename.take().map(|(_, r)| r);
+            }
+            _ => self.pending_batch_rename = None,
+        }
+    }
+
+    /// Rename the entries of the last batch back to their old names
+    fn undo_batch_rename(&mut self) {
+        let Some(renames) = self.last_batch_rename.take() else {
+            self.error_message = Some(("No batch rename to undo".to_owned(), Instant::now()));
+            return;
+        };
+        let parent_dir = renames[0]
+            .0
+            .parent()
+            .unwrap_or(Path::new("/"))
+            .to_path_buf();
+        let reversed = renames.into_iter().map(|(from, to)| (to, from)).collect();
+        self.job_manager
+            .start_batch_rename_job(reversed, parent_dir);
+    }
+
     pub fn handle_rename_in_progress(&mut self, key: KeyCode, job_id: JobId) {
         // Only handle Escape to cancel
         if key == KeyCode::Esc {
@@ -661,9 +2311,9 @@ impl App {
         };
 
         // Build the completed path
-        let completed_word = if word_to_complete.starts_with("~/") {
-            format!("~/{}", &common)
-        } else if word_to_complete == "~" && !common.is_empty() {
+        let completed_word = if word_to_complete.starts_with("~/")
+            || (word_to_complete == "~" && !common.is_empty())
+        {
             format!("~/{}", &common)
         } else if expanded.ends_with('/') {
             format!("{}{}", word_to_complete, common)
@@ -711,7 +2361,11 @@ impl App {
                     .unwrap_or(current_path.clone())
             } else if path_str == "-" {
                 // cd - -> previous directory
-                self.previous_path.clone().unwrap_or(current_path.clone())
+                self.active_pane()
+                    .history
+                    .previous()
+                    .cloned()
+                    .unwrap_or(current_path.clone())
             } else if path_str.starts_with("~/") {
                 // cd ~/something -> home + path
                 std::env::var("HOME")
@@ -727,26 +2381,33 @@ impl App {
                 }
             };
 
-            // Canonicalize to resolve . and ..
-            let target = target.canonicalize().unwrap_or(target);
+            if let Err(msg) = self.change_directory(target) {
+                self.error_message = Some((format!("cd: {}", msg), Instant::now()));
+            }
 
-            // Try to navigate
-            let pane = self.active_pane_mut();
-            let old_path = pane.path.clone();
+            return Ok(());
+        }
 
-            if target.is_dir() {
-                pane.path = target;
-                if let Err(e) = pane.load_entries() {
-                    pane.path = old_path;
-                    let _ = pane.load_entries();
-                    self.error_message = Some((format!("cd: {}", e), Instant::now()));
-                } else {
-                    pane.list_state.select(Some(0));
-                    self.previous_path = Some(old_path);
+        // z <keywords> -> best frecency match, like zoxide
+        if command == "z" || command.starts_with("z ") {
+            let keywords: Vec<String> =
+                command[1..].split_whitespace().map(str::to_owned).collect();
+            let current_path = self.active_pane().path.clone();
+            let target = self
+                .frecency
+                .query(&keywords)
+                .into_iter()
+                .find(|p| *p != current_path && p.is_dir());
+
+            match target {
+                Some(target) => {
+                    if let Err(msg) = self.change_directory(target) {
+                        self.error_message = Some((format!("z: {}", msg), Instant::now()));
+                    }
+                }
+                None => {
+                    self.error_message = Some(("z: no match found".to_owned(), Instant::now()));
                 }
-            } else {
-                self.error_message =
-                    Some((format!("cd: not a directory: {}", path_str), Instant::now()));
             }
 
             return Ok(());
@@ -784,6 +2445,38 @@ impl App {
         Ok(())
     }
 
+    /// Navigate the active pane to `target` and record it in the history
+    pub fn change_directory(&mut self, target: PathBuf) -> Result<(), String> {
+        self.open_directory(target)?;
+        self.record_visit();
+        Ok(())
+    }
+
+    /// Load `target` into the active pane, keeping the old listing on failure.
+    /// Does not touch the history.
+    fn open_directory(&mut self, target: PathBuf) -> Result<(), String> {
+        // Canonicalize to resolve . and ..
+        let target = target.canonicalize().unwrap_or(target);
+        if !target.is_dir() {
+            return Err(format!("not a directory: {}", target.display()));
+        }
+
+        let pane = self.active_pane_mut();
+        let old_path = pane.path.clone();
+        let old_panel = pane.panel.take();
+        let old_archive = pane.archive.take();
+        pane.path = target;
+        if let Err(e) = pane.load_entries() {
+            pane.path = old_path;
+            pane.panel = old_panel;
+            pane.archive = old_archive;
+            let _ = pane.load_entries();
+            return Err(e.to_string());
+        }
+        pane.list_state.select(Some(0));
+        Ok(())
+    }
+
     pub fn handle_mouse(&mut self, kind: MouseEventKind, col: u16, row: u16) {
         // Check which pane was clicked
         let in_left = col >= self.left_area.x
@@ -840,38 +2533,68 @@ impl App {
             Some(e) if e.name != ".." => e.clone(),
             _ => return Ok(()),
         };
+        run_editor(terminal, &entry.path)
+    }
 
-        let editor = env::var("EDITOR").unwrap_or_else(|_| "vi".to_owned());
-
-        // Leave alternate screen and disable raw mode
-        let mut stdout = std::io::stdout();
-        let _ = crossterm::terminal::disable_raw_mode();
-        let _ = stdout.execute(LeaveAlternateScreen);
-
-        // Run the editor
-        let status = std::process::Command::new(&editor)
-            .arg(&entry.path)
-            .status();
-
-        // Re-enter alternate screen and enable raw mode
-        let _ = stdout.execute(EnterAlternateScreen);
-        let _ = crossterm::terminal::enable_raw_mode();
+    /// Rename the selected entries (or the whole listing if nothing is
+    /// selected) by editing their names in `$EDITOR`, then ask to confirm
+    pub fn rename_in_editor(&mut self, terminal: &mut DefaultTerminal) -> Result<(), String> {
+        let pane = self.active_pane();
+        let sources: Vec<PathBuf> = pane
+            .entries
+            .iter()
+            .filter(|e| e.name != ".." && (pane.selected.is_empty() || pane.is_selected(e)))
+            .map(|e| e.path.clone())
+            .collect();
+        if sources.is_empty() {
+            return Ok(());
+        }
 
-        // Force ratatui to do a full redraw
-        let _ = terminal.clear();
+        let listing = editor_listing(&sources)?;
+        let temp_dir = util::private_temp_dir("rmc-rename-")
+            .map_err(|e| format!("Cannot create a temporary directory: {}", e))?;
+        let listing_path = temp_dir.join("names.txt");
+        let edited = std::fs::File::options()
+            .write(true)
+            .create_new(true)
+            .open(&listing_path)
+            .and_then(|mut file| file.write_all(listing.as_bytes()))
+            .map_err(|e| format!("Cannot write {}: {}", listing_path.display(), e))
+            .and_then(|()| run_editor(terminal, &listing_path))
+            .and_then(|()| {
+                std::fs::read_to_string(&listing_path)
+                    .map_err(|e| format!("Cannot read {}: {}", listing_path.display(), e))
+            });
+        let _ = std::fs::remove_dir_all(&temp_dir);
+
+        let renames: Vec<(PathBuf, PathBuf)> = parse_editor_listing(&sources, &edited?)?
+            .into_iter()
+            .map(|p| {
+                let target = p.source.with_file_name(&p.new_name);
+                (p.source, target)
+            })
+            .collect();
+        if renames.is_empty() {
+            return Err("No names changed".to_owned());
+        }
+        let cycles = count_cycles(&renames);
+        self.ui_mode = UIMode::ConfirmBatchRename { renames, cycles };
+        Ok(())
+    }
 
-        match status {
-            Ok(exit_status) => {
-                if !exit_status.success() {
-                    return Err(format!("Editor exited with status {}", exit_status));
-                }
-            }
-            Err(e) => {
-                return Err(format!("Failed to run '{}': {}", editor, e));
+    pub fn handle_confirm_batch_rename(
+        &mut self,
+        key: KeyCode,
+        renames: Vec<(PathBuf, PathBuf)>,
+        cycles: usize,
+    ) {
+        match handle_yes_no_keys(key) {
+            DialogResult::Accept => self.start_batch_rename(renames),
+            DialogResult::Reject => {}
+            DialogResult::Pending => {
+                self.ui_mode = UIMode::ConfirmBatchRename { renames, cycles };
             }
         }
-
-        Ok(())
     }
 
     pub fn view_selected(&mut self) {
@@ -883,17 +2606,72 @@ impl App {
         let viewer = FileViewer::new(entry.path);
         self.ui_mode = UIMode::FileViewer {
             viewer: Box::new(viewer),
+            return_to: None,
+        };
+    }
+
+    /// Diff the file under the cursor against the same-named file in the
+    /// other pane, or against the other pane's cursor file if there is none
+    pub fn diff_selected(&mut self) -> Result<(), String> {
+        let entry = match self.active_pane().selected_entry() {
+            Some(e) if e.name != ".." && !e.is_dir => e,
+            _ => return Err("Select a file to diff".to_owned()),
+        };
+        let other = self.other_pane();
+        let counterpart = other
+            .entries
+            .iter()
+            .find(|e| !e.is_dir && e.name == entry.name && e.path != entry.path)
+            .or_else(|| other.selected_entry().filter(|e| !e.is_dir))
+            .ok_or("No file to diff against in the other pane")?;
+
+        let (left, right) = match self.active_pane {
+            Pane::Left => (entry.path.clone(), counterpart.path.clone()),
+            Pane::Right => (counterpart.path.clone(), entry.path.clone()),
         };
+        self.ui_mode = UIMode::DiffViewer {
+            viewer: Box::new(DiffViewer::new(left, right)),
+        };
+        Ok(())
     }
 
-    pub fn handle_file_viewer(&mut self, key: KeyCode, mut viewer: Box<FileViewer>) {
+    pub fn handle_diff_viewer(&mut self, key: KeyCode, mut viewer: Box<DiffViewer>) {
+        // Same estimate as the file viewer
+        let visible_height = 20usize;
+
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') => return,
+
+            KeyCode::Up | KeyCode::Char('k') => viewer.scroll_up(1),
+            KeyCode::Down | KeyCode::Char('j') => viewer.scroll_down(1, visible_height),
+            KeyCode::PageUp => viewer.scroll_up(visible_height),
+            KeyCode::PageDown => viewer.scroll_down(visible_height, visible_height),
+            KeyCode::Home | KeyCode::Char('g') => viewer.scroll_to_top(),
+            KeyCode::End | KeyCode::Char('G') => viewer.scroll_to_bottom(visible_height),
+
+            KeyCode::Char('n') => viewer.next_hunk(),
+            KeyCode::Char('p') | KeyCode::Char('N') => viewer.prev_hunk(),
+            KeyCode::Tab | KeyCode::Char('u') => viewer.toggle_layout(),
+
+            _ => {}
+        }
+
+        self.ui_mode = UIMode::DiffViewer { viewer };
+    }
+
+    pub fn handle_file_viewer(
+        &mut self,
+        key: KeyCode,
+        mut viewer: Box<FileViewer>,
+        return_to: Option<Box<UIMode>>,
+    ) {
         // Calculate visible height (will be set properly during render, use estimate)
         let visible_height = 20usize;
 
         match key {
             // Exit viewer
             KeyCode::Esc | KeyCode::Char('q') | KeyCode::F(3) => {
-                self.ui_mode = UIMode::Normal;
+                self.ui_mode = return_to.map(|mode| *mode).unwrap_or_default();
                 return;
             }
 
@@ -922,7 +2700,7 @@ impl App {
             _ => {}
         }
 
-        self.ui_mode = UIMode::FileViewer { viewer };
+        self.ui_mode = UIMode::FileViewer { viewer, return_to };
     }
 
     // ========================================================================
@@ -969,3 +2747,36 @@ impl App {
         }
     }
 }
+
+/// Run `$EDITOR` (vi by default) on `path`, suspending the TUI meanwhile
+fn run_editor(terminal: &mut DefaultTerminal, path: &Path) -> Result<(), String> {
+    let editor = env::var("EDITOR").unwrap_or_else(|_| "vi".to_owned());
+
+    // Leave alternate screen and disable raw mode
+    let mut stdout = std::io::stdout();
+    let _ = crossterm::terminal::disable_raw_mode();
+    let _ = stdout.execute(LeaveAlternateScreen);
+
+    // Run the editor
+    let status = std::process::Command::new(&editor).arg(path).status();
+
+    // Re-enter alternate screen and enable raw mode
+    let _ = stdout.execute(EnterAlternateScreen);
+    let _ = crossterm::terminal::enable_raw_mode();
+
+    // Force ratatui to do a full redraw
+    let _ = terminal.clear();
+
+    match status {
+        Ok(exit_status) => {
+            if !exit_status.success() {
+                return Err(format!("Editor exited with status {}", exit_status));
+            }
+        }
+        Err(e) => {
+            return Err(format!("Failed to run '{}': {}", editor, e));
+        }
+    }
+
+    Ok(())
+}
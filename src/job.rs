@@ -1,6 +1,7 @@
 use std::{
-    collections::{HashMap, VecDeque},
+    collections::{HashMap, HashSet, VecDeque},
     io::{BufReader, BufWriter, Read, Write},
+    os::unix::fs::PermissionsExt,
     path::{Path, PathBuf},
     sync::{
         Arc,
@@ -13,9 +14,16 @@ use std::{
 
 use walkdir::WalkDir;
 
-use crate::util::{
-    COPY_BUFFER_SIZE, JOB_VISIBILITY_THRESHOLD_MS, THROUGHPUT_HISTORY_SIZE,
-    THROUGHPUT_SAMPLE_INTERVAL_MS,
+use crate::{
+    archive::{self, ArchiveListing, EntryKind},
+    attributes::AttrChange,
+    compress::{ArchiveWriter, CompressOp},
+    extract,
+    links::LinkOp,
+    util::{
+        COPY_BUFFER_SIZE, JOB_VISIBILITY_THRESHOLD_MS, THROUGHPUT_HISTORY_SIZE,
+        THROUGHPUT_SAMPLE_INTERVAL_MS,
+    },
 };
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
@@ -27,6 +35,10 @@ pub enum JobType {
     Move,
     Delete,
     Rename,
+    Attributes,
+    Link,
+    Extract,
+    Compress,
 }
 
 #[derive(Clone)]
@@ -187,6 +199,10 @@ impl JobManager {
             JobType::Move => "Moving",
             JobType::Delete => "Deleting", // Not used, delete has its own method
             JobType::Rename => "Renaming", // Not used, rename has its own method
+            JobType::Attributes => "Changing", // Not used, attributes have their own method
+            JobType::Link => "Linking",    // Not used, links have their own method
+            JobType::Extract => "Extracting", // Not used, extraction has its own method
+            JobType::Compress => "Compressing", // Not used, compression has its own method
         };
 
         let description = format!(
@@ -378,6 +394,294 @@ impl JobManager {
         id
     }
 
+    /// Start a job that renames several entries as one batch. Targets may
+    /// swap or cycle through each other's names; if any rename fails, the
+    /// ones already done are rolled back.
+    pub fn start_batch_rename_job(
+        &mut self,
+        renames: Vec<(PathBuf, PathBuf)>,
+        parent_dir: PathBuf,
+    ) -> JobId {
+        let id = JobId(self.next_id);
+        self.next_id += 1;
+
+        let job = Job {
+            id,
+            job_type: JobType::Rename,
+            description: format!("Renaming {} items", renames.len()),
+            source: parent_dir, // Parent directory for refresh
+            destination: PathBuf::new(),
+            source_canonical: PathBuf::new(),
+            destination_canonical: PathBuf::new(),
+            status: JobStatus::Running {
+                started_at: Instant::now(),
+            },
+            progress: JobProgress::default(),
+            throughput: ThroughputTracker::new(),
+        };
+
+        self.jobs.insert(id, job);
+
+        let cancel_flag = Arc::new(AtomicBool::new(false));
+        let pause_flag = Arc::new(AtomicBool::new(false));
+        let (conflict_tx, _conflict_rx) = mpsc::channel();
+
+        let worker_handle = WorkerHandle {
+            cancel_flag: Arc::clone(&cancel_flag),
+            pause_flag: Arc::clone(&pause_flag),
+            conflict_tx,
+        };
+        self.workers.insert(id, worker_handle);
+
+        let progress_tx = self.progress_tx.clone();
+
+        thread::spawn(move || {
+            batch_rename_worker(id, renames, progress_tx, cancel_flag);
+        });
+
+        id
+    }
+
+    /// Start a job that changes permissions and/or ownership
+    pub fn start_attributes_job(
+        &mut self,
+        targets: Vec<PathBuf>,
+        change: AttrChange,
+        parent_dir: PathBuf,
+    ) -> JobId {
+        let id = JobId(self.next_id);
+        self.next_id += 1;
+
+        let description = if targets.len() == 1 && !change.recursive {
+            format!(
+                "Changing attributes of '{}'",
+                targets[0].file_name().unwrap_or_default().to_string_lossy()
+            )
+        } else {
+            format!("Changing attributes of {} items", targets.len())
+        };
+
+        let job = Job {
+            id,
+            job_type: JobType::Attributes,
+            description,
+            source: parent_dir, // Parent directory for refresh
+            destination: PathBuf::new(),
+            source_canonical: PathBuf::new(),
+            destination_canonical: PathBuf::new(),
+            status: JobStatus::Running {
+                started_at: Instant::now(),
+            },
+            progress: JobProgress::default(),
+            throughput: ThroughputTracker::new(),
+        };
+
+        self.jobs.insert(id, job);
+
+        let cancel_flag = Arc::new(AtomicBool::new(false));
+        let pause_flag = Arc::new(AtomicBool::new(false));
+        let (conflict_tx, _conflict_rx) = mpsc::channel();
+
+        let worker_handle = WorkerHandle {
+            cancel_flag: Arc::clone(&cancel_flag),
+            pause_flag: Arc::clone(&pause_flag),
+            conflict_tx,
+        };
+        self.workers.insert(id, worker_handle);
+
+        let progress_tx = self.progress_tx.clone();
+
+        thread::spawn(move || {
+            attributes_worker(id, targets, change, progress_tx, cancel_flag, pause_flag);
+        });
+
+        id
+    }
+
+    /// Start a job that creates a link or points a symlink elsewhere
+    pub fn start_link_job(&mut self, op: LinkOp, parent_dir: PathBuf) -> JobId {
+        let id = JobId(self.next_id);
+        self.next_id += 1;
+
+        let job = Job {
+            id,
+            job_type: JobType::Link,
+            description: op.description(),
+            source: parent_dir, // Directory of the link, for refresh
+            destination: PathBuf::new(),
+            source_canonical: PathBuf::new(),
+            destination_canonical: PathBuf::new(),
+            status: JobStatus::Running {
+                started_at: Instant::now(),
+            },
+            progress: JobProgress::default(),
+            throughput: ThroughputTracker::new(),
+        };
+
+        self.jobs.insert(id, job);
+
+        let cancel_flag = Arc::new(AtomicBool::new(false));
+        let pause_flag = Arc::new(AtomicBool::new(false));
+        let (conflict_tx, _conflict_rx) = mpsc::channel();
+
+        let worker_handle = WorkerHandle {
+            cancel_flag: Arc::clone(&cancel_flag),
+            pause_flag: Arc::clone(&pause_flag),
+            conflict_tx,
+        };
+        self.workers.insert(id, worker_handle);
+
+        let progress_tx = self.progress_tx.clone();
+
+        thread::spawn(move || {
+            link_worker(id, op, progress_tx, cancel_flag);
+        });
+
+        id
+    }
+
+    /// Start a job that copies `members` of an archive (and everything below
+    /// them) into `dest_dir`, with paths taken relative to the archive
+    /// directory `base`. No members extracts the whole archive. Without a
+    /// `listing` the job reads one first.
+    pub fn start_extract_job(
+        &mut self,
+        archive: PathBuf,
+        listing: Option<Arc<ArchiveListing>>,
+        members: Vec<String>,
+        base: String,
+        dest_dir: PathBuf,
+    ) -> JobId {
+        let id = JobId(self.next_id);
+        self.next_id += 1;
+
+        let description = match members.as_slice() {
+            [] => format!(
+                "Extracting '{}' to {}",
+                archive.file_name().unwrap_or_default().to_string_lossy(),
+                dest_dir.display()
+            ),
+            [member] => format!(
+                "Extracting '{}' to {}",
+                member.rsplit('/').next().unwrap_or(member),
+                dest_dir.display()
+            ),
+            _ => format!(
+                "Extracting {} items from '{}' to {}",
+                members.len(),
+                archive.file_name().unwrap_or_default().to_string_lossy(),
+                dest_dir.display()
+            ),
+        };
+
+        let source_canonical = archive.canonicalize().unwrap_or_else(|_| archive.clone());
+        let destination_canonical = dest_dir.canonicalize().unwrap_or_else(|_| dest_dir.clone());
+
+        let job = Job {
+            id,
+            job_type: JobType::Extract,
+            description,
+            source: archive.clone(),
+            destination: dest_dir.clone(),
+            source_canonical,
+            destination_canonical,
+            status: JobStatus::Running {
+                started_at: Instant::now(),
+            },
+            progress: JobProgress::default(),
+            throughput: ThroughputTracker::new(),
+        };
+
+        self.jobs.insert(id, job);
+
+        let cancel_flag = Arc::new(AtomicBool::new(false));
+        let pause_flag = Arc::new(AtomicBool::new(false));
+        let (conflict_tx, conflict_rx) = mpsc::channel();
+
+        let worker_handle = WorkerHandle {
+            cancel_flag: Arc::clone(&cancel_flag),
+            pause_flag: Arc::clone(&pause_flag),
+            conflict_tx,
+        };
+        self.workers.insert(id, worker_handle);
+
+        let progress_tx = self.progress_tx.clone();
+
+        thread::spawn(move || {
+            extract_worker(
+                id,
+                archive,
+                listing,
+                members,
+                base,
+                dest_dir,
+                progress_tx,
+                cancel_flag,
+                pause_flag,
+                conflict_rx,
+            );
+        });
+
+        id
+    }
+
+    /// Start a job that packs entries into a new archive
+    pub fn start_compress_job(&mut self, op: CompressOp) -> JobId {
+        let id = JobId(self.next_id);
+        self.next_id += 1;
+
+        // All sources come from the same directory
+        let source_dir = op
+            .sources
+            .first()
+            .and_then(|s| s.parent())
+            .unwrap_or(Path::new("/"))
+            .to_path_buf();
+        let source_canonical = source_dir
+            .canonicalize()
+            .unwrap_or_else(|_| source_dir.clone());
+        let destination_canonical = op
+            .archive
+            .canonicalize()
+            .unwrap_or_else(|_| op.archive.clone());
+
+        let job = Job {
+            id,
+            job_type: JobType::Compress,
+            description: op.description(),
+            source: source_dir,
+            destination: op.archive.clone(),
+            source_canonical,
+            destination_canonical,
+            status: JobStatus::Running {
+                started_at: Instant::now(),
+            },
+            progress: JobProgress::default(),
+            throughput: ThroughputTracker::new(),
+        };
+
+        self.jobs.insert(id, job);
+
+        let cancel_flag = Arc::new(AtomicBool::new(false));
+        let pause_flag = Arc::new(AtomicBool::new(false));
+        let (conflict_tx, conflict_rx) = mpsc::channel();
+
+        let worker_handle = WorkerHandle {
+            cancel_flag: Arc::clone(&cancel_flag),
+            pause_flag: Arc::clone(&pause_flag),
+            conflict_tx,
+        };
+        self.workers.insert(id, worker_handle);
+
+        let progress_tx = self.progress_tx.clone();
+
+        thread::spawn(move || {
+            compress_worker(id, op, progress_tx, cancel_flag, pause_flag, conflict_rx);
+        });
+
+        id
+    }
+
     pub fn send_conflict_resolution(&self, job_id: JobId, resolution: ConflictResolution) {
         if let Some(handle) = self.workers.get(&job_id) {
             let _ = handle.conflict_tx.send(resolution);
@@ -418,17 +722,26 @@ impl JobManager {
                 JobUpdate::Completed { job_id } => {
                     if let Some(job) = self.jobs.get_mut(&job_id) {
                         match job.job_type {
-                            JobType::Copy => {
+                            JobType::Copy | JobType::Extract => {
                                 completed_destinations.push(job.destination.clone());
                             }
+                            JobType::Compress => {
+                                // Destination is the archive itself
+                                if let Some(parent) = job.destination.parent() {
+                                    completed_destinations.push(parent.to_path_buf());
+                                }
+                            }
                             JobType::Move => {
                                 completed_destinations.push(job.destination.clone());
                                 if let Some(parent) = job.source.parent() {
                                     completed_sources.push(parent.to_path_buf());
                                 }
                             }
-                            JobType::Delete | JobType::Rename => {
-                                // For delete/rename, source holds the parent directory
+                            JobType::Delete
+                            | JobType::Rename
+                            | JobType::Attributes
+                            | JobType::Link => {
+                                // For these, source holds the parent directory
                                 completed_sources.push(job.source.clone());
                             }
                         }
@@ -467,10 +780,10 @@ impl JobManager {
         let now = Instant::now();
 
         for job in self.jobs.values_mut() {
-            if let JobStatus::Running { started_at } = job.status {
-                if now.duration_since(started_at) >= threshold {
-                    job.status = JobStatus::Visible;
-                }
+            if let JobStatus::Running { started_at } = job.status
+                && now.duration_since(started_at) >= threshold
+            {
+                job.status = JobStatus::Visible;
             }
         }
     }
@@ -490,7 +803,7 @@ impl JobManager {
     pub fn all_jobs(&self) -> Vec<&Job> {
         let mut jobs: Vec<_> = self.jobs.values().collect();
         // Sort by JobId descending so newest jobs appear first
-        jobs.sort_by(|a, b| b.id.0.cmp(&a.id.0));
+        jobs.sort_by_key(|j| std::cmp::Reverse(j.id.0));
         jobs
     }
 
@@ -499,13 +812,13 @@ impl JobManager {
     }
 
     pub fn dismiss_job(&mut self, job_id: JobId) {
-        if let Some(job) = self.jobs.get(&job_id) {
-            if matches!(
+        if let Some(job) = self.jobs.get(&job_id)
+            && matches!(
                 job.status,
                 JobStatus::Completed | JobStatus::Failed(_) | JobStatus::Cancelled
-            ) {
-                self.jobs.remove(&job_id);
-            }
+            )
+        {
+            self.jobs.remove(&job_id);
         }
     }
 
@@ -522,7 +835,12 @@ impl JobManager {
                     JobStatus::Running { .. } | JobStatus::Visible | JobStatus::Paused
                 )
             })
-            .filter(|j| j.job_type != JobType::Delete) // Only check copy/move jobs
+            .filter(|j| {
+                matches!(
+                    j.job_type,
+                    JobType::Copy | JobType::Move | JobType::Extract | JobType::Compress
+                )
+            })
             .collect();
 
         for path_canonical in paths_canonical {
@@ -717,70 +1035,121 @@ fn copy_file_with_progress(
     overwrite_all: &mut bool,
     skip_all: &mut bool,
 ) -> std::io::Result<()> {
-    // Check for conflict
-    if dest.exists() {
-        if *skip_all {
-            *files_processed += 1;
-            return Ok(());
-        }
-
-        if !*overwrite_all {
-            // Send conflict notification and wait for resolution
-            let _ = progress_tx.send(JobUpdate::ConflictDetected {
-                job_id,
-                file_path: dest.to_path_buf(),
-            });
-
-            // Wait for resolution (blocking)
-            match conflict_rx.recv() {
-                Ok(ConflictResolution::Overwrite) => {}
-                Ok(ConflictResolution::Skip) => {
-                    *files_processed += 1;
-                    return Ok(());
-                }
-                Ok(ConflictResolution::OverwriteAll) => {
-                    *overwrite_all = true;
-                }
-                Ok(ConflictResolution::SkipAll) => {
-                    *skip_all = true;
-                    *files_processed += 1;
-                    return Ok(());
-                }
-                Ok(ConflictResolution::Cancel) | Err(_) => {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::Interrupted,
-                        "Cancelled",
-                    ));
-                }
-            }
-        }
+    if !resolve_conflict(
+        dest,
+        progress_tx,
+        job_id,
+        conflict_rx,
+        files_processed,
+        overwrite_all,
+        skip_all,
+    )? {
+        return Ok(());
     }
 
     let src_file = std::fs::File::open(source)?;
-    let dest_file = std::fs::File::create(dest)?;
-
     let mut reader = BufReader::with_capacity(COPY_BUFFER_SIZE, src_file);
-    let mut writer = BufWriter::with_capacity(COPY_BUFFER_SIZE, dest_file);
-    let mut buffer = vec![0u8; COPY_BUFFER_SIZE];
-
     let file_name = source.file_name().map(|s| s.to_string_lossy().into_owned());
 
-    loop {
-        // Check cancel flag
-        if cancel_flag.load(Ordering::Relaxed) {
-            drop(writer);
-            let _ = std::fs::remove_file(dest);
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::Interrupted,
-                "Cancelled",
-            ));
-        }
+    write_with_progress(
+        &mut reader,
+        dest,
+        file_name,
+        progress_tx,
+        job_id,
+        cancel_flag,
+        pause_flag,
+        processed_bytes,
+        files_processed,
+    )
+}
 
-        // Wait while paused
-        while pause_flag.load(Ordering::Relaxed) {
-            if cancel_flag.load(Ordering::Relaxed) {
-                drop(writer);
-                let _ = std::fs::remove_file(dest);
+/// Ask the UI what to do about an existing `dest`, unless an earlier
+/// "all" answer covers it. Returns false if the file should be skipped.
+fn resolve_conflict(
+    dest: &Path,
+    progress_tx: &Sender<JobUpdate>,
+    job_id: JobId,
+    conflict_rx: &Receiver<ConflictResolution>,
+    files_processed: &mut u64,
+    overwrite_all: &mut bool,
+    skip_all: &mut bool,
+) -> std::io::Result<bool> {
+    // A dangling symlink is a conflict too
+    if std::fs::symlink_metadata(dest).is_err() {
+        return Ok(true);
+    }
+    if *skip_all {
+        *files_processed += 1;
+        return Ok(false);
+    }
+    if *overwrite_all {
+        return Ok(true);
+    }
+
+    // Send conflict notification and wait for resolution
+    let _ = progress_tx.send(JobUpdate::ConflictDetected {
+        job_id,
+        file_path: dest.to_path_buf(),
+    });
+
+    // Wait for resolution (blocking)
+    match conflict_rx.recv() {
+        Ok(ConflictResolution::Overwrite) => Ok(true),
+        Ok(ConflictResolution::Skip) => {
+            *files_processed += 1;
+            Ok(false)
+        }
+        Ok(ConflictResolution::OverwriteAll) => {
+            *overwrite_all = true;
+            Ok(true)
+        }
+        Ok(ConflictResolution::SkipAll) => {
+            *skip_all = true;
+            *files_processed += 1;
+            Ok(false)
+        }
+        Ok(ConflictResolution::Cancel) | Err(_) => Err(std::io::Error::new(
+            std::io::ErrorKind::Interrupted,
+            "Cancelled",
+        )),
+    }
+}
+
+/// Write everything `reader` yields to `dest`, reporting progress and
+/// honouring pause and cancel. A cancelled file is removed.
+#[allow(clippy::too_many_arguments)]
+fn write_with_progress(
+    reader: &mut dyn Read,
+    dest: &Path,
+    file_name: Option<String>,
+    progress_tx: &Sender<JobUpdate>,
+    job_id: JobId,
+    cancel_flag: &Arc<AtomicBool>,
+    pause_flag: &Arc<AtomicBool>,
+    processed_bytes: &mut u64,
+    files_processed: &mut u64,
+) -> std::io::Result<()> {
+    let dest_file = std::fs::File::create(dest)?;
+    let mut writer = BufWriter::with_capacity(COPY_BUFFER_SIZE, dest_file);
+    let mut buffer = vec![0u8; COPY_BUFFER_SIZE];
+
+    loop {
+        // Check cancel flag
+        if cancel_flag.load(Ordering::Relaxed) {
+            drop(writer);
+            let _ = std::fs::remove_file(dest);
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Interrupted,
+                "Cancelled",
+            ));
+        }
+
+        // Wait while paused
+        while pause_flag.load(Ordering::Relaxed) {
+            if cancel_flag.load(Ordering::Relaxed) {
+                drop(writer);
+                let _ = std::fs::remove_file(dest);
                 return Err(std::io::Error::new(
                     std::io::ErrorKind::Interrupted,
                     "Cancelled",
@@ -818,6 +1187,419 @@ fn copy_file_with_progress(
     Ok(())
 }
 
+// ============================================================================
+// Extract Worker
+// ============================================================================
+
+/// Whether `path` is one of `members` or lies below one of them. No members
+/// means the whole archive.
+fn is_selected_member(path: &str, members: &[String]) -> bool {
+    members.is_empty()
+        || members.iter().any(|m| {
+            path.strip_prefix(m.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        })
+}
+
+/// `path` relative to the archive directory `base`, or None if it is not
+/// below it
+fn strip_base<'a>(path: &'a str, base: &str) -> Option<&'a str> {
+    if base.is_empty() {
+        return Some(path);
+    }
+    path.strip_prefix(base)?.strip_prefix('/')
+}
+
+/// Make room for a new file or link at `target`: an existing file or
+/// symlink is removed rather than written through
+fn remove_existing(target: &Path) -> std::io::Result<()> {
+    match std::fs::symlink_metadata(target) {
+        Ok(metadata) if !metadata.is_dir() => std::fs::remove_file(target),
+        _ => Ok(()),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn extract_worker(
+    job_id: JobId,
+    archive: PathBuf,
+    listing: Option<Arc<ArchiveListing>>,
+    members: Vec<String>,
+    base: String,
+    dest_dir: PathBuf,
+    progress_tx: Sender<JobUpdate>,
+    cancel_flag: Arc<AtomicBool>,
+    pause_flag: Arc<AtomicBool>,
+    conflict_rx: Receiver<ConflictResolution>,
+) {
+    // Phase 1: Totals come from the listing, read now unless the archive is
+    // already open in a pane
+    let listing = match listing {
+        Some(listing) => listing,
+        None => match ArchiveListing::read(&archive) {
+            Ok(listing) => Arc::new(listing),
+            Err(error) => {
+                let _ = progress_tx.send(JobUpdate::Failed { job_id, error });
+                return;
+            }
+        },
+    };
+    let (total_bytes, total_files) = if members.is_empty() {
+        listing.totals("")
+    } else {
+        members
+            .iter()
+            .map(|m| listing.totals(m))
+            .fold((0, 0), |(bytes, files), (b, f)| (bytes + b, files + f))
+    };
+    let _ = progress_tx.send(JobUpdate::ScanComplete {
+        job_id,
+        total_bytes,
+        total_files,
+    });
+
+    // Phase 2: Stream the archive, writing out the selected members
+    let mut processed_bytes = 0u64;
+    let mut files_processed = 0u64;
+    let mut overwrite_all = false;
+    let mut skip_all = false;
+    // Members that would have ended up outside dest_dir
+    let mut unsafe_entries = 0u64;
+    // Symlinks written so far, which later link targets may go through
+    let mut created_links = HashSet::new();
+    // Directory modes are applied last, so read-only directories can still
+    // be filled
+    let mut dir_modes = Vec::new();
+
+    let result = std::fs::create_dir_all(&dest_dir).and_then(|()| {
+        archive::for_each_entry(&archive, |entry, data| {
+            if cancel_flag.load(Ordering::Relaxed) {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::Interrupted,
+                    "Cancelled",
+                ));
+            }
+            if !is_selected_member(&entry.path, &members) {
+                return Ok(true);
+            }
+            let relative = strip_base(&entry.path, &base).unwrap_or(&entry.path);
+            let Some(target) = extract::safe_target(&dest_dir, relative) else {
+                unsafe_entries += 1;
+                return Ok(true);
+            };
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            match &entry.kind {
+                EntryKind::Dir => {
+                    if std::fs::symlink_metadata(&target).is_ok_and(|m| m.is_symlink()) {
+                        unsafe_entries += 1;
+                        return Ok(true);
+                    }
+                    std::fs::create_dir_all(&target)?;
+                    if let Some(mode) = entry.mode {
+                        dir_modes.push((target, mode));
+                    }
+                }
+                EntryKind::File => {
+                    if resolve_conflict(
+                        &target,
+                        &progress_tx,
+                        job_id,
+                        &conflict_rx,
+                        &mut files_processed,
+                        &mut overwrite_all,
+                        &mut skip_all,
+                    )? {
+                        remove_existing(&target)?;
+                        write_with_progress(
+                            data,
+                            &target,
+                            Some(entry.name().to_string()),
+                            &progress_tx,
+                            job_id,
+                            &cancel_flag,
+                            &pause_flag,
+                            &mut processed_bytes,
+                            &mut files_processed,
+                        )?;
+                        if let Some(mode) = entry.mode {
+                            std::fs::set_permissions(
+                                &target,
+                                std::fs::Permissions::from_mode(mode & 0o777),
+                            )?;
+                        }
+                    }
+                }
+                EntryKind::Symlink(link_target) => {
+                    if extract::link_escapes(&dest_dir, relative, link_target, &created_links) {
+                        unsafe_entries += 1;
+                    } else if !link_target.is_empty()
+                        && resolve_conflict(
+                            &target,
+                            &progress_tx,
+                            job_id,
+                            &conflict_rx,
+                            &mut files_processed,
+                            &mut overwrite_all,
+                            &mut skip_all,
+                        )?
+                    {
+                        remove_existing(&target)?;
+                        std::os::unix::fs::symlink(link_target, &target)?;
+                        created_links.insert(target);
+                    }
+                }
+                EntryKind::Hardlink(original) => {
+                    // The original is an earlier member, extracted (or not)
+                    // to its own place below dest_dir
+                    let Some(name) = archive::normalize_name(original) else {
+                        unsafe_entries += 1;
+                        return Ok(true);
+                    };
+                    let original = match strip_base(&name, &base)
+                        .map(|r| extract::safe_target(&dest_dir, r))
+                    {
+                        Some(None) => {
+                            unsafe_entries += 1;
+                            return Ok(true);
+                        }
+                        Some(Some(original)) if original.symlink_metadata().is_ok() => original,
+                        // Not extracted, as when copying part of the archive
+                        _ => return Ok(true),
+                    };
+                    if resolve_conflict(
+                        &target,
+                        &progress_tx,
+                        job_id,
+                        &conflict_rx,
+                        &mut files_processed,
+                        &mut overwrite_all,
+                        &mut skip_all,
+                    )? {
+                        remove_existing(&target)?;
+                        std::fs::hard_link(original, &target)?;
+                    }
+                }
+            }
+            Ok(true)
+        })
+    });
+
+    // Deepest directories first, so a parent's mode can't lock out a child
+    let result = result.and_then(|()| {
+        for (dir, mode) in dir_modes.iter().rev() {
+            std::fs::set_permissions(dir, std::fs::Permissions::from_mode(mode & 0o777))?;
+        }
+        Ok(())
+    });
+
+    let _ = match result {
+        Ok(()) if unsafe_entries > 0 => progress_tx.send(JobUpdate::Failed {
+            job_id,
+            error: format!(
+                "Skipped {} entries that would have been written outside {}",
+                unsafe_entries,
+                dest_dir.display()
+            ),
+        }),
+        Ok(()) => progress_tx.send(JobUpdate::Completed { job_id }),
+        Err(e) => progress_tx.send(JobUpdate::Failed {
+            job_id,
+            error: e.to_string(),
+        }),
+    };
+}
+
+// ============================================================================
+// Compress Worker
+// ============================================================================
+
+/// Reader that reports every chunk read as job progress and honours pause
+/// and cancel. Cancelling is reported as an `Other` error, since
+/// `io::copy` retries reads that fail with `Interrupted`.
+struct ProgressReader<'a, R> {
+    inner: R,
+    file_name: Option<String>,
+    progress_tx: &'a Sender<JobUpdate>,
+    job_id: JobId,
+    cancel_flag: &'a AtomicBool,
+    pause_flag: &'a AtomicBool,
+    processed_bytes: &'a mut u64,
+    files_processed: u64,
+}
+
+impl<R: Read> Read for ProgressReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while self.pause_flag.load(Ordering::Relaxed) && !self.cancel_flag.load(Ordering::Relaxed) {
+            thread::sleep(Duration::from_millis(100));
+        }
+        if self.cancel_flag.load(Ordering::Relaxed) {
+            return Err(std::io::Error::other("Cancelled"));
+        }
+
+        let n = self.inner.read(buf)?;
+        *self.processed_bytes += n as u64;
+        let _ = self.progress_tx.send(JobUpdate::Progress {
+            job_id: self.job_id,
+            processed_bytes: *self.processed_bytes,
+            current_file: self.file_name.clone(),
+            files_processed: self.files_processed,
+        });
+        Ok(n)
+    }
+}
+
+/// Path of `path` inside an archive whose entries are relative to `base`
+fn archive_name(path: &Path, base: &Path) -> String {
+    let relative = path.strip_prefix(base).unwrap_or(path);
+    relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Write `op.sources` and everything below them into `writer`, leaving out
+/// the archive being written (`output`) when it lies inside a source
+#[allow(clippy::too_many_arguments)]
+fn write_archive(
+    writer: &mut ArchiveWriter,
+    op: &CompressOp,
+    output: &Path,
+    progress_tx: &Sender<JobUpdate>,
+    job_id: JobId,
+    cancel_flag: &AtomicBool,
+    pause_flag: &AtomicBool,
+    processed_bytes: &mut u64,
+    files_processed: &mut u64,
+) -> std::io::Result<()> {
+    for source in &op.sources {
+        let base = source.parent().unwrap_or(Path::new("/"));
+        for entry in WalkDir::new(source).sort_by_file_name() {
+            let entry = entry.map_err(std::io::Error::other)?;
+            if cancel_flag.load(Ordering::Relaxed) {
+                return Err(std::io::Error::other("Cancelled"));
+            }
+            // Never pack the archive into itself, should it be inside a source
+            if entry.path() == output || entry.path() == op.archive {
+                continue;
+            }
+            let name = archive_name(entry.path(), base);
+            let metadata = entry.path().symlink_metadata()?;
+            let file_type = metadata.file_type();
+
+            if file_type.is_dir() {
+                writer.add_dir(&name, &metadata)?;
+            } else if file_type.is_symlink() {
+                let target = std::fs::read_link(entry.path())?;
+                writer.add_symlink(&name, &target, &metadata)?;
+            } else if file_type.is_file() {
+                let mut reader = ProgressReader {
+                    inner: BufReader::with_capacity(
+                        COPY_BUFFER_SIZE,
+                        std::fs::File::open(entry.path())?,
+                    ),
+                    file_name: Some(entry.file_name().to_string_lossy().into_owned()),
+                    progress_tx,
+                    job_id,
+                    cancel_flag,
+                    pause_flag,
+                    processed_bytes: &mut *processed_bytes,
+                    files_processed: *files_processed,
+                };
+                writer.add_file(&name, &metadata, &mut reader)?;
+                *files_processed += 1;
+            }
+            // Devices, sockets and fifos are skipped
+        }
+    }
+    Ok(())
+}
+
+fn compress_worker(
+    job_id: JobId,
+    op: CompressOp,
+    progress_tx: Sender<JobUpdate>,
+    cancel_flag: Arc<AtomicBool>,
+    pause_flag: Arc<AtomicBool>,
+    conflict_rx: Receiver<ConflictResolution>,
+) {
+    // Phase 1: Scan to calculate totals
+    let mut total_bytes = 0u64;
+    let mut total_files = 0u64;
+    for source in &op.sources {
+        for entry in WalkDir::new(source).into_iter().filter_map(|e| e.ok()) {
+            if cancel_flag.load(Ordering::Relaxed) {
+                return;
+            }
+            if entry.file_type().is_file() {
+                total_bytes += entry.metadata().map(|m| m.len()).unwrap_or(0);
+                total_files += 1;
+            }
+        }
+    }
+    let _ = progress_tx.send(JobUpdate::ScanComplete {
+        job_id,
+        total_bytes,
+        total_files,
+    });
+
+    let mut processed_bytes = 0u64;
+    let mut files_processed = 0u64;
+    let (mut overwrite_all, mut skip_all) = (false, false);
+    let proceed = resolve_conflict(
+        &op.archive,
+        &progress_tx,
+        job_id,
+        &conflict_rx,
+        &mut files_processed,
+        &mut overwrite_all,
+        &mut skip_all,
+    );
+
+    // Phase 2: Write next to the archive, replacing it only once complete, so
+    // a failed or cancelled job leaves an existing archive alone
+    let partial = op.archive.with_file_name(format!(
+        ".{}.rmc-part",
+        op.archive.file_name().unwrap_or_default().to_string_lossy()
+    ));
+    let result = proceed.and_then(|proceed| {
+        if !proceed {
+            return Ok(());
+        }
+        let mut writer = ArchiveWriter::create(&partial, op.format, op.level)?;
+        write_archive(
+            &mut writer,
+            &op,
+            &partial,
+            &progress_tx,
+            job_id,
+            &cancel_flag,
+            &pause_flag,
+            &mut processed_bytes,
+            &mut files_processed,
+        )?;
+        writer.finish()?;
+        std::fs::rename(&partial, &op.archive)
+    });
+
+    let _ = match result {
+        Ok(()) => progress_tx.send(JobUpdate::Completed { job_id }),
+        Err(e) => {
+            let _ = std::fs::remove_file(&partial);
+            // A cancelled job already has its status set
+            if cancel_flag.load(Ordering::Relaxed) {
+                return;
+            }
+            progress_tx.send(JobUpdate::Failed {
+                job_id,
+                error: e.to_string(),
+            })
+        }
+    };
+}
+
 // ============================================================================
 // Delete Worker
 // ============================================================================
@@ -999,7 +1781,7 @@ fn delete_path_with_progress(
         }
 
         // Delete directories in reverse order (deepest first)
-        dirs_to_delete.sort_by(|a, b| b.components().count().cmp(&a.components().count()));
+        dirs_to_delete.sort_by_key(|d| std::cmp::Reverse(d.components().count()));
         for dir_path in dirs_to_delete {
             if cancel_flag.load(Ordering::Relaxed) {
                 return Err(std::io::Error::new(
@@ -1043,27 +1825,182 @@ fn rename_worker(
     }
 }
 
+fn link_worker(
+    job_id: JobId,
+    op: LinkOp,
+    progress_tx: Sender<JobUpdate>,
+    cancel_flag: Arc<AtomicBool>,
+) {
+    if cancel_flag.load(Ordering::Relaxed) {
+        return;
+    }
+
+    let _ = match op.apply() {
+        Ok(()) => progress_tx.send(JobUpdate::Completed { job_id }),
+        Err(e) => progress_tx.send(JobUpdate::Failed {
+            job_id,
+            error: e.to_string(),
+        }),
+    };
+}
+
+fn batch_rename_worker(
+    job_id: JobId,
+    renames: Vec<(PathBuf, PathBuf)>,
+    progress_tx: Sender<JobUpdate>,
+    cancel_flag: Arc<AtomicBool>,
+) {
+    let _ = progress_tx.send(JobUpdate::ScanComplete {
+        job_id,
+        total_bytes: 0,
+        total_files: renames.len() as u64,
+    });
+
+    let result = rename_batch(&renames, &cancel_flag, |done, target| {
+        let _ = progress_tx.send(JobUpdate::Progress {
+            job_id,
+            processed_bytes: 0,
+            current_file: target.file_name().map(|n| n.to_string_lossy().into_owned()),
+            files_processed: done as u64,
+        });
+    });
+
+    let _ = match result {
+        Ok(()) => progress_tx.send(JobUpdate::Completed { job_id }),
+        // A cancelled job already has its status set
+        Err(e) if e.kind() == std::io::ErrorKind::Interrupted => return,
+        Err(e) => progress_tx.send(JobUpdate::Failed {
+            job_id,
+            error: e.to_string(),
+        }),
+    };
+}
+
+/// Temporary name for the `index`th entry of a rename batch, next to the original
+fn batch_temp_path(original: &Path, index: usize) -> PathBuf {
+    let name = format!(".rmc-rename-{}-{}", std::process::id(), index);
+    original.with_file_name(name)
+}
+
+/// Rename all `(from, to)` pairs. Every source is first moved to a temporary
+/// name, so targets may be other sources' current names (swaps and cycles).
+/// An existing target that is not part of the batch fails the rename. On
+/// failure or cancellation everything done so far is undone.
+pub fn rename_batch(
+    renames: &[(PathBuf, PathBuf)],
+    cancel_flag: &AtomicBool,
+    mut on_renamed: impl FnMut(usize, &Path),
+) -> std::io::Result<()> {
+    let temps: Vec<PathBuf> = renames
+        .iter()
+        .enumerate()
+        .map(|(i, (from, _))| batch_temp_path(from, i))
+        .collect();
+
+    let mut parked = 0;
+    let mut placed = 0;
+    let result = (|| {
+        for ((from, _), temp) in renames.iter().zip(&temps) {
+            std::fs::rename(from, temp)?;
+            parked += 1;
+        }
+        for ((_, to), temp) in renames.iter().zip(&temps) {
+            if cancel_flag.load(Ordering::Relaxed) {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::Interrupted,
+                    "Cancelled",
+                ));
+            }
+            if to.symlink_metadata().is_ok() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::AlreadyExists,
+                    format!(
+                        "'{}' already exists",
+                        to.file_name().unwrap_or_default().to_string_lossy()
+                    ),
+                ));
+            }
+            std::fs::rename(temp, to)?;
+            placed += 1;
+            on_renamed(placed, to);
+        }
+        Ok(())
+    })();
+
+    if result.is_err() {
+        // Best effort: put everything back where it was
+        for ((_, to), temp) in renames.iter().zip(&temps).take(placed) {
+            let _ = std::fs::rename(to, temp);
+        }
+        for ((from, _), temp) in renames.iter().zip(&temps).take(parked) {
+            let _ = std::fs::rename(temp, from);
+        }
+    }
+    result
+}
+
+// ============================================================================
+// Attributes Worker
+// ============================================================================
+
+fn attributes_worker(
+    job_id: JobId,
+    targets: Vec<PathBuf>,
+    change: AttrChange,
+    progress_tx: Sender<JobUpdate>,
+    cancel_flag: Arc<AtomicBool>,
+    pause_flag: Arc<AtomicBool>,
+) {
+    let paths = change.expand_targets(&targets);
+    let _ = progress_tx.send(JobUpdate::ScanComplete {
+        job_id,
+        total_bytes: 0,
+        total_files: paths.len() as u64,
+    });
+
+    // Keep going past failures so one unreadable entry doesn't stop the rest
+    let mut errors = 0;
+    let mut first_error = None;
+    for (i, path) in paths.iter().enumerate() {
+        while pause_flag.load(Ordering::Relaxed) && !cancel_flag.load(Ordering::Relaxed) {
+            thread::sleep(Duration::from_millis(100));
+        }
+        if cancel_flag.load(Ordering::Relaxed) {
+            return;
+        }
+        if let Err(e) = change.apply(path) {
+            errors += 1;
+            first_error.get_or_insert_with(|| format!("{}: {}", path.display(), e));
+        }
+        let _ = progress_tx.send(JobUpdate::Progress {
+            job_id,
+            processed_bytes: 0,
+            current_file: path.file_name().map(|n| n.to_string_lossy().into_owned()),
+            files_processed: i as u64 + 1,
+        });
+    }
+
+    let _ = match first_error {
+        None => progress_tx.send(JobUpdate::Completed { job_id }),
+        Some(error) if errors == 1 => progress_tx.send(JobUpdate::Failed { job_id, error }),
+        Some(error) => progress_tx.send(JobUpdate::Failed {
+            job_id,
+            error: format!("{} errors, first: {}", errors, error),
+        }),
+    };
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    use std::{
-        fs,
-        sync::mpsc,
-        time::{SystemTime, UNIX_EPOCH},
-    };
+    use std::{fs, sync::mpsc};
+
+    use crate::test_util::unique_temp_dir;
 
     #[cfg(unix)]
     use std::os::unix::fs::symlink;
 
-    fn unique_temp_dir(name: &str) -> PathBuf {
-        let nanos = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_nanos();
-        std::env::temp_dir().join(format!("rmc-{name}-{}-{nanos}", std::process::id()))
-    }
-
     #[test]
     #[cfg(unix)]
     fn delete_directory_removes_symlinks_without_touching_targets() {
@@ -1101,4 +2038,156 @@ mod tests {
 
         let _ = fs::remove_dir_all(&keep_dir);
     }
+
+    #[test]
+    fn rename_batch_handles_swaps_and_rolls_back_on_conflict() {
+        let root = unique_temp_dir("rename-batch");
+        fs::create_dir_all(&root).unwrap();
+        for name in ["a", "b", "c", "taken"] {
+            fs::write(root.join(name), name).unwrap();
+        }
+        let pair = |from: &str, to: &str| (root.join(from), root.join(to));
+        let no_cancel = AtomicBool::new(false);
+
+        // Swap a and b, rename c
+        let renames = [pair("a", "b"), pair("b", "a"), pair("c", "d")];
+        rename_batch(&renames, &no_cancel, |_, _| {}).unwrap();
+        assert_eq!(fs::read_to_string(root.join("a")).unwrap(), "b");
+        assert_eq!(fs::read_to_string(root.join("b")).unwrap(), "a");
+        assert_eq!(fs::read_to_string(root.join("d")).unwrap(), "c");
+        assert!(!root.join("c").exists());
+
+        // The second target exists outside the batch: nothing changes
+        let renames = [pair("a", "x"), pair("b", "taken")];
+        assert!(rename_batch(&renames, &no_cancel, |_, _| {}).is_err());
+        assert_eq!(fs::read_to_string(root.join("a")).unwrap(), "b");
+        assert_eq!(fs::read_to_string(root.join("b")).unwrap(), "a");
+        assert!(!root.join("x").exists());
+        assert_eq!(fs::read_dir(&root).unwrap().count(), 4);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn compress_leaves_out_the_archive_being_written() {
+        let root = unique_temp_dir("compress-inside");
+        let src = root.join("src");
+        fs::create_dir_all(&src).unwrap();
+        fs::write(src.join("a.txt"), b"a").unwrap();
+
+        // Written into its own source, as if the form had allowed it
+        let op = CompressOp {
+            sources: vec![src.clone()],
+            archive: src.join("out.tar"),
+            format: crate::compress::CompressFormat::Tar,
+            level: 0,
+        };
+        let output = src.join(".out.tar.rmc-part");
+        let mut writer = ArchiveWriter::create(&output, op.format, op.level).unwrap();
+        let (tx, _rx) = mpsc::channel();
+        let no_flag = AtomicBool::new(false);
+        let (mut bytes, mut files) = (0, 0);
+        write_archive(
+            &mut writer,
+            &op,
+            &output,
+            &tx,
+            JobId(0),
+            &no_flag,
+            &no_flag,
+            &mut bytes,
+            &mut files,
+        )
+        .unwrap();
+        writer.finish().unwrap();
+
+        fs::rename(&output, &op.archive).unwrap();
+        let listing = ArchiveListing::read(&op.archive).unwrap();
+        let names: Vec<&str> = listing.entries().map(|e| e.path.as_str()).collect();
+        assert_eq!(names, ["src", "src/a.txt"]);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn extract_keeps_links_and_modes_inside_the_target() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let root = unique_temp_dir("extract");
+        let outside = unique_temp_dir("extract-outside");
+        let dest = root.join("dest");
+        fs::create_dir_all(&dest).unwrap();
+        fs::create_dir_all(&outside).unwrap();
+        symlink(&outside, dest.join("pre")).unwrap();
+
+        let archive = root.join("pkg.tar");
+        let mut tar = tar::Builder::new(fs::File::create(&archive).unwrap());
+        let mut append = |kind: tar::EntryType, path: &str, mode: u32, data: &[u8], link: &str| {
+            let mut header = tar::Header::new_gnu();
+            header.set_entry_type(kind);
+            header.set_mode(mode);
+            header.set_size(data.len() as u64);
+            if link.is_empty() {
+                tar.append_data(&mut header, path, data).unwrap();
+            } else {
+                tar.append_link(&mut header, path, link).unwrap();
+            }
+        };
+        append(tar::EntryType::Directory, "pkg", 0o750, b"", "");
+        append(
+            tar::EntryType::Regular,
+            "pkg/run.sh",
+            0o700,
+            b"#!/bin/sh",
+            "",
+        );
+        append(tar::EntryType::Symlink, "pkg/link", 0o777, b"", "run.sh");
+        append(tar::EntryType::Link, "pkg/hard", 0o700, b"", "pkg/run.sh");
+        append(tar::EntryType::Symlink, "escape", 0o777, b"", "../..");
+        append(tar::EntryType::Regular, "escape/file", 0o644, b"x", "");
+        append(tar::EntryType::Regular, "pre/pwned", 0o644, b"x", "");
+        // Each link stays inside on its own, but l2 climbs out through l1
+        append(tar::EntryType::Symlink, "a/b/l1", 0o777, b"", "../..");
+        append(tar::EntryType::Symlink, "l2", 0o777, b"", "a/b/l1/..");
+        tar.into_inner().unwrap();
+
+        let (progress_tx, progress_rx) = mpsc::channel();
+        let (_conflict_tx, conflict_rx) = mpsc::channel();
+        extract_worker(
+            JobId(0),
+            archive,
+            None,
+            Vec::new(),
+            String::new(),
+            dest.clone(),
+            progress_tx,
+            Arc::new(AtomicBool::new(false)),
+            Arc::new(AtomicBool::new(false)),
+            conflict_rx,
+        );
+
+        let error = progress_rx.try_iter().find_map(|update| match update {
+            JobUpdate::Failed { error, .. } => Some(error),
+            _ => None,
+        });
+        assert!(error.unwrap().starts_with("Skipped 3 entries"));
+        assert_eq!(fs::read_dir(&outside).unwrap().count(), 0);
+        assert_eq!(
+            fs::read_link(dest.join("pkg/link")).unwrap(),
+            Path::new("run.sh")
+        );
+        assert!(!dest.join("escape").is_symlink());
+        assert!(dest.join("escape/file").is_file());
+        assert!(dest.join("a/b/l1").is_symlink());
+        assert!(dest.join("l2").symlink_metadata().is_err());
+
+        let mode = |path: &str| fs::metadata(dest.join(path)).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode("pkg"), 0o750);
+        assert_eq!(mode("pkg/run.sh"), 0o700);
+        assert_eq!(fs::read(dest.join("pkg/hard")).unwrap(), b"#!/bin/sh");
+
+        let _ = fs::remove_dir_all(&root);
+        let _ = fs::remove_dir_all(&outside);
+    }
 }
@@ -0,0 +1,340 @@
+//! Symbolic and hard link creation, and editing symlink targets
+//!
+//! Links are created in the other pane by default, like Midnight Commander's
+//! Ctrl+X s / Ctrl+X l. A relative target in the dialog is relative to the
+//! link's directory, which is how the kernel resolves symlinks; the dialog can
+//! switch a target between absolute and relative form. The filesystem work
+//! itself runs as a background job.
+
+use std::{
+    io,
+    path::{Component, Path, PathBuf},
+};
+
+pub const FOCUS_TARGET: usize = 0;
+pub const FOCUS_LINK: usize = 1;
+pub const FOCUS_RELATIVE: usize = 2;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum LinkKind {
+    Symlink,
+    Hardlink,
+    EditSymlink,
+}
+
+impl LinkKind {
+    pub fn title(self) -> &'static str {
+        match self {
+            Self::Symlink => "Symbolic Link",
+            Self::Hardlink => "Hard Link",
+            Self::EditSymlink => "Edit Symbolic Link",
+        }
+    }
+
+    pub fn target_label(self) -> &'static str {
+        match self {
+            Self::Hardlink => "Existing file:",
+            Self::Symlink | Self::EditSymlink => "Points to:",
+        }
+    }
+
+    fn field_count(self) -> usize {
+        match self {
+            Self::Symlink => 3,
+            Self::Hardlink => 2,
+            Self::EditSymlink => 1,
+        }
+    }
+}
+
+/// Remove `.` and `..` components without touching the filesystem
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !out.pop() {
+                    out.push("..");
+                }
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// Path that leads from directory `base` to `target`. Both should be
+/// absolute; the result is computed lexically.
+pub fn relative_path(target: &Path, base: &Path) -> PathBuf {
+    let target = normalize(target);
+    let base = normalize(base);
+    let common = target
+        .components()
+        .zip(base.components())
+        .take_while(|(t, b)| t == b)
+        .count();
+
+    let mut rel = PathBuf::new();
+    for _ in base.components().skip(common) {
+        rel.push("..");
+    }
+    for component in target.components().skip(common) {
+        rel.push(component);
+    }
+    if rel.as_os_str().is_empty() {
+        rel.push(".");
+    }
+    rel
+}
+
+#[derive(Clone)]
+pub struct LinkForm {
+    pub kind: LinkKind,
+    /// What the link points to (for hard links, the existing file)
+    pub target: String,
+    /// Path of the link; relative paths are taken from `base_dir`
+    pub link: String,
+    pub relative: bool,
+    pub focus: usize,
+    base_dir: PathBuf,
+}
+
+impl LinkForm {
+    /// Link `source` into `link_dir` under the same name
+    pub fn new(kind: LinkKind, source: &Path, link_dir: &Path) -> Self {
+        let name = source.file_name().unwrap_or_default();
+        Self {
+            kind,
+            target: source.to_string_lossy().into_owned(),
+            link: link_dir.join(name).to_string_lossy().into_owned(),
+            relative: false,
+            focus: FOCUS_TARGET,
+            base_dir: link_dir.to_path_buf(),
+        }
+    }
+
+    /// Edit the target of the existing symlink `link`
+    pub fn edit_symlink(link: &Path) -> Result<Self, String> {
+        let is_symlink = std::fs::symlink_metadata(link).is_ok_and(|m| m.file_type().is_symlink());
+        if !is_symlink {
+            return Err(format!("{} is not a symbolic link", link.display()));
+        }
+        let target = std::fs::read_link(link)
+            .map_err(|e| format!("Cannot read link {}: {}", link.display(), e))?;
+        Ok(Self {
+            kind: LinkKind::EditSymlink,
+            relative: target.is_relative(),
+            target: target.to_string_lossy().into_owned(),
+            link: link.to_string_lossy().into_owned(),
+            focus: FOCUS_TARGET,
+            base_dir: link.parent().unwrap_or(Path::new("/")).to_path_buf(),
+        })
+    }
+
+    pub fn focused_text_mut(&mut self) -> Option<&mut String> {
+        match self.focus {
+            FOCUS_TARGET => Some(&mut self.target),
+            FOCUS_LINK => Some(&mut self.link),
+            _ => None,
+        }
+    }
+
+    pub fn focus_next(&mut self) {
+        self.focus = (self.focus + 1) % self.kind.field_count();
+    }
+
+    pub fn focus_prev(&mut self) {
+        let count = self.kind.field_count();
+        self.focus = (self.focus + count - 1) % count;
+    }
+
+    fn link_path(&self) -> PathBuf {
+        normalize(&self.base_dir.join(&self.link))
+    }
+
+    /// Directory the link will live in, which relative targets start from
+    fn link_dir(&self) -> PathBuf {
+        self.link_path()
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| self.base_dir.clone())
+    }
+
+    /// Switch the target between absolute and relative form
+    pub fn toggle_relative(&mut self) {
+        if self.kind == LinkKind::Hardlink || self.target.is_empty() {
+            return;
+        }
+        self.relative = !self.relative;
+        let link_dir = self.link_dir();
+        let absolute = normalize(&link_dir.join(&self.target));
+        let target = if self.relative {
+            relative_path(&absolute, &link_dir)
+        } else {
+            absolute
+        };
+        self.target = target.to_string_lossy().into_owned();
+    }
+
+    pub fn compile(&self) -> Result<LinkOp, String> {
+        if self.target.is_empty() {
+            return Err("Link target is empty".to_string());
+        }
+        if self.link.is_empty() {
+            return Err("Link name is empty".to_string());
+        }
+        let link = self.link_path();
+        let target = PathBuf::from(&self.target);
+        Ok(match self.kind {
+            LinkKind::Symlink => LinkOp::Symlink { target, link },
+            LinkKind::Hardlink => {
+                let original = normalize(&self.link_dir().join(target));
+                if original.is_dir() {
+                    return Err("Cannot hard link a directory".to_string());
+                }
+                LinkOp::Hardlink { original, link }
+            }
+            LinkKind::EditSymlink => LinkOp::Retarget { link, target },
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum LinkOp {
+    Symlink {
+        target: PathBuf,
+        link: PathBuf,
+    },
+    Hardlink {
+        original: PathBuf,
+        link: PathBuf,
+    },
+    /// Point an existing symlink somewhere else
+    Retarget {
+        link: PathBuf,
+        target: PathBuf,
+    },
+}
+
+impl LinkOp {
+    pub fn link(&self) -> &Path {
+        match self {
+            Self::Symlink { link, .. }
+            | Self::Hardlink { link, .. }
+            | Self::Retarget { link, .. } => link,
+        }
+    }
+
+    pub fn description(&self) -> String {
+        let name = self
+            .link()
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy();
+        match self {
+            Self::Symlink { target, .. } => {
+                format!("Linking '{}' -> {}", name, target.display())
+            }
+            Self::Hardlink { original, .. } => format!(
+                "Hard linking '{}' to '{}'",
+                name,
+                original.file_name().unwrap_or_default().to_string_lossy()
+            ),
+            Self::Retarget { target, .. } => {
+                format!("Relinking '{}' -> {}", name, target.display())
+            }
+        }
+    }
+
+    pub fn apply(&self) -> io::Result<()> {
+        match self {
+            Self::Symlink { target, link } => std::os::unix::fs::symlink(target, link),
+            Self::Hardlink { original, link } => std::fs::hard_link(original, link),
+            Self::Retarget { link, target } => {
+                if !std::fs::symlink_metadata(link)?.file_type().is_symlink() {
+                    return Err(io::Error::other("not a symbolic link"));
+                }
+                // Replace the link in one step so it never goes missing
+                let temp = link.with_file_name(format!(".rmc-link-{}", std::process::id()));
+                std::os::unix::fs::symlink(target, &temp)?;
+                std::fs::rename(&temp, link).inspect_err(|_| {
+                    let _ = std::fs::remove_file(&temp);
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+
+    use crate::test_util::unique_temp_dir;
+
+    #[test]
+    fn relative_paths_climb_to_the_common_ancestor() {
+        let rel = |t: &str, b: &str| relative_path(Path::new(t), Path::new(b));
+        assert_eq!(rel("/a/b/file", "/a/c"), PathBuf::from("../b/file"));
+        assert_eq!(rel("/a/b/file", "/a/b"), PathBuf::from("file"));
+        assert_eq!(rel("/a/file", "/a/b/c"), PathBuf::from("../../file"));
+        assert_eq!(rel("/a/./b/../file", "/x"), PathBuf::from("../a/file"));
+        assert_eq!(rel("/a", "/a"), PathBuf::from("."));
+    }
+
+    #[test]
+    fn form_toggles_target_between_absolute_and_relative() {
+        let mut form = LinkForm::new(
+            LinkKind::Symlink,
+            Path::new("/data/src/file.txt"),
+            Path::new("/data/links"),
+        );
+        assert_eq!(form.link, "/data/links/file.txt");
+        form.toggle_relative();
+        assert_eq!(form.target, "../src/file.txt");
+        form.toggle_relative();
+        assert_eq!(form.target, "/data/src/file.txt");
+
+        form.link = "sub/renamed".to_string();
+        assert_eq!(
+            form.compile(),
+            Ok(LinkOp::Symlink {
+                target: PathBuf::from("/data/src/file.txt"),
+                link: PathBuf::from("/data/links/sub/renamed"),
+            })
+        );
+    }
+
+    #[test]
+    fn creates_links_and_retargets_symlinks() {
+        let root = unique_temp_dir("links");
+        fs::create_dir_all(root.join("links")).unwrap();
+        fs::write(root.join("a.txt"), "a").unwrap();
+        fs::write(root.join("b.txt"), "b").unwrap();
+
+        let mut form = LinkForm::new(LinkKind::Symlink, &root.join("a.txt"), &root.join("links"));
+        form.toggle_relative();
+        form.compile().unwrap().apply().unwrap();
+        let link = root.join("links/a.txt");
+        assert_eq!(fs::read_link(&link).unwrap(), PathBuf::from("../a.txt"));
+        assert_eq!(fs::read_to_string(&link).unwrap(), "a");
+        // The link now exists, so creating it again fails
+        assert!(form.compile().unwrap().apply().is_err());
+
+        let mut edit = LinkForm::edit_symlink(&link).unwrap();
+        assert!(edit.relative);
+        edit.target = "../b.txt".to_string();
+        edit.compile().unwrap().apply().unwrap();
+        assert_eq!(fs::read_to_string(&link).unwrap(), "b");
+
+        let hard = LinkForm::new(LinkKind::Hardlink, &root.join("b.txt"), &root.join("links"));
+        let op = hard.compile().unwrap();
+        assert_eq!(op.link(), root.join("links/b.txt"));
+        op.apply().unwrap();
+        assert_eq!(fs::read_to_string(root.join("links/b.txt")).unwrap(), "b");
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
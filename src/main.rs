@@ -1,29 +1,73 @@
+mod archive;
+mod attributes;
+mod bookmarks;
+mod bulk_rename;
+mod compare;
+mod compress;
 mod dialog;
+mod diff;
+mod dir_size;
+mod disk_usage;
+mod extract;
+mod find;
+mod finder;
+mod frecency;
+mod grep;
+mod history;
 mod input;
 mod job;
+mod links;
+mod mounts;
 mod pane;
+mod properties;
+mod quick_view;
 mod render;
 mod state;
+mod tabs;
+#[cfg(test)]
+mod test_util;
 mod theme;
+mod tree;
 mod util;
 mod viewer;
+mod watcher;
 
 use std::{
     path::PathBuf,
+    sync::mpsc::Receiver,
     time::{Duration, Instant},
 };
 
+use attributes::AttrForm;
+use bookmarks::Bookmarks;
+use bulk_rename::BulkRename;
+use compare::{CompareMethod, CompareRun};
+use compress::CompressForm;
 use crossterm::{
     ExecutableCommand,
     event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyEventKind},
 };
 use ratatui::{DefaultTerminal, layout::Rect};
 
+use diff::DiffViewer;
+use disk_usage::DiskUsage;
+use extract::ExtractForm;
+use find::{FindForm, FindSearch};
+use finder::FuzzyFinder;
+use frecency::FrecencyDb;
+use grep::{GrepForm, GrepSearch};
+use history::DirHistory;
 use job::{JobId, JobManager, JobType};
+use links::LinkForm;
+use mounts::{DiskSpace, Mount};
 use pane::{Entry, Pane, PaneState};
-use state::AppState;
+use properties::PropertiesView;
+use quick_view::QuickView;
+use state::{AppState, PaneTabsState};
+use tabs::TabSet;
 use util::{ERROR_DISPLAY_SECS, EVENT_POLL_MS};
 use viewer::FileViewer;
+use watcher::DirWatcher;
 
 fn main() -> color_eyre::Result<()> {
     color_eyre::install()?;
@@ -36,12 +80,31 @@ fn main() -> color_eyre::Result<()> {
 // UI Mode
 // ============================================================================
 
+/// Panel shown in place of the inactive pane
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum SidePanel {
+    #[default]
+    Off,
+    /// Properties of the active cursor entry
+    Info,
+    /// Preview of the active cursor file
+    QuickView,
+}
+
+impl SidePanel {
+    /// Switch to `panel`, or back to the listing if it is already shown
+    pub fn toggle(self, panel: SidePanel) -> Self {
+        if self == panel { Self::Off } else { panel }
+    }
+}
+
 /// UI mode determines what the user is currently interacting with.
 ///
 /// Most variants contain small Copy types or Strings which are cheap to clone.
 /// FileViewer is boxed because it can contain up to 50MB of file data.
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub enum UIMode {
+    #[default]
     Normal,
     JobList {
         selected: usize,
@@ -76,17 +139,111 @@ pub enum UIMode {
     Search {
         query: String,
     },
+    /// Select (`+`) or unselect (`-`) entries matching a glob pattern
+    SelectGroup {
+        input: String,
+        select: bool,
+        files_only: bool,
+        case_sensitive: bool,
+    },
+    /// Directory hotlist popup. `rename` holds the input while renaming the
+    /// selected bookmark.
+    Hotlist {
+        selected: usize,
+        rename: Option<String>,
+    },
+    /// Recently visited directories of the active pane
+    DirHistory {
+        selected: usize,
+    },
+    /// Mounted filesystems with their space; Enter takes the active pane to
+    /// the selected mount point. `show_all` includes pseudo filesystems.
+    Mounts {
+        mounts: Vec<(Mount, Option<DiskSpace>)>,
+        selected: usize,
+        show_all: bool,
+    },
+    /// Frecency-ranked directory jump popup. `candidates` is ranked once when
+    /// the popup opens and filtered by `query` as the user types.
+    FrecencyJump {
+        query: String,
+        candidates: Vec<PathBuf>,
+        selected: usize,
+    },
+    /// Find File dialog
+    FindFile {
+        form: FindForm,
+    },
+    /// Results of the current Find File search (kept in `App::find`)
+    FindResults {
+        selected: usize,
+    },
+    /// Content search (grep) dialog
+    GrepForm {
+        form: GrepForm,
+    },
+    /// Matches of the current content search (kept in `App::grep`)
+    GrepResults {
+        selected: usize,
+    },
+    /// Directory comparison options
+    CompareDirs {
+        method: CompareMethod,
+        recursive: bool,
+    },
+    /// Differences found by the current comparison (kept in `App::compare`)
+    CompareResults {
+        selected: usize,
+    },
+    /// Ctrl+P fuzzy finder popup (state kept in `App::finder`)
+    FuzzyFinder,
+    /// Waiting for the letter after `m` (set mark) or `'` (jump to mark)
+    MarkPending {
+        set: bool,
+    },
     /// File viewer - boxed because it contains potentially large file data.
     /// Handlers use mem::take to avoid cloning this variant.
+    /// `return_to` is the mode to restore when the viewer closes (Normal if None).
     FileViewer {
         viewer: Box<FileViewer>,
+        return_to: Option<Box<UIMode>>,
+    },
+    /// Permissions and ownership dialog
+    Attributes {
+        form: AttrForm,
+    },
+    /// Disk usage analyzer for the scan in `App::disk_usage`
+    DiskUsage,
+    /// Properties popup for the entry in `App::properties`
+    Properties,
+    /// Ctrl+X was pressed; the next key picks a link command
+    CtrlXPrefix,
+    /// Create a symlink or hard link, or edit a symlink's target
+    LinkInput {
+        form: LinkForm,
+    },
+    /// Pack the selection into a new archive
+    Compress {
+        form: CompressForm,
+    },
+    /// Extract the cursor archive
+    Extract {
+        form: ExtractForm,
+    },
+    /// Confirm renames edited in $EDITOR. `cycles` counts swaps and longer
+    /// cycles among them.
+    ConfirmBatchRename {
+        renames: Vec<(PathBuf, PathBuf)>,
+        cycles: usize,
+    },
+    /// Bulk rename dialog with its preview - boxed, handlers use mem::take
+    BulkRename {
+        rename: Box<BulkRename>,
+    },
+    /// Diff of two files - boxed like FileViewer, handlers use mem::take
+    DiffViewer {
+        viewer: Box<DiffViewer>,
     },
-}
-
-impl Default for UIMode {
-    fn default() -> Self {
-        UIMode::Normal
-    }
 }
 
 // ============================================================================
@@ -94,8 +251,12 @@ impl Default for UIMode {
 // ============================================================================
 
 pub struct App {
+    /// Live (active tab) state of each pane
     pub left: PaneState,
     pub right: PaneState,
+    /// Background tabs of each pane
+    pub left_tabs: TabSet,
+    pub right_tabs: TabSet,
     pub active_pane: Pane,
     pub should_quit: bool,
     pub job_manager: JobManager,
@@ -103,7 +264,39 @@ pub struct App {
     pub error_message: Option<(String, Instant)>,
     pub left_area: Rect,
     pub right_area: Rect,
-    pub previous_path: Option<PathBuf>, // For cd -
+    pub bookmarks: Bookmarks,
+    /// Visit counts for `:z` and the jump popup
+    pub frecency: FrecencyDb,
+    /// Most recent Find File search, running or finished
+    pub find: Option<FindSearch>,
+    /// Most recent content search, running or finished
+    pub grep: Option<GrepSearch>,
+    /// Most recent directory comparison, running or finished
+    pub compare: Option<CompareRun>,
+    /// Open fuzzy finder, if any
+    pub finder: Option<FuzzyFinder>,
+    /// `(from, to)` pairs of the last batch rename, for Ctrl+Z
+    pub last_batch_rename: Option<Vec<(PathBuf, PathBuf)>>,
+    /// Batch rename job still running; becomes `last_batch_rename` once it
+    /// has renamed everything
+    pub pending_batch_rename: Option<(JobId, Vec<(PathBuf, PathBuf)>)>,
+    /// Open disk usage analyzer, scanning or finished
+    pub disk_usage: Option<DiskUsage>,
+    /// Delete jobs started from the disk usage analyzer, with the entry each
+    /// one removes from the tree once it completes
+    pub disk_usage_deletes: Vec<(JobId, PathBuf)>,
+    /// Space figures for the open mount list, read in the background
+    pub mount_spaces: Option<Receiver<(PathBuf, DiskSpace)>>,
+    /// Properties shown by the popup
+    pub properties: Option<PropertiesView>,
+    /// What the inactive pane shows instead of its listing, if anything
+    pub side_panel: SidePanel,
+    /// Properties of the active cursor entry for the info panel
+    pub info: Option<PropertiesView>,
+    /// Preview of the active cursor file for the quick view panel
+    pub quick_view: Option<QuickView>,
+    /// inotify watcher for auto-refreshing panes (None if inotify is unavailable)
+    pub watcher: Option<DirWatcher>,
 }
 
 impl App {
@@ -111,16 +304,29 @@ impl App {
         let cwd = std::env::current_dir()?;
         let state = AppState::load();
 
-        let right_path = state.right_path.unwrap_or_else(|| cwd.clone());
+        // Left pane's active tab always starts in current directory
+        let mut left = PaneState::new(cwd.clone())?;
+        left.history = DirHistory::from_entries(state.left_history);
+        left.history.visit(&left.path);
+        let left_tabs = restore_background_tabs(&state.left_tabs);
 
-        // Left pane always starts in current directory
-        let left = PaneState::new(cwd.clone())?;
         // Right pane uses saved path, falls back to cwd if it fails
-        let right = PaneState::new(right_path).or_else(|_| PaneState::new(cwd))?;
+        let right_path = state
+            .right_tabs
+            .paths
+            .get(state.right_tabs.active)
+            .cloned()
+            .unwrap_or_else(|| cwd.clone());
+        let mut right = PaneState::new(right_path).or_else(|_| PaneState::new(cwd))?;
+        right.history = DirHistory::from_entries(state.right_history);
+        right.history.visit(&right.path);
+        let right_tabs = restore_background_tabs(&state.right_tabs);
 
         Ok(Self {
             left,
             right,
+            left_tabs,
+            right_tabs,
             active_pane: Pane::Left,
             should_quit: false,
             job_manager: JobManager::new(),
@@ -128,7 +334,22 @@ impl App {
             error_message: None,
             left_area: Rect::default(),
             right_area: Rect::default(),
-            previous_path: None,
+            bookmarks: Bookmarks::load(),
+            frecency: FrecencyDb::load(),
+            find: None,
+            grep: None,
+            compare: None,
+            last_batch_rename: None,
+            pending_batch_rename: None,
+            finder: None,
+            disk_usage: None,
+            disk_usage_deletes: Vec::new(),
+            mount_spaces: None,
+            properties: None,
+            side_panel: SidePanel::Off,
+            info: None,
+            quick_view: None,
+            watcher: DirWatcher::new().ok(),
         })
     }
 
@@ -139,17 +360,44 @@ impl App {
         while !self.should_quit {
             terminal.draw(|frame| self.render(frame))?;
 
-            // When in FileViewer mode, skip ALL background operations to ensure
+            // When in a viewer, skip ALL background operations to ensure
             // the UI remains responsive even when network filesystems are slow.
-            if matches!(self.ui_mode, UIMode::FileViewer { .. }) {
+            if matches!(
+                self.ui_mode,
+                UIMode::FileViewer { .. } | UIMode::DiffViewer { .. }
+            ) {
                 if event::poll(Duration::from_millis(EVENT_POLL_MS))? {
                     self.handle_events(terminal)?;
                 }
                 continue;
             }
 
+            // Stream in Find File results
+            if let Some(find) = &mut self.find {
+                find.poll();
+            }
+            if let Some(grep) = &mut self.grep {
+                grep.poll();
+            }
+            if let Some(finder) = &mut self.finder {
+                finder.poll();
+            }
+            if self.compare.as_mut().is_some_and(|c| c.poll()) {
+                self.mark_compare_differences();
+            }
+            if let Some(disk_usage) = &mut self.disk_usage {
+                disk_usage.poll();
+            }
+            if let Some(properties) = &mut self.properties {
+                properties.poll();
+            }
+            self.poll_mount_spaces();
+            self.update_side_panel();
+
             // Process job updates (always process to keep job state current)
             let (completed_dests, completed_sources) = self.job_manager.process_updates();
+            self.poll_batch_rename();
+            self.poll_disk_usage_deletes();
 
             // Only do pane-related operations when in Normal mode.
             // This prevents blocking on slow/NFS filesystems when in other modal views.
@@ -158,6 +406,13 @@ impl App {
                 self.refresh_panes_for_paths(completed_dests);
                 self.refresh_panes_for_paths(completed_sources);
 
+                // Refresh panes whose directories changed on disk
+                if let Some(watcher) = &mut self.watcher {
+                    watcher.sync(&[&self.left.path, &self.right.path]);
+                    let changed = watcher.poll_changes();
+                    self.refresh_panes_for_paths(changed);
+                }
+
                 // Poll for async directory loading results
                 if let Some(Err(e)) = self.left.poll_load_result() {
                     self.error_message = Some((e, Instant::now()));
@@ -169,6 +424,10 @@ impl App {
                 // Poll for size calculation results
                 self.left.poll_size_results();
                 self.right.poll_size_results();
+                self.left.poll_free_space();
+                self.right.poll_free_space();
+
+                self.poll_trees();
             }
 
             self.job_manager.update_visibility();
@@ -177,10 +436,10 @@ impl App {
             self.check_rename_progress();
 
             // Clear old error messages
-            if let Some((_, timestamp)) = &self.error_message {
-                if timestamp.elapsed() > Duration::from_secs(ERROR_DISPLAY_SECS) {
-                    self.error_message = None;
-                }
+            if let Some((_, timestamp)) = &self.error_message
+                && timestamp.elapsed() > Duration::from_secs(ERROR_DISPLAY_SECS)
+            {
+                self.error_message = None;
             }
 
             // Poll for input with timeout
@@ -198,8 +457,16 @@ impl App {
         // Disable mouse capture
         std::io::stdout().execute(DisableMouseCapture)?;
 
-        // Save state before exiting (only right pane path)
-        AppState::save(&self.right.path);
+        // Save open tabs before exiting
+        AppState {
+            left_tabs: self.left_tabs.to_state(&self.left),
+            right_tabs: self.right_tabs.to_state(&self.right),
+            left_history: self.left.history.entries().to_vec(),
+            right_history: self.right.history.entries().to_vec(),
+        }
+        .save();
+        self.frecency.save();
+        archive::remove_view_dir();
 
         Ok(())
     }
@@ -272,6 +539,14 @@ impl App {
         }
     }
 
+    /// Get the active pane's live state together with its tabs
+    pub fn active_tabs_mut(&mut self) -> (&mut TabSet, &mut PaneState) {
+        match self.active_pane {
+            Pane::Left => (&mut self.left_tabs, &mut self.left),
+            Pane::Right => (&mut self.right_tabs, &mut self.right),
+        }
+    }
+
     pub fn toggle_pane(&mut self) {
         self.active_pane = match self.active_pane {
             Pane::Left => Pane::Right,
@@ -286,6 +561,10 @@ impl App {
         std::mem::swap(&mut self.left.list_state, &mut self.right.list_state);
         std::mem::swap(&mut self.left.show_hidden, &mut self.right.show_hidden);
         std::mem::swap(&mut self.left.size_mode, &mut self.right.size_mode);
+        std::mem::swap(&mut self.left.history, &mut self.right.history);
+        std::mem::swap(&mut self.left.panel, &mut self.right.panel);
+        std::mem::swap(&mut self.left.archive, &mut self.right.archive);
+        std::mem::swap(&mut self.left_tabs, &mut self.right_tabs);
     }
 
     /// Refresh any pane whose path matches one of the given paths
@@ -294,12 +573,12 @@ impl App {
         let mut right_done = false;
 
         for path in paths {
-            if !left_done && self.left.path == path && !self.left.is_loading_any() {
-                self.left.load_entries_async();
+            if !left_done && self.left.lists_dir(&path) {
+                self.left.request_reload();
                 left_done = true;
             }
-            if !right_done && self.right.path == path && !self.right.is_loading_any() {
-                self.right.load_entries_async();
+            if !right_done && self.right.lists_dir(&path) {
+                self.right.request_reload();
                 right_done = true;
             }
             if left_done && right_done {
@@ -317,6 +596,10 @@ impl App {
             Pane::Left => (&self.left, &self.right),
             Pane::Right => (&self.right, &self.left),
         };
+        if dest_pane.archive.is_some() {
+            self.error_message = Some(("Cannot copy into an archive".to_owned(), Instant::now()));
+            return;
+        }
 
         let entries_to_transfer: Vec<PathBuf> = source_pane
             .selected_entries()
@@ -340,4 +623,113 @@ impl App {
                 .start_job(job_type, source, dest_dir.clone());
         }
     }
+
+    /// Show the directory under the active tree's cursor in the other pane
+    pub fn follow_tree(&mut self) {
+        let Some(path) = self
+            .active_pane()
+            .tree
+            .as_ref()
+            .map(|t| t.selected_path().to_path_buf())
+        else {
+            return;
+        };
+        let other = self.other_pane_mut();
+        if other.path == path && other.panel.is_none() && other.archive.is_none() {
+            return;
+        }
+        other.path = path.clone();
+        other.panel = None;
+        other.archive = None;
+        other.entries.clear();
+        other.selected.clear();
+        other.list_state.select(Some(0));
+        other.load_entries_async();
+        other.history.visit(&path);
+    }
+
+    /// Apply directories the trees read in the background
+    fn poll_trees(&mut self) {
+        if self.left.tree.as_mut().is_some_and(|t| t.poll()) && self.active_pane == Pane::Left {
+            self.follow_tree();
+        }
+        if self.right.tree.as_mut().is_some_and(|t| t.poll()) && self.active_pane == Pane::Right {
+            self.follow_tree();
+        }
+    }
+
+    /// Keep the info or quick view panel on the active cursor entry,
+    /// loading it again whenever the cursor moves to another entry
+    fn update_side_panel(&mut self) {
+        if self.side_panel != SidePanel::Info {
+            self.info = None;
+        }
+        if self.side_panel != SidePanel::QuickView {
+            self.quick_view = None;
+        }
+        if self.side_panel == SidePanel::Off {
+            return;
+        }
+        let path = self
+            .active_pane()
+            .selected_entry()
+            .filter(|e| e.name != "..")
+            .map(|e| e.path.clone())
+            .unwrap_or_else(|| self.active_pane().path.clone());
+        match self.side_panel {
+            SidePanel::Info => match &mut self.info {
+                Some(info) if info.path == path => info.poll(),
+                _ => self.info = Some(PropertiesView::start(path)),
+            },
+            SidePanel::QuickView => match &mut self.quick_view {
+                Some(view) if view.path == path => view.poll(),
+                _ => self.quick_view = Some(QuickView::start(path)),
+            },
+            SidePanel::Off => {}
+        }
+    }
+
+    /// Select the top-level entries the finished comparison found missing or
+    /// newer on each side, so F5 copies exactly the differences
+    fn mark_compare_differences(&mut self) {
+        let Some(run) = &self.compare else {
+            return;
+        };
+        let Some(differences) = &run.differences else {
+            return;
+        };
+
+        for (pane, root, is_left) in [
+            (&mut self.left, &run.left, true),
+            (&mut self.right, &run.right, false),
+        ] {
+            // The pane may have moved on while the comparison was running
+            if pane.path != *root || pane.panel.is_some() {
+                continue;
+            }
+            pane.selected = differences
+                .iter()
+                .filter(|d| d.rel_path.components().count() == 1)
+                .filter(|d| {
+                    if is_left {
+                        d.kind.marks_left()
+                    } else {
+                        d.kind.marks_right()
+                    }
+                })
+                .map(|d| root.join(&d.rel_path))
+                .collect();
+        }
+    }
+}
+
+/// Set up the saved background tabs of a pane (every tab except the active
+/// one). They are listed when first switched to, not at startup.
+fn restore_background_tabs(saved: &PaneTabsState) -> TabSet {
+    let panes = |paths: &[PathBuf]| -> Vec<PaneState> {
+        paths.iter().cloned().map(PaneState::unloaded).collect()
+    };
+    let active = saved.active.min(saved.paths.len());
+    let after = saved.paths.get(active + 1..).unwrap_or_default();
+    TabSet::with_background(panes(&saved.paths[..active]), panes(after))
 }
@@ -0,0 +1,257 @@
+//! Mounted filesystems and their free space
+//!
+//! Mounts come from /proc/self/mountinfo (falling back to /proc/self/mounts);
+//! space figures from statvfs(3).
+
+use std::{
+    ffi::CString,
+    os::unix::ffi::OsStrExt,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver},
+    thread,
+};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mount {
+    pub device: String,
+    pub mount_point: PathBuf,
+    pub fs_type: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DiskSpace {
+    pub total: u64,
+    /// Free space, including blocks reserved for root
+    pub free: u64,
+    /// Space available to unprivileged users
+    pub available: u64,
+}
+
+impl DiskSpace {
+    pub fn used(&self) -> u64 {
+        self.total.saturating_sub(self.free)
+    }
+}
+
+/// Kernel and virtual filesystems hidden from the mount list by default
+const PSEUDO_FS_TYPES: &[&str] = &[
+    "autofs",
+    "binfmt_misc",
+    "bpf",
+    "cgroup",
+    "cgroup2",
+    "configfs",
+    "debugfs",
+    "devpts",
+    "devtmpfs",
+    "efivarfs",
+    "fusectl",
+    "hugetlbfs",
+    "mqueue",
+    "nsfs",
+    "proc",
+    "pstore",
+    "ramfs",
+    "rpc_pipefs",
+    "securityfs",
+    "selinuxfs",
+    "sysfs",
+    "tracefs",
+];
+
+impl Mount {
+    /// Kernel interfaces and the like, which hold no user files
+    pub fn is_pseudo(&self) -> bool {
+        PSEUDO_FS_TYPES.contains(&self.fs_type.as_str())
+            || self.mount_point.starts_with("/proc")
+            || self.mount_point.starts_with("/sys")
+    }
+}
+
+/// Undo the octal escapes (`\040` for a space) used in /proc/self/mounts
+fn unescape(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let code = (bytes[i] == b'\\')
+            .then(|| bytes.get(i + 1..i + 4))
+            .flatten()
+            .and_then(|digits| u8::from_str_radix(std::str::from_utf8(digits).ok()?, 8).ok());
+        match code {
+            Some(b) => {
+                out.push(b);
+                i += 4;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn parse_mounts(content: &str) -> Vec<Mount> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            Some(Mount {
+                device: unescape(fields.next()?),
+                mount_point: PathBuf::from(unescape(fields.next()?)),
+                fs_type: fields.next()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Parse /proc/self/mountinfo lines: `id parent major:minor root mount-point
+/// options [optional fields...] - fs-type source super-options`
+fn parse_mountinfo(content: &str) -> Vec<Mount> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let (before, after) = line.split_once(" - ")?;
+            let mount_point = before.split_whitespace().nth(4)?;
+            let mut fields = after.split_whitespace();
+            let fs_type = fields.next()?.to_string();
+            Some(Mount {
+                device: unescape(fields.next()?),
+                mount_point: PathBuf::from(unescape(mount_point)),
+                fs_type,
+            })
+        })
+        .collect()
+}
+
+/// All mounted filesystems, in mount order
+pub fn read_mounts() -> Vec<Mount> {
+    if let Ok(content) = std::fs::read_to_string("/proc/self/mountinfo") {
+        return parse_mountinfo(&content);
+    }
+    std::fs::read_to_string("/proc/self/mounts")
+        .map(|content| parse_mounts(&content))
+        .unwrap_or_default()
+}
+
+/// Mounted filesystems for the mount list. Pseudo filesystems are left out
+/// unless `show_all` is set; a mount point mounted over more than once is
+/// listed once, for the filesystem on top.
+pub fn list_mounts(show_all: bool) -> Vec<Mount> {
+    let mounts = read_mounts();
+    mounts
+        .iter()
+        .enumerate()
+        .filter(|(i, m)| {
+            !mounts[i + 1..]
+                .iter()
+                .any(|later| later.mount_point == m.mount_point)
+        })
+        .filter(|(_, m)| show_all || !m.is_pseudo())
+        .map(|(_, m)| m.clone())
+        .collect()
+}
+
+/// Read the space of each mount in the background. statvfs can hang on an
+/// unreachable network filesystem, so every mount gets its own thread and a
+/// hung one only leaves its own figures missing.
+pub fn query_spaces(mounts: &[Mount]) -> Receiver<(PathBuf, DiskSpace)> {
+    let (tx, rx) = mpsc::channel();
+    for mount in mounts {
+        let tx = tx.clone();
+        let mount_point = mount.mount_point.clone();
+        thread::spawn(move || {
+            if let Some(space) = disk_space(&mount_point) {
+                let _ = tx.send((mount_point, space));
+            }
+        });
+    }
+    rx
+}
+
+/// The filesystem `path` lives on: the mount with the longest matching
+/// mount point (the last one wins when mounts are stacked)
+pub fn mount_for(mounts: &[Mount], path: &Path) -> Option<Mount> {
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    mounts
+        .iter()
+        .filter(|m| path.starts_with(&m.mount_point))
+        .max_by_key(|m| m.mount_point.components().count())
+        .cloned()
+}
+
+/// Total and available space of the filesystem holding `path`
+pub fn disk_space(path: &Path) -> Option<DiskSpace> {
+    let c_path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: c_path is NUL-terminated and stat is a valid out-pointer
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    let fragment = stat.f_frsize as u64;
+    Some(DiskSpace {
+        total: stat.f_blocks as u64 * fragment,
+        free: stat.f_bfree as u64 * fragment,
+        available: stat.f_bavail as u64 * fragment,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_mounts_and_picks_the_deepest_mount_point() {
+        let mounts = parse_mounts(
+            "/dev/sda1 / ext4 rw,relatime 0 0\n\
+             /dev/sdb1 /mnt/my\\040disk xfs rw 0 0\n\
+             tmpfs /mnt tmpfs rw 0 0\n",
+        );
+        assert_eq!(mounts.len(), 3);
+        assert_eq!(mounts[1].mount_point, PathBuf::from("/mnt/my disk"));
+
+        let mount = |p: &str| mount_for(&mounts, Path::new(p)).map(|m| m.device);
+        assert_eq!(
+            mount("/nonexistent-rmc/mnt/my disk/file"),
+            Some("/dev/sda1".into())
+        );
+        assert_eq!(
+            mount("/mnt/my disk/nonexistent-rmc"),
+            Some("/dev/sdb1".into())
+        );
+        assert_eq!(mount("/mnt/other"), Some("tmpfs".into()));
+    }
+
+    #[test]
+    fn mountinfo_takes_the_mount_point_and_the_fields_after_the_separator() {
+        let info = parse_mountinfo(
+            "23 28 0:22 / /proc rw,relatime - proc proc rw\n\
+             28 1 8:1 / / rw,relatime shared:1 master:2 - ext4 /dev/sda1 rw\n\
+             40 28 8:17 /data /srv/my\\040data rw - xfs /dev/sdb1 rw\n\
+             41 28 8:18 / /broken rw\n",
+        );
+        assert_eq!(info.len(), 3);
+        assert!(info[0].is_pseudo());
+        assert!(!info[1].is_pseudo());
+        assert_eq!(info[1].fs_type, "ext4");
+        assert_eq!(info[1].mount_point, PathBuf::from("/"));
+        assert_eq!(info[2].device, "/dev/sdb1");
+        // The root within the filesystem (/data) is not the mount point
+        assert_eq!(info[2].mount_point, PathBuf::from("/srv/my data"));
+    }
+
+    #[test]
+    fn spaces_are_read_in_the_background() {
+        let root = Mount {
+            device: "rootfs".to_string(),
+            mount_point: PathBuf::from("/"),
+            fs_type: "ext4".to_string(),
+        };
+        let (mount_point, space) = query_spaces(&[root])
+            .recv_timeout(std::time::Duration::from_secs(5))
+            .unwrap();
+        assert_eq!(mount_point, PathBuf::from("/"));
+        assert!(space.total >= space.free && space.free >= space.available);
+    }
+}
@@ -1,13 +1,25 @@
 use std::{
     collections::HashSet,
     path::{Path, PathBuf},
-    sync::mpsc::{self, Receiver},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver},
+    },
     thread,
     time::{Duration, Instant},
 };
 
 use ratatui::widgets::ListState;
-use walkdir::WalkDir;
+
+use crate::{
+    archive::{ArchiveFormat, ArchiveListing, ArchiveView},
+    dir_size::dir_size_with_progress,
+    history::DirHistory,
+    mounts::{self, DiskSpace},
+    tree::DirTree,
+    util::glob_match,
+};
 
 /// Threshold after which we show "Loading..." indicator
 const LOADING_INDICATOR_THRESHOLD: Duration = Duration::from_millis(100);
@@ -37,7 +49,8 @@ pub struct Entry {
     pub name: String,
     pub path: PathBuf,
     pub is_dir: bool,
-    /// File size in bytes (Some for files, None for directories in quick mode)
+    /// Size in bytes. Always Some for files; for directories it depends on
+    /// the size display mode (inode size in Quick, recursive total in Full)
     pub size: Option<u64>,
 }
 
@@ -52,62 +65,121 @@ pub enum Pane {
 pub struct LoadResult {
     pub path: PathBuf,
     pub entries: Result<Vec<Entry>, String>,
+    /// Listing of the archive being shown, if it had to be read
+    pub listing: Option<Arc<ArchiveListing>>,
 }
 
 /// Result from async size calculation - uses path for safety across refreshes
 pub struct SizeResult {
     pub path: PathBuf,
     pub size: u64,
+    /// False for a running total while the directory is still being walked
+    pub complete: bool,
 }
 
 pub struct PaneState {
     pub path: PathBuf,
     pub entries: Vec<Entry>,
     pub list_state: ListState,
-    pub selected: HashSet<usize>,
+    /// Paths of explicitly selected entries. Keyed by path rather than index so
+    /// the selection stays on the same files when the listing is reloaded.
+    pub selected: HashSet<PathBuf>,
     pub show_hidden: bool,
+    /// Directories visited in this pane, for back/forward navigation
+    pub history: DirHistory,
+    /// Panelized search results. When set, the pane lists these paths (named
+    /// relative to `path`) instead of the contents of `path`.
+    pub panel: Option<Vec<PathBuf>>,
+    /// Directory tree shown instead of the listing, if in tree mode
+    pub tree: Option<DirTree>,
+    /// Archive being browsed. `path` is then the archive file joined with
+    /// the directory inside it, and entries have such virtual paths too.
+    pub archive: Option<ArchiveView>,
     /// Receiver for async directory loading results
     load_rx: Option<Receiver<LoadResult>>,
     /// When async loading started (for "Loading..." display)
     pub loading_since: Option<Instant>,
+    /// The directory changed while a load was running; reload once it is done
+    reload_pending: bool,
     /// Current size display mode
     pub size_mode: SizeDisplayMode,
     /// Receiver for async size calculation results
     size_rx: Option<Receiver<SizeResult>>,
+    /// Stops the running size calculation when the listing changes
+    size_cancel: Option<Arc<AtomicBool>>,
     /// When size calculation started
     pub size_calc_since: Option<Instant>,
+    /// Directories whose listed size is a running total
+    pub partial_sizes: HashSet<PathBuf>,
+    /// Space on the filesystem holding `path`, as of the last reload
+    pub free_space: Option<DiskSpace>,
+    /// Receiver for the free space read after a reload
+    space_rx: Option<Receiver<Option<DiskSpace>>>,
 }
 
 impl PaneState {
     pub fn new(path: PathBuf) -> std::io::Result<Self> {
-        let mut state = Self {
+        let mut state = Self::unloaded(path);
+        state.load_entries()?;
+        if !state.entries.is_empty() {
+            state.list_state.select(Some(0));
+        }
+        Ok(state)
+    }
+
+    /// A pane for `path` with no entries yet; the listing is read by the next
+    /// `load_entries_async`
+    pub fn unloaded(path: PathBuf) -> Self {
+        let mut history = DirHistory::default();
+        history.visit(&path);
+        Self {
             path,
+            history,
+            panel: None,
+            tree: None,
+            archive: None,
             entries: Vec::new(),
             list_state: ListState::default(),
             selected: HashSet::new(),
             show_hidden: false,
             load_rx: None,
             loading_since: None,
+            reload_pending: false,
             size_mode: SizeDisplayMode::None,
             size_rx: None,
+            size_cancel: None,
             size_calc_since: None,
-        };
-        state.load_entries()?;
-        if !state.entries.is_empty() {
-            state.list_state.select(Some(0));
+            partial_sizes: HashSet::new(),
+            free_space: None,
+            space_rx: None,
         }
-        Ok(state)
     }
 
-    /// Synchronous directory loading (used for initial load)
+    /// Synchronous directory loading (used for initial load).
+    /// Cursor and selection follow their paths; selected paths that are no
+    /// longer listed are dropped.
     pub fn load_entries(&mut self) -> std::io::Result<()> {
-        self.entries.clear();
-        self.selected.clear();
-        // Cancel any pending size calculations
-        self.size_rx = None;
-        self.size_calc_since = None;
+        self.cancel_size_calculation();
 
-        self.entries = load_directory_entries(&self.path, self.show_hidden, self.size_mode)?;
+        let entries = if let Some(view) = &mut self.archive {
+            let listing = match &view.listing {
+                Some(listing) => Arc::clone(listing),
+                None => {
+                    let listing =
+                        Arc::new(ArchiveListing::read(&view.file).map_err(std::io::Error::other)?);
+                    view.listing = Some(Arc::clone(&listing));
+                    listing
+                }
+            };
+            load_archive_entries(view, &listing, self.show_hidden, self.size_mode)
+        } else {
+            match &self.panel {
+                Some(paths) => load_panel_entries(&self.path, paths, self.size_mode),
+                None => load_directory_entries(&self.path, self.show_hidden, self.size_mode)?,
+            }
+        };
+        self.replace_entries_preserving_state(entries);
+        self.refresh_free_space();
 
         // If in full mode, start async size calculation for directories
         if self.size_mode == SizeDisplayMode::Full {
@@ -122,19 +194,52 @@ impl PaneState {
         let path = self.path.clone();
         let show_hidden = self.show_hidden;
         let size_mode = self.size_mode;
+        let panel = self.panel.clone();
+        let archive = self
+            .archive
+            .as_ref()
+            .map(|view| (view.file.clone(), view.dir.clone(), view.listing.clone()));
 
-        // Cancel any pending size calculations
-        self.size_rx = None;
-        self.size_calc_since = None;
+        self.cancel_size_calculation();
 
         let (tx, rx) = mpsc::channel();
         self.load_rx = Some(rx);
         self.loading_since = Some(Instant::now());
+        self.reload_pending = false;
 
         thread::spawn(move || {
-            let entries = load_directory_entries(&path, show_hidden, size_mode)
-                .map_err(|e| format_io_error(&e));
-            let _ = tx.send(LoadResult { path, entries });
+            let (entries, listing) = match (archive, panel) {
+                (Some((file, dir, listing)), _) => {
+                    let listing = match listing {
+                        Some(listing) => Ok(listing),
+                        None => ArchiveListing::read(&file).map(Arc::new),
+                    };
+                    match listing {
+                        Ok(listing) => {
+                            let view = ArchiveView {
+                                file,
+                                dir,
+                                listing: None,
+                            };
+                            let entries =
+                                load_archive_entries(&view, &listing, show_hidden, size_mode);
+                            (Ok(entries), Some(listing))
+                        }
+                        Err(e) => (Err(e), None),
+                    }
+                }
+                (None, Some(paths)) => (Ok(load_panel_entries(&path, &paths, size_mode)), None),
+                (None, None) => (
+                    load_directory_entries(&path, show_hidden, size_mode)
+                        .map_err(|e| format_io_error(&e)),
+                    None,
+                ),
+            };
+            let _ = tx.send(LoadResult {
+                path,
+                entries,
+                listing,
+            });
         });
     }
 
@@ -148,26 +253,36 @@ impl PaneState {
                 self.loading_since = None;
 
                 // Only apply if path still matches (user might have navigated away)
-                if result.path == self.path {
+                let applied = if result.path == self.path {
+                    if let (Some(view), Some(listing)) = (&mut self.archive, result.listing) {
+                        view.listing = Some(listing);
+                    }
                     match result.entries {
                         Ok(entries) => {
-                            self.entries = entries;
-                            self.selected.clear();
-                            if !self.entries.is_empty() && self.list_state.selected().is_none() {
-                                self.list_state.select(Some(0));
-                            }
+                            self.replace_entries_preserving_state(entries);
+                            self.refresh_free_space();
                             // Start size calculation for directories in full mode
                             if self.size_mode == SizeDisplayMode::Full {
                                 self.start_size_calculation();
                             }
                             Some(Ok(()))
                         }
-                        Err(e) => Some(Err(e)),
+                        Err(e) => {
+                            // An archive that can't be read is closed again
+                            if self.archive.as_ref().is_some_and(|v| v.listing.is_none()) {
+                                self.close_archive();
+                            }
+                            Some(Err(e))
+                        }
                     }
                 } else {
                     // Path changed, ignore result
                     None
+                };
+                if self.reload_pending {
+                    self.load_entries_async();
                 }
+                applied
             }
             Err(mpsc::TryRecvError::Empty) => None,
             Err(mpsc::TryRecvError::Disconnected) => {
@@ -178,6 +293,58 @@ impl PaneState {
         }
     }
 
+    /// Read the space on the filesystem holding the directory (or the archive
+    /// being browsed) in a background thread, as statvfs can block on a
+    /// network filesystem. The previous figure is shown until it arrives.
+    fn refresh_free_space(&mut self) {
+        let path = self
+            .archive
+            .as_ref()
+            .map_or(&self.path, |view| &view.file)
+            .clone();
+        let (tx, rx) = mpsc::channel();
+        self.space_rx = Some(rx);
+        thread::spawn(move || {
+            let _ = tx.send(mounts::disk_space(&path));
+        });
+    }
+
+    pub fn poll_free_space(&mut self) {
+        if let Some(rx) = &self.space_rx
+            && let Ok(space) = rx.try_recv()
+        {
+            self.free_space = space;
+            self.space_rx = None;
+        }
+    }
+
+    /// Swap in a fresh listing, keeping the cursor and selection on the same
+    /// paths rather than the same indices
+    fn replace_entries_preserving_state(&mut self, entries: Vec<Entry>) {
+        let cursor_idx = self.list_state.selected();
+        let cursor_path = self.selected_entry().map(|e| e.path.clone());
+
+        self.entries = entries;
+        let listed: HashSet<&PathBuf> = self
+            .entries
+            .iter()
+            .filter(|e| e.name != "..")
+            .map(|e| &e.path)
+            .collect();
+        self.selected.retain(|p| listed.contains(p));
+
+        if self.entries.is_empty() {
+            self.list_state.select(None);
+            return;
+        }
+        // Follow the cursor entry by path; if it disappeared, stay at the same row
+        let idx = cursor_path
+            .and_then(|path| self.entries.iter().position(|e| e.path == path))
+            .or(cursor_idx.map(|i| i.min(self.entries.len() - 1)))
+            .unwrap_or(0);
+        self.list_state.select(Some(idx));
+    }
+
     /// Returns true if we're loading and should show the indicator
     pub fn is_loading(&self) -> bool {
         if let Some(since) = self.loading_since {
@@ -187,6 +354,16 @@ impl PaneState {
         }
     }
 
+    /// Reload in the background, or once the load already running is done,
+    /// since it may have read the directory before the change
+    pub fn request_reload(&mut self) {
+        if self.is_loading_any() {
+            self.reload_pending = true;
+        } else {
+            self.load_entries_async();
+        }
+    }
+
     /// Returns true if currently loading (regardless of threshold)
     pub fn is_loading_any(&self) -> bool {
         self.loading_since.is_some()
@@ -199,6 +376,11 @@ impl PaneState {
 
     /// Start async size calculation for directories
     pub fn start_size_calculation(&mut self) {
+        // Sizes inside an archive come from its listing
+        if self.archive.is_some() {
+            return;
+        }
+
         // Collect directories that need size calculation
         let dirs_to_calc: Vec<PathBuf> = self
             .entries
@@ -212,19 +394,50 @@ impl PaneState {
         }
 
         let (tx, rx) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
         self.size_rx = Some(rx);
+        self.size_cancel = Some(Arc::clone(&cancel));
         self.size_calc_since = Some(Instant::now());
 
         thread::spawn(move || {
             for path in dirs_to_calc {
-                let size = calculate_dir_size(&path);
-                if tx.send(SizeResult { path, size }).is_err() {
+                // Stream running totals so large directories fill in gradually
+                let size = dir_size_with_progress(&path, &cancel, |size| {
+                    let partial = SizeResult {
+                        path: path.clone(),
+                        size,
+                        complete: false,
+                    };
+                    if tx.send(partial).is_err() {
+                        cancel.store(true, Ordering::Relaxed);
+                    }
+                });
+                if cancel.load(Ordering::Relaxed) {
+                    break;
+                }
+                let done = SizeResult {
+                    path,
+                    size,
+                    complete: true,
+                };
+                if tx.send(done).is_err() {
                     break; // Receiver dropped, stop calculating
                 }
             }
         });
     }
 
+    /// Stop the running size calculation, if any. Directories already
+    /// summarized stay in the shared cache.
+    fn cancel_size_calculation(&mut self) {
+        if let Some(cancel) = self.size_cancel.take() {
+            cancel.store(true, Ordering::Relaxed);
+        }
+        self.size_rx = None;
+        self.size_calc_since = None;
+        self.partial_sizes.clear();
+    }
+
     /// Poll for size calculation results and update entries
     pub fn poll_size_results(&mut self) {
         let rx = match &self.size_rx {
@@ -240,11 +453,17 @@ impl PaneState {
                     if let Some(entry) = self.entries.iter_mut().find(|e| e.path == result.path) {
                         entry.size = Some(result.size);
                     }
+                    if result.complete {
+                        self.partial_sizes.remove(&result.path);
+                    } else {
+                        self.partial_sizes.insert(result.path);
+                    }
                 }
                 Err(mpsc::TryRecvError::Empty) => break,
                 Err(mpsc::TryRecvError::Disconnected) => {
                     // All calculations complete
                     self.size_rx = None;
+                    self.size_cancel = None;
                     self.size_calc_since = None;
                     break;
                 }
@@ -255,9 +474,6 @@ impl PaneState {
     /// Cycle size display mode and reload entries
     pub fn cycle_size_mode(&mut self) {
         self.size_mode = self.size_mode.cycle();
-        // Cancel any pending size calculations
-        self.size_rx = None;
-        self.size_calc_since = None;
         // Reload to get sizes
         let _ = self.load_entries();
     }
@@ -265,7 +481,42 @@ impl PaneState {
     pub fn toggle_hidden(&mut self) {
         self.show_hidden = !self.show_hidden;
         let _ = self.load_entries();
+    }
+
+    /// Replace the listing with search results found under `root`
+    pub fn panelize(&mut self, root: PathBuf, paths: Vec<PathBuf>) {
+        self.path = root;
+        self.panel = Some(paths);
+        self.selected.clear();
+        self.list_state.select(Some(0));
+        let _ = self.load_entries();
+    }
+
+    /// Leave a panelized listing and show the root directory again.
+    /// Returns false if the pane was not panelized.
+    pub fn exit_panel(&mut self) -> bool {
+        if self.panel.take().is_none() {
+            return false;
+        }
+        self.selected.clear();
         self.list_state.select(Some(0));
+        let _ = self.load_entries();
+        true
+    }
+
+    /// Whether a change in `dir` affects this pane's listing
+    pub fn lists_dir(&self, dir: &Path) -> bool {
+        match self.panel {
+            Some(_) => dir.starts_with(&self.path),
+            None => self.path == dir,
+        }
+    }
+
+    /// Put the cursor on the entry for `path`, if it is listed
+    pub fn select_path(&mut self, path: &Path) {
+        if let Some(idx) = self.entries.iter().position(|e| e.path == path) {
+            self.list_state.select(Some(idx));
+        }
     }
 
     pub fn selected_entry(&self) -> Option<&Entry> {
@@ -273,18 +524,18 @@ impl PaneState {
     }
 
     pub fn move_up(&mut self) {
-        if let Some(selected) = self.list_state.selected() {
-            if selected > 0 {
-                self.list_state.select(Some(selected - 1));
-            }
+        if let Some(selected) = self.list_state.selected()
+            && selected > 0
+        {
+            self.list_state.select(Some(selected - 1));
         }
     }
 
     pub fn move_down(&mut self) {
-        if let Some(selected) = self.list_state.selected() {
-            if selected < self.entries.len().saturating_sub(1) {
-                self.list_state.select(Some(selected + 1));
-            }
+        if let Some(selected) = self.list_state.selected()
+            && selected < self.entries.len().saturating_sub(1)
+        {
+            self.list_state.select(Some(selected + 1));
         }
     }
 
@@ -304,25 +555,13 @@ impl PaneState {
     }
 
     pub fn toggle_selection(&mut self) {
-        if let Some(idx) = self.list_state.selected() {
-            // Don't allow selecting ".."
-            if idx == 0
-                && self
-                    .entries
-                    .first()
-                    .map(|e| e.name == "..")
-                    .unwrap_or(false)
-            {
-                // Move down without selecting
-                self.move_down();
-                return;
-            }
-
-            // Toggle selection
-            if self.selected.contains(&idx) {
-                self.selected.remove(&idx);
-            } else {
-                self.selected.insert(idx);
+        if let Some(entry) = self.selected_entry() {
+            // Don't allow selecting ".." - just move down
+            if entry.name != ".." {
+                let path = entry.path.clone();
+                if !self.selected.remove(&path) {
+                    self.selected.insert(path);
+                }
             }
 
             // Move cursor down
@@ -330,19 +569,100 @@ impl PaneState {
         }
     }
 
+    pub fn is_selected(&self, entry: &Entry) -> bool {
+        self.selected.contains(&entry.path)
+    }
+
     /// Select all items (except "..")
     pub fn select_all(&mut self) {
-        let start = if self
+        self.selected = self
             .entries
-            .first()
-            .map(|e| e.name == "..")
-            .unwrap_or(false)
-        {
-            1
-        } else {
-            0
+            .iter()
+            .filter(|e| e.name != "..")
+            .map(|e| e.path.clone())
+            .collect();
+    }
+
+    /// Select or unselect all entries whose name matches a glob pattern.
+    /// Returns the number of entries that matched.
+    pub fn select_by_pattern(
+        &mut self,
+        pattern: &str,
+        files_only: bool,
+        case_sensitive: bool,
+        select: bool,
+    ) -> usize {
+        let matches: Vec<PathBuf> = self
+            .entries
+            .iter()
+            .filter(|e| e.name != "..")
+            .filter(|e| !(files_only && e.is_dir))
+            .filter(|e| glob_match(pattern, &e.name, case_sensitive))
+            .map(|e| e.path.clone())
+            .collect();
+
+        let count = matches.len();
+        for path in matches {
+            if select {
+                self.selected.insert(path);
+            } else {
+                self.selected.remove(&path);
+            }
+        }
+        count
+    }
+
+    /// Invert the selection (".." is never selected)
+    pub fn invert_selection(&mut self) {
+        self.selected = self
+            .entries
+            .iter()
+            .filter(|e| e.name != ".." && !self.selected.contains(&e.path))
+            .map(|e| e.path.clone())
+            .collect();
+    }
+
+    /// Select all files with the same extension as the cursor entry.
+    /// Returns the number of entries that matched.
+    pub fn select_same_extension(&mut self) -> usize {
+        let Some(entry) = self.selected_entry() else {
+            return 0;
         };
-        self.selected = (start..self.entries.len()).collect();
+        if entry.is_dir || entry.name == ".." {
+            return 0;
+        }
+        let ext = Path::new(&entry.name)
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase());
+
+        let matches: Vec<PathBuf> = self
+            .entries
+            .iter()
+            .filter(|e| !e.is_dir)
+            .filter(|e| {
+                Path::new(&e.name)
+                    .extension()
+                    .map(|x| x.to_string_lossy().to_lowercase())
+                    == ext
+            })
+            .map(|e| e.path.clone())
+            .collect();
+
+        let count = matches.len();
+        self.selected.extend(matches);
+        count
+    }
+
+    /// Number of explicitly selected entries and their total size in bytes.
+    /// Directories only contribute once their size is known (Full size mode).
+    pub fn selection_summary(&self) -> (usize, u64) {
+        let total = self
+            .entries
+            .iter()
+            .filter(|e| self.is_selected(e))
+            .filter_map(|e| e.size)
+            .sum();
+        (self.selected.len(), total)
     }
 
     pub fn selected_entries(&self) -> Vec<&Entry> {
@@ -350,51 +670,138 @@ impl PaneState {
             // If nothing explicitly selected, return cursor item
             self.selected_entry().into_iter().collect()
         } else {
-            self.selected
+            // Listing order, so operations run in the order the user sees
+            self.entries
                 .iter()
-                .filter_map(|&i| self.entries.get(i))
+                .filter(|e| self.is_selected(e))
                 .collect()
         }
     }
 
     pub fn enter_selected(&mut self) -> Result<(), String> {
-        if let Some(entry) = self.selected_entry().cloned() {
-            if entry.is_dir {
-                let going_up = entry.name == "..";
-                let old_path = self.path.clone();
-                let old_entries = std::mem::take(&mut self.entries);
-                let old_selection = self.list_state.selected();
-                let old_selected = std::mem::take(&mut self.selected);
-
-                self.path = entry.path.canonicalize().unwrap_or(entry.path);
-
-                if let Err(e) = self.load_entries() {
-                    // Restore previous state on failure
-                    self.path = old_path;
-                    self.entries = old_entries;
-                    self.list_state.select(old_selection);
-                    self.selected = old_selected;
-
-                    return Err(format_io_error(&e));
-                }
+        if self.archive.is_some() {
+            self.enter_archive_selected();
+            return Ok(());
+        }
+        if let Some(entry) = self.selected_entry().cloned()
+            && !entry.is_dir
+            && ArchiveFormat::detect(&entry.path).is_some()
+        {
+            self.open_archive(entry.path);
+            return Ok(());
+        }
+        if let Some(entry) = self.selected_entry().cloned()
+            && entry.is_dir
+        {
+            let going_up = entry.name == "..";
+            let old_path = self.path.clone();
+            let old_entries = std::mem::take(&mut self.entries);
+            let old_selection = self.list_state.selected();
+            let old_selected = std::mem::take(&mut self.selected);
+            let old_panel = self.panel.take();
 
-                if going_up {
-                    // Select the directory we just came from
-                    let idx = self
-                        .entries
-                        .iter()
-                        .position(|e| e.path == old_path)
-                        .unwrap_or(0);
-                    self.list_state.select(Some(idx));
-                } else {
-                    self.list_state.select(Some(0));
-                }
+            self.path = entry.path.canonicalize().unwrap_or(entry.path);
+
+            if let Err(e) = self.load_entries() {
+                // Restore previous state on failure
+                self.path = old_path;
+                self.panel = old_panel;
+                self.entries = old_entries;
+                self.list_state.select(old_selection);
+                self.selected = old_selected;
+
+                return Err(format_io_error(&e));
+            }
+
+            if going_up {
+                // Select the directory we just came from
+                let idx = self
+                    .entries
+                    .iter()
+                    .position(|e| e.path == old_path)
+                    .unwrap_or(0);
+                self.list_state.select(Some(idx));
+            } else {
+                self.list_state.select(Some(0));
             }
         }
         Ok(())
     }
 }
 
+// ============================================================================
+// Archive Browsing
+// ============================================================================
+
+impl PaneState {
+    /// Show the top level of an archive. Its listing is read in the
+    /// background; if that fails the pane goes back to the archive's directory.
+    pub fn open_archive(&mut self, file: PathBuf) {
+        let view = ArchiveView::new(file);
+        self.path = view.path();
+        self.archive = Some(view);
+        self.panel = None;
+        self.entries.clear();
+        self.selected.clear();
+        self.list_state.select(Some(0));
+        self.load_entries_async();
+    }
+
+    /// Leave the archive for the directory holding it, with the cursor on
+    /// the archive file
+    pub fn close_archive(&mut self) {
+        let Some(view) = self.archive.take() else {
+            return;
+        };
+        self.path = view
+            .file
+            .parent()
+            .map_or_else(|| PathBuf::from("/"), Path::to_path_buf);
+        self.selected.clear();
+        let _ = self.load_entries();
+        self.select_path(&view.file);
+    }
+
+    /// Go up one directory inside the archive, leaving it from the top
+    /// level. Returns false if the pane is not showing an archive.
+    pub fn archive_up(&mut self) -> bool {
+        let Some(view) = &mut self.archive else {
+            return false;
+        };
+        let came_from = view.path();
+        if !view.up() {
+            self.close_archive();
+            return true;
+        }
+        self.path = view.path();
+        self.selected.clear();
+        let _ = self.load_entries();
+        self.select_path(&came_from);
+        true
+    }
+
+    fn enter_archive_selected(&mut self) {
+        let Some(entry) = self.selected_entry().filter(|e| e.is_dir).cloned() else {
+            return;
+        };
+        if entry.name == ".." {
+            self.archive_up();
+            return;
+        }
+        let Some(view) = &mut self.archive else {
+            return;
+        };
+        let Some(dir) = view.member(&entry.path) else {
+            return;
+        };
+        view.dir = dir;
+        self.path = view.path();
+        self.selected.clear();
+        let _ = self.load_entries();
+        self.list_state.select(Some(0));
+    }
+}
+
 // ============================================================================
 // Helper Functions
 // ============================================================================
@@ -439,13 +846,13 @@ fn load_directory_entries(
         .map(|e| {
             let metadata = e.metadata().ok();
             let is_dir = metadata.as_ref().map(|m| m.is_dir()).unwrap_or(false);
-            // In Quick mode: show entry size for all (files + directory inodes)
-            // In Full mode: show file sizes now, directory sizes calculated async
+            // File sizes are always kept (the selection summary needs them).
+            // In Quick mode: also keep directory inode sizes
+            // In Full mode: directory sizes calculated async
             let size = match size_mode {
-                SizeDisplayMode::None => None,
+                _ if !is_dir => metadata.map(|m| m.len()),
                 SizeDisplayMode::Quick => metadata.map(|m| m.len()),
-                SizeDisplayMode::Full if !is_dir => metadata.map(|m| m.len()),
-                SizeDisplayMode::Full => None, // Directory sizes calculated separately
+                SizeDisplayMode::None | SizeDisplayMode::Full => None,
             };
             Entry {
                 name: e.file_name().to_string_lossy().into_owned(),
@@ -456,24 +863,219 @@ fn load_directory_entries(
         })
         .collect();
 
-    // Sort: directories first, then by name (case-insensitive)
-    dir_entries.sort_by(|a, b| match (a.is_dir, b.is_dir) {
+    sort_entries(&mut dir_entries);
+    entries.extend(dir_entries);
+    Ok(entries)
+}
+
+/// Sort: directories first, then by name (case-insensitive)
+fn sort_entries(entries: &mut [Entry]) {
+    entries.sort_by(|a, b| match (a.is_dir, b.is_dir) {
         (true, false) => std::cmp::Ordering::Less,
         (false, true) => std::cmp::Ordering::Greater,
         _ => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
     });
+}
 
-    entries.extend(dir_entries);
-    Ok(entries)
+/// Build entries for a directory inside an archive. Directory sizes are
+/// totalled from the listing in Full mode.
+fn load_archive_entries(
+    view: &ArchiveView,
+    listing: &ArchiveListing,
+    show_hidden: bool,
+    size_mode: SizeDisplayMode,
+) -> Vec<Entry> {
+    let here = view.path();
+    let mut entries = vec![Entry {
+        name: "..".to_owned(),
+        path: here
+            .parent()
+            .map_or_else(|| here.clone(), Path::to_path_buf),
+        is_dir: true,
+        size: None,
+    }];
+
+    let mut children: Vec<Entry> = listing
+        .children(&view.dir)
+        .filter(|e| show_hidden || !e.name().starts_with('.'))
+        .map(|e| {
+            let is_dir = e.is_dir();
+            let size = match size_mode {
+                _ if !is_dir => Some(e.size),
+                SizeDisplayMode::Full => Some(listing.totals(&e.path).0),
+                SizeDisplayMode::None | SizeDisplayMode::Quick => None,
+            };
+            Entry {
+                name: e.name().to_owned(),
+                path: view.file.join(&e.path),
+                is_dir,
+                size,
+            }
+        })
+        .collect();
+
+    sort_entries(&mut children);
+    entries.extend(children);
+    entries
 }
 
-/// Calculate the total size of a directory recursively
-fn calculate_dir_size(path: &Path) -> u64 {
-    WalkDir::new(path)
-        .into_iter()
-        .filter_map(|e| e.ok())
-        .filter(|e| e.file_type().is_file())
-        .filter_map(|e| e.metadata().ok())
-        .map(|m| m.len())
-        .sum()
+/// Build entries for a panelized listing. Paths that no longer exist are
+/// dropped; names are shown relative to `root`.
+fn load_panel_entries(root: &Path, paths: &[PathBuf], size_mode: SizeDisplayMode) -> Vec<Entry> {
+    // ".." leaves the panel and goes back to the search root
+    let mut entries = vec![Entry {
+        name: "..".to_owned(),
+        path: root.to_path_buf(),
+        is_dir: true,
+        size: None,
+    }];
+
+    entries.extend(paths.iter().filter_map(|path| {
+        let metadata = std::fs::metadata(path).ok()?;
+        let is_dir = metadata.is_dir();
+        let size = match size_mode {
+            _ if !is_dir => Some(metadata.len()),
+            SizeDisplayMode::Quick => Some(metadata.len()),
+            SizeDisplayMode::None | SizeDisplayMode::Full => None,
+        };
+        let name = path
+            .strip_prefix(root)
+            .unwrap_or(path)
+            .display()
+            .to_string();
+        Some(Entry {
+            name,
+            path: path.clone(),
+            is_dir,
+            size,
+        })
+    }));
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+
+    use crate::test_util::unique_temp_dir;
+
+    #[test]
+    fn reload_keeps_cursor_and_selection_on_same_files() {
+        let dir = unique_temp_dir("pane-reload");
+        fs::create_dir_all(&dir).unwrap();
+        for name in ["b.txt", "c.txt", "d.txt"] {
+            fs::write(dir.join(name), b"x").unwrap();
+        }
+
+        let mut pane = PaneState::new(dir.clone()).unwrap();
+        let idx = |pane: &PaneState, name: &str| {
+            pane.entries.iter().position(|e| e.name == name).unwrap()
+        };
+
+        pane.list_state.select(Some(idx(&pane, "c.txt")));
+        pane.toggle_selection(); // selects c.txt, cursor moves to d.txt
+
+        // New entries sort before the existing ones and shift every index
+        fs::write(dir.join("a.txt"), b"x").unwrap();
+        fs::remove_file(dir.join("b.txt")).unwrap();
+        pane.load_entries().unwrap();
+
+        assert_eq!(pane.selected_entry().unwrap().name, "d.txt");
+        let selected: Vec<&str> = pane
+            .selected_entries()
+            .iter()
+            .map(|e| e.name.as_str())
+            .collect();
+        assert_eq!(selected, vec!["c.txt"]);
+
+        // Hiding entries must not move the selection onto other files
+        fs::write(dir.join(".hidden"), b"x").unwrap();
+        pane.toggle_hidden();
+        pane.toggle_hidden();
+        assert_eq!(pane.selected_entry().unwrap().name, "d.txt");
+        assert_eq!(pane.selection_summary().0, 1);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn change_during_a_load_reloads_once_the_load_finishes() {
+        let dir = unique_temp_dir("pane-pending-reload");
+        fs::create_dir_all(&dir).unwrap();
+        let mut pane = PaneState::new(dir.clone()).unwrap();
+
+        pane.load_entries_async();
+        fs::write(dir.join("late.txt"), b"x").unwrap();
+        pane.request_reload();
+        while pane.poll_load_result().is_none() {
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        // The first load may have missed the file, so another one runs
+        assert!(pane.is_loading_any());
+        while pane.poll_load_result().is_none() {
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        assert!(!pane.is_loading_any());
+        assert!(pane.entries.iter().any(|e| e.name == "late.txt"));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn free_space_arrives_after_the_listing() {
+        let dir = unique_temp_dir("pane-free-space");
+        fs::create_dir_all(&dir).unwrap();
+        let mut pane = PaneState::new(dir.clone()).unwrap();
+        for _ in 0..200 {
+            pane.poll_free_space();
+            if pane.free_space.is_some() {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        assert!(pane.free_space.is_some_and(|s| s.total >= s.available));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn browses_archive_directories_and_leaves_onto_the_archive() {
+        let dir = unique_temp_dir("pane-archive");
+        fs::create_dir_all(&dir).unwrap();
+        let tar_path = dir.join("bundle.tar");
+        let mut tar = tar::Builder::new(fs::File::create(&tar_path).unwrap());
+        let mut header = tar::Header::new_gnu();
+        header.set_size(2);
+        tar.append_data(&mut header, "src/main.rs", &b"fn"[..])
+            .unwrap();
+        tar.into_inner().unwrap();
+
+        let mut pane = PaneState::new(dir.clone()).unwrap();
+        pane.select_path(&tar_path);
+        pane.enter_selected().unwrap();
+        while pane.poll_load_result().is_none() {
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        let names = |pane: &PaneState| -> Vec<String> {
+            pane.entries.iter().map(|e| e.name.clone()).collect()
+        };
+        assert_eq!(names(&pane), ["..", "src"]);
+
+        pane.list_state.select(Some(1));
+        pane.enter_selected().unwrap();
+        assert_eq!(names(&pane), ["..", "main.rs"]);
+        assert_eq!(pane.path, tar_path.join("src"));
+
+        assert!(pane.archive_up());
+        assert_eq!(pane.selected_entry().unwrap().name, "src");
+        assert!(pane.archive_up());
+        assert!(pane.archive.is_none());
+        assert_eq!(pane.path, dir);
+        assert_eq!(pane.selected_entry().unwrap().path, tar_path);
+        assert!(!pane.archive_up());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
 }
@@ -0,0 +1,331 @@
+//! File properties for the popup and the info pane
+//!
+//! Properties are read in a background thread, since finding the MIME type
+//! runs file(1) and the filesystem may be slow. A directory's recursive size
+//! is summed afterwards in the same thread, which stops when the view is
+//! dropped.
+
+use std::{
+    fs::FileType,
+    os::unix::fs::{FileTypeExt, MetadataExt},
+    path::{Path, PathBuf},
+    process::Command,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver},
+    },
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    attributes::{group_name, mode_string, user_name},
+    dir_size::calculate_dir_size,
+    mounts::{self, DiskSpace, Mount},
+    util::{format_bytes, utc_datetime},
+};
+
+pub struct Properties {
+    pub path: PathBuf,
+    pub kind: &'static str,
+    pub is_dir: bool,
+    pub size: u64,
+    /// Allocated size on disk (512-byte blocks)
+    pub allocated: u64,
+    pub inode: u64,
+    pub links: u64,
+    pub mode: u32,
+    pub uid: u32,
+    pub gid: u32,
+    pub owner: String,
+    pub group: String,
+    pub accessed: Option<SystemTime>,
+    pub modified: Option<SystemTime>,
+    pub changed: Option<SystemTime>,
+    pub created: Option<SystemTime>,
+    /// Target of a symlink, and whether it exists
+    pub symlink_target: Option<(PathBuf, bool)>,
+    pub mime: Option<String>,
+    pub mount: Option<Mount>,
+    pub space: Option<DiskSpace>,
+}
+
+fn kind_label(file_type: FileType) -> &'static str {
+    if file_type.is_symlink() {
+        "Symbolic link"
+    } else if file_type.is_dir() {
+        "Directory"
+    } else if file_type.is_file() {
+        "Regular file"
+    } else if file_type.is_fifo() {
+        "Named pipe"
+    } else if file_type.is_socket() {
+        "Socket"
+    } else if file_type.is_block_device() {
+        "Block device"
+    } else if file_type.is_char_device() {
+        "Character device"
+    } else {
+        "Unknown"
+    }
+}
+
+/// MIME type as reported by file(1), if it is installed
+fn mime_type(path: &Path) -> Option<String> {
+    let output = Command::new("file")
+        .args(["--brief", "--mime-type", "--"])
+        .arg(path)
+        .output()
+        .ok()?;
+    let mime = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !mime.is_empty()).then_some(mime)
+}
+
+fn format_time(time: Option<SystemTime>) -> String {
+    let Some(time) = time else {
+        return "-".to_string();
+    };
+    let (year, month, day, hour, minute, second) = utc_datetime(time);
+    format!("{year:04}-{month:02}-{day:02} {hour:02}:{minute:02}:{second:02} UTC")
+}
+
+impl Properties {
+    /// Read the properties of `path` itself (symlinks are not followed)
+    pub fn read(path: &Path) -> Result<Self, String> {
+        let metadata = std::fs::symlink_metadata(path)
+            .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+        let file_type = metadata.file_type();
+        let ctime = UNIX_EPOCH
+            + Duration::new(
+                metadata.ctime().max(0) as u64,
+                metadata.ctime_nsec().clamp(0, 999_999_999) as u32,
+            );
+        let symlink_target = file_type
+            .is_symlink()
+            .then(|| std::fs::read_link(path).ok())
+            .flatten()
+            .map(|target| (target, path.exists()));
+        let mime = (file_type.is_file() || file_type.is_symlink())
+            .then(|| mime_type(path))
+            .flatten();
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            kind: kind_label(file_type),
+            is_dir: file_type.is_dir(),
+            size: metadata.len(),
+            allocated: metadata.blocks() * 512,
+            inode: metadata.ino(),
+            links: metadata.nlink(),
+            mode: metadata.mode(),
+            uid: metadata.uid(),
+            gid: metadata.gid(),
+            owner: user_name(metadata.uid()),
+            group: group_name(metadata.gid()),
+            accessed: metadata.accessed().ok(),
+            modified: metadata.modified().ok(),
+            changed: Some(ctime),
+            created: metadata.created().ok(),
+            symlink_target,
+            mime,
+            mount: mounts::mount_for(&mounts::read_mounts(), path),
+            space: mounts::disk_space(path),
+        })
+    }
+
+    /// Label/value rows for display. `dir_size` is the recursive size of a
+    /// directory, None while it is being computed.
+    pub fn rows(&self, dir_size: Option<u64>) -> Vec<(&'static str, String)> {
+        let size = match (self.is_dir, dir_size) {
+            (true, Some(total)) => format!("{} ({} bytes)", format_bytes(total), total),
+            (true, None) => "calculating...".to_string(),
+            (false, _) => format!("{} ({} bytes)", format_bytes(self.size), self.size),
+        };
+        let mut rows = vec![
+            ("Path", self.path.display().to_string()),
+            ("Type", self.kind.to_string()),
+        ];
+        if let Some((target, exists)) = &self.symlink_target {
+            let broken = if *exists { "" } else { " (broken)" };
+            rows.push(("Target", format!("{}{}", target.display(), broken)));
+        }
+        if let Some(mime) = &self.mime {
+            rows.push(("MIME type", mime.clone()));
+        }
+        rows.extend([
+            ("Size", size),
+            ("Allocated", format_bytes(self.allocated)),
+            ("Inode", self.inode.to_string()),
+            ("Links", self.links.to_string()),
+            (
+                "Mode",
+                format!("{} ({:04o})", mode_string(self.mode), self.mode & 0o7777),
+            ),
+            ("Owner", format!("{} ({})", self.owner, self.uid)),
+            ("Group", format!("{} ({})", self.group, self.gid)),
+            ("Accessed", format_time(self.accessed)),
+            ("Modified", format_time(self.modified)),
+            ("Changed", format_time(self.changed)),
+            ("Created", format_time(self.created)),
+        ]);
+        if let Some(mount) = &self.mount {
+            rows.push((
+                "Filesystem",
+                format!(
+                    "{} on {} ({})",
+                    mount.device,
+                    mount.mount_point.display(),
+                    mount.fs_type
+                ),
+            ));
+        }
+        if let Some(space) = self.space {
+            rows.push((
+                "Free space",
+                format!(
+                    "{} of {}",
+                    format_bytes(space.available),
+                    format_bytes(space.total)
+                ),
+            ));
+        }
+        rows
+    }
+}
+
+/// What the background thread of a `PropertiesView` reports, in order
+enum Loaded {
+    Props(Result<Box<Properties>, String>),
+    DirSize(u64),
+}
+
+/// Properties of one entry, read in the background together with a
+/// directory's size
+pub struct PropertiesView {
+    pub path: PathBuf,
+    /// None while loading
+    pub props: Option<Result<Box<Properties>, String>>,
+    pub dir_size: Option<u64>,
+    rx: Option<Receiver<Loaded>>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl PropertiesView {
+    pub fn start(path: PathBuf) -> Self {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let (tx, rx) = mpsc::channel();
+        let worker_cancel = Arc::clone(&cancel);
+        let worker_path = path.clone();
+        thread::spawn(move || {
+            let props = Properties::read(&worker_path).map(Box::new);
+            let is_dir = props.as_ref().is_ok_and(|p| p.is_dir);
+            if tx.send(Loaded::Props(props)).is_err() || !is_dir {
+                return;
+            }
+            let size = calculate_dir_size(&worker_path, &worker_cancel);
+            if !worker_cancel.load(Ordering::Relaxed) {
+                let _ = tx.send(Loaded::DirSize(size));
+            }
+        });
+        Self {
+            path,
+            props: None,
+            dir_size: None,
+            rx: Some(rx),
+            cancel,
+        }
+    }
+
+    pub fn poll(&mut self) {
+        let Some(rx) = &self.rx else {
+            return;
+        };
+        loop {
+            match rx.try_recv() {
+                Ok(Loaded::Props(props)) => self.props = Some(props),
+                Ok(Loaded::DirSize(size)) => self.dir_size = Some(size),
+                Err(mpsc::TryRecvError::Empty) => return,
+                Err(mpsc::TryRecvError::Disconnected) => break,
+            }
+        }
+        self.rx = None;
+    }
+
+    pub fn rows(&self) -> Vec<(&'static str, String)> {
+        match &self.props {
+            Some(Ok(props)) => props.rows(self.dir_size),
+            Some(Err(msg)) => vec![("Error", msg.clone())],
+            None => vec![
+                ("Path", self.path.display().to_string()),
+                ("Type", "reading...".to_string()),
+            ],
+        }
+    }
+}
+
+impl Drop for PropertiesView {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+
+    use crate::test_util::unique_temp_dir;
+
+    #[test]
+    fn reads_properties_and_sums_directory_sizes() {
+        let dir = unique_temp_dir("properties");
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("a.txt"), "hello").unwrap();
+        fs::write(dir.join("sub/b.txt"), "world!").unwrap();
+        std::os::unix::fs::symlink("missing", dir.join("dangling")).unwrap();
+
+        let file = Properties::read(&dir.join("a.txt")).unwrap();
+        assert_eq!(file.kind, "Regular file");
+        assert_eq!(file.size, 5);
+        assert_eq!(file.links, 1);
+        let rows = file.rows(None);
+        let row = |rows: &[(&str, String)], label: &str| {
+            rows.iter()
+                .find(|(l, _)| *l == label)
+                .map(|(_, v)| v.clone())
+        };
+        assert_eq!(row(&rows, "Size"), Some("5B (5 bytes)".to_string()));
+
+        let link = Properties::read(&dir.join("dangling")).unwrap();
+        assert_eq!(link.kind, "Symbolic link");
+        assert_eq!(
+            row(&link.rows(None), "Target"),
+            Some("missing (broken)".to_string())
+        );
+
+        let mut view = PropertiesView::start(dir.clone());
+        for _ in 0..200 {
+            view.poll();
+            if view.dir_size.is_some() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert!(matches!(&view.props, Some(Ok(props)) if props.is_dir));
+        assert_eq!(view.dir_size, Some(11));
+
+        let mut missing = PropertiesView::start(dir.join("nope"));
+        for _ in 0..200 {
+            missing.poll();
+            if missing.props.is_some() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert!(row(&missing.rows(), "Error").is_some());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
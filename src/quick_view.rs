@@ -0,0 +1,107 @@
+//! Quick view: a preview of the cursor file in place of the inactive pane
+//!
+//! Files are loaded into a `FileViewer` in a background thread, so moving the
+//! cursor over large files or slow filesystems never blocks the UI. Only the
+//! start of each file is read; F3 opens the full viewer.
+
+use std::{
+    path::PathBuf,
+    sync::mpsc::{self, Receiver},
+    thread,
+};
+
+use crate::viewer::FileViewer;
+
+/// Bytes of each file read for the preview (1 MB)
+const QUICK_VIEW_MAX_SIZE: usize = 1024 * 1024;
+
+pub enum QuickViewState {
+    Loading(Receiver<Result<FileViewer, &'static str>>),
+    Ready(Box<FileViewer>),
+    /// Directories and special files are not previewed
+    Unavailable(&'static str),
+}
+
+pub struct QuickView {
+    pub path: PathBuf,
+    pub state: QuickViewState,
+}
+
+impl QuickView {
+    pub fn start(path: PathBuf) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let worker_path = path.clone();
+        thread::spawn(move || {
+            // Follow symlinks, but never open FIFOs or devices: reads could block forever
+            let result = match std::fs::metadata(&worker_path) {
+                Ok(m) if m.is_dir() => Err("Directory"),
+                Ok(m) if !m.is_file() => Err("Not a regular file"),
+                Err(_) => Err("Cannot read file"),
+                Ok(_) => Ok(FileViewer::with_limit(worker_path, QUICK_VIEW_MAX_SIZE)),
+            };
+            let _ = tx.send(result);
+        });
+        Self {
+            path,
+            state: QuickViewState::Loading(rx),
+        }
+    }
+
+    pub fn poll(&mut self) {
+        if let QuickViewState::Loading(rx) = &self.state {
+            match rx.try_recv() {
+                Ok(Ok(viewer)) => self.state = QuickViewState::Ready(Box::new(viewer)),
+                Ok(Err(reason)) => self.state = QuickViewState::Unavailable(reason),
+                Err(mpsc::TryRecvError::Empty) => {}
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    self.state = QuickViewState::Unavailable("Cannot read file");
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{fs, time::Duration};
+
+    use crate::{test_util::unique_temp_dir, viewer::ViewMode};
+
+    fn wait(view: &mut QuickView) {
+        for _ in 0..200 {
+            view.poll();
+            if !matches!(view.state, QuickViewState::Loading(_)) {
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn loads_files_in_the_background_and_skips_directories() {
+        let dir = unique_temp_dir("quick-view");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("notes.txt"), "first\nsecond\n").unwrap();
+
+        let mut view = QuickView::start(dir.join("notes.txt"));
+        wait(&mut view);
+        match &view.state {
+            QuickViewState::Ready(viewer) => {
+                assert!(viewer.mode == ViewMode::Text);
+                assert_eq!(viewer.visible_lines(10), ["first", "second"]);
+            }
+            _ => panic!("quick view did not load"),
+        }
+
+        let mut view = QuickView::start(dir.clone());
+        wait(&mut view);
+        assert!(matches!(
+            view.state,
+            QuickViewState::Unavailable("Directory")
+        ));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -2,30 +2,49 @@
 //!
 //! This module contains all UI rendering functions.
 
-use std::{path::Path, time::Duration};
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 use ratatui::{
+    Frame,
     layout::{Constraint, Layout, Rect},
     style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, Gauge, List, ListItem, Paragraph, Sparkline, Wrap},
-    Frame,
+    widgets::{
+        Block, Borders, Clear, Gauge, List, ListItem, ListState, Paragraph, Sparkline, Wrap,
+    },
 };
 
 use crate::{
+    App, SidePanel, UIMode,
+    attributes::{self, AttrForm},
+    bulk_rename::{self, BulkRename, RenameStatus},
+    compare::CompareMethod,
+    compress::{self, CompressForm, CompressFormat},
     dialog::{centered_rect, render_dialog_frame, render_yes_no_buttons},
+    diff::{DiffLayout, DiffLine, DiffViewer, RowKind},
+    extract::{self, ExtractForm},
+    find::{self, FindForm},
+    frecency::filter_jump_candidates,
+    grep::{self, GrepForm},
     job::{Job, JobStatus},
+    links::{self, LinkForm, LinkKind},
+    mounts::{DiskSpace, Mount},
     pane::{Entry, Pane, SizeDisplayMode},
+    quick_view::QuickViewState,
     theme::THEME,
-    util::{format_bytes, format_size},
-    viewer::FileViewer,
-    App, UIMode,
+    tree::DirTree,
+    util::{DU_BAR_WIDTH, format_bytes, format_size},
+    viewer::{FileViewer, Scrollable, ViewMode},
 };
 
 impl App {
     pub fn render(&mut self, frame: &mut Frame) {
         let active_jobs = self.job_manager.active_job_count();
-        let has_status = active_jobs > 0 || self.error_message.is_some();
+        let has_selection = !self.active_pane().selected.is_empty();
+        let has_status = active_jobs > 0 || has_selection || self.error_message.is_some();
 
         // Main layout: panes + optional status bar + help bar
         let main_layout = if has_status {
@@ -48,18 +67,40 @@ impl App {
             Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
                 .split(main_layout[0]);
 
-        self.left_area = pane_layout[0];
-        self.right_area = pane_layout[1];
+        // Tab bars take a line above a pane when it has more than one tab
+        let (left_tab_bar, left_area) = self.split_tab_bar(pane_layout[0], Pane::Left);
+        let (right_tab_bar, right_area) = self.split_tab_bar(pane_layout[1], Pane::Right);
+        self.left_area = left_area;
+        self.right_area = right_area;
 
-        // Skip pane rendering when in FileViewer mode - it covers the entire screen anyway
+        // Skip pane rendering in the viewers - they cover the entire screen anyway
         // and this avoids any potential access to pane state during render
-        if !matches!(self.ui_mode, UIMode::FileViewer { .. }) {
-            self.render_pane(frame, pane_layout[0], Pane::Left);
-            self.render_pane(frame, pane_layout[1], Pane::Right);
+        let full_screen = matches!(
+            self.ui_mode,
+            UIMode::FileViewer { .. } | UIMode::DiffViewer { .. }
+        );
+        if !full_screen {
+            if let Some(bar) = left_tab_bar {
+                self.render_tab_bar(frame, bar, Pane::Left);
+            }
+            if let Some(bar) = right_tab_bar {
+                self.render_tab_bar(frame, bar, Pane::Right);
+            }
+            for (area, pane) in [(left_area, Pane::Left), (right_area, Pane::Right)] {
+                match self.side_panel {
+                    SidePanel::Info if pane != self.active_pane => {
+                        self.render_info_panel(frame, area);
+                    }
+                    SidePanel::QuickView if pane != self.active_pane => {
+                        self.render_quick_view(frame, area);
+                    }
+                    _ => self.render_pane(frame, area, pane),
+                }
+            }
         }
 
-        // Status bar and help bar - skip when in FileViewer since it covers entire screen
-        if !matches!(self.ui_mode, UIMode::FileViewer { .. }) {
+        // Status bar and help bar - skip in the viewers since they cover entire screen
+        if !full_screen {
             if has_status {
                 self.render_status_bar(frame, main_layout[1]);
                 self.render_help_bar(frame, main_layout[2]);
@@ -105,11 +146,137 @@ impl App {
             UIMode::Search { query } => {
                 self.render_search_bar(frame, query);
             }
-            UIMode::FileViewer { viewer } => {
+            UIMode::SelectGroup {
+                input,
+                select,
+                files_only,
+                case_sensitive,
+            } => {
+                self.render_select_group_dialog(
+                    frame,
+                    input,
+                    *select,
+                    *files_only,
+                    *case_sensitive,
+                );
+            }
+            UIMode::DirHistory { selected } => {
+                self.render_dir_history(frame, *selected);
+            }
+            UIMode::Mounts {
+                mounts,
+                selected,
+                show_all,
+            } => {
+                self.render_mounts(frame, mounts, *selected, *show_all);
+            }
+            UIMode::Hotlist { selected, rename } => {
+                self.render_hotlist(frame, *selected, rename.as_deref());
+            }
+            UIMode::FrecencyJump {
+                query,
+                candidates,
+                selected,
+            } => {
+                self.render_frecency_jump(frame, query, candidates, *selected);
+            }
+            UIMode::FindFile { form } => {
+                self.render_find_dialog(frame, form);
+            }
+            UIMode::FindResults { selected } => {
+                self.render_find_results(frame, *selected);
+            }
+            UIMode::GrepForm { form } => {
+                self.render_grep_dialog(frame, form);
+            }
+            UIMode::GrepResults { selected } => {
+                self.render_grep_results(frame, *selected);
+            }
+            UIMode::CompareDirs { method, recursive } => {
+                self.render_compare_dialog(frame, *method, *recursive);
+            }
+            UIMode::CompareResults { selected } => {
+                self.render_compare_results(frame, *selected);
+            }
+            UIMode::FuzzyFinder => {
+                self.render_fuzzy_finder(frame);
+            }
+            UIMode::FileViewer { viewer, .. } => {
                 self.render_file_viewer(frame, viewer);
             }
-            UIMode::Normal => {}
+            UIMode::DiskUsage => {
+                self.render_disk_usage(frame);
+            }
+            UIMode::Properties => {
+                self.render_properties_popup(frame);
+            }
+            UIMode::LinkInput { form } => {
+                self.render_link_dialog(frame, form);
+            }
+            UIMode::Compress { form } => {
+                self.render_compress_dialog(frame, form);
+            }
+            UIMode::Extract { form } => {
+                self.render_extract_dialog(frame, form);
+            }
+            UIMode::Attributes { form } => {
+                self.render_attributes_dialog(frame, form);
+            }
+            UIMode::ConfirmBatchRename { renames, cycles } => {
+                self.render_confirm_batch_rename(frame, renames, *cycles);
+            }
+            UIMode::BulkRename { rename } => {
+                self.render_bulk_rename(frame, rename);
+            }
+            UIMode::DiffViewer { viewer } => {
+                self.render_diff_viewer(frame, viewer);
+            }
+            UIMode::Normal | UIMode::MarkPending { .. } | UIMode::CtrlXPrefix => {}
+        }
+    }
+
+    /// Split off a one-line tab bar above the pane if it has more than one tab.
+    /// Returns (tab bar area, remaining pane area).
+    fn split_tab_bar(&self, area: Rect, pane: Pane) -> (Option<Rect>, Rect) {
+        let tabs = match pane {
+            Pane::Left => &self.left_tabs,
+            Pane::Right => &self.right_tabs,
+        };
+        if tabs.len() <= 1 {
+            return (None, area);
+        }
+        let layout = Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).split(area);
+        (Some(layout[0]), layout[1])
+    }
+
+    fn render_tab_bar(&self, frame: &mut Frame, area: Rect, pane: Pane) {
+        let (tabs, live) = match pane {
+            Pane::Left => (&self.left_tabs, &self.left),
+            Pane::Right => (&self.right_tabs, &self.right),
+        };
+        let is_active_pane = self.active_pane == pane;
+
+        let mut spans: Vec<Span> = Vec::new();
+        for (i, label) in tabs.labels(live).into_iter().enumerate() {
+            let style = if i != tabs.active() {
+                Style::default().fg(THEME.help_key_fg).bg(THEME.help_key_bg)
+            } else if is_active_pane {
+                Style::default()
+                    .fg(THEME.cursor_active_fg)
+                    .bg(THEME.cursor_active_bg)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+                    .fg(THEME.cursor_inactive_fg)
+                    .bg(THEME.cursor_inactive_bg)
+                    .add_modifier(Modifier::BOLD)
+            };
+            spans.push(Span::styled(format!(" {} ", label), style));
+            spans.push(Span::raw(" "));
         }
+
+        let bar = Paragraph::new(Line::from(spans)).style(Style::default().bg(THEME.help_desc_bg));
+        frame.render_widget(bar, area);
     }
 
     fn render_pane(&mut self, frame: &mut Frame, area: Rect, pane: Pane) {
@@ -125,19 +292,40 @@ impl App {
             Style::default().fg(THEME.pane_inactive_border)
         };
 
+        if let Some(tree) = &mut pane_state.tree {
+            render_tree(frame, area, tree, is_active, border_style);
+            return;
+        }
+
         // Build title with loading/calculating indicators
         let mut title = format!(" {} ", pane_state.path.display());
+        if pane_state.panel.is_some() {
+            title.push_str("[Panelized] ");
+        }
+        if pane_state.archive.is_some() {
+            title.push_str("[Archive] ");
+        }
         if pane_state.is_loading() {
             title.push_str("[Loading...] ");
         } else if pane_state.is_calculating_sizes() {
             title.push_str("[Calculating...] ");
         }
 
-        let block = Block::default()
+        let mut block = Block::default()
             .title(title)
             .title_style(Style::default().fg(THEME.pane_title))
             .borders(Borders::ALL)
             .border_style(border_style);
+        if let Some(space) = pane_state.free_space {
+            block = block.title_bottom(
+                Line::from(format!(
+                    " {} free of {} ",
+                    format_size(space.available),
+                    format_size(space.total)
+                ))
+                .right_aligned(),
+            );
+        }
 
         // Calculate available width for size column
         let inner_width = area.width.saturating_sub(2) as usize; // -2 for borders
@@ -146,9 +334,8 @@ impl App {
         let items: Vec<ListItem> = pane_state
             .entries
             .iter()
-            .enumerate()
-            .map(|(i, entry)| {
-                let is_multi_selected = pane_state.selected.contains(&i);
+            .map(|entry| {
+                let is_multi_selected = pane_state.is_selected(entry);
                 let base_style = if entry.is_dir {
                     Style::default()
                         .fg(THEME.directory_fg)
@@ -171,6 +358,10 @@ impl App {
                 // Format size if available and mode is not None
                 let display = if size_mode != SizeDisplayMode::None {
                     let size_str = match entry.size {
+                        // A running total is marked until the walk finishes
+                        Some(size) if pane_state.partial_sizes.contains(&entry.path) => {
+                            format!("{}+", format_size(size))
+                        }
                         Some(size) => format_size(size),
                         // Only show "..." for directories in Full mode while calculating
                         None if entry.is_dir && size_mode == SizeDisplayMode::Full => {
@@ -224,24 +415,37 @@ impl App {
 
         let content = if let Some((msg, _)) = &self.error_message {
             format!("[Error] {}  ", msg)
-        } else if active_jobs > 0 {
-            // Calculate total throughput from all active jobs
-            let total_throughput: u64 = self
-                .job_manager
-                .all_jobs()
-                .iter()
-                .filter(|j| matches!(j.status, JobStatus::Running { .. } | JobStatus::Visible))
-                .map(|j| j.throughput.current_throughput())
-                .sum();
-
-            format!(
-                "[{} job{} running @ {}/s] Press J to view",
-                active_jobs,
-                if active_jobs == 1 { "" } else { "s" },
-                format_bytes(total_throughput)
-            )
         } else {
-            String::new()
+            let mut parts = Vec::new();
+
+            let (selected_count, selected_bytes) = self.active_pane().selection_summary();
+            if selected_count > 0 {
+                parts.push(format!(
+                    "[{} selected, {}]",
+                    selected_count,
+                    format_bytes(selected_bytes)
+                ));
+            }
+
+            if active_jobs > 0 {
+                // Calculate total throughput from all active jobs
+                let total_throughput: u64 = self
+                    .job_manager
+                    .all_jobs()
+                    .iter()
+                    .filter(|j| matches!(j.status, JobStatus::Running { .. } | JobStatus::Visible))
+                    .map(|j| j.throughput.current_throughput())
+                    .sum();
+
+                parts.push(format!(
+                    "[{} job{} running @ {}/s] Press J to view",
+                    active_jobs,
+                    if active_jobs == 1 { "" } else { "s" },
+                    format_bytes(total_throughput)
+                ));
+            }
+
+            parts.join(" ")
         };
 
         let style = if self.error_message.is_some() {
@@ -265,9 +469,23 @@ impl App {
             .bg(THEME.help_desc_bg);
         let sep_style = Style::default().bg(THEME.help_desc_bg);
 
+        let ctrl_x_shortcuts = [
+            ("Ctrl+X", "then"),
+            ("s", "Symlink"),
+            ("l", "Hard link"),
+            ("c", "Compress"),
+            ("x", "Extract"),
+            ("Ctrl+S", "Edit symlink"),
+            ("i", "Info panel"),
+            ("q", "Quick view"),
+            ("t", "Tree"),
+            ("m", "Mounts"),
+            ("u", "Disk usage"),
+        ];
         let shortcuts = [
             ("Ins", "Select"),
             ("*", "All"),
+            ("+/-", "Group"),
             ("F2", "Rename"),
             ("F3", "View"),
             ("F4", "Edit"),
@@ -281,6 +499,12 @@ impl App {
             ("q", "Quit"),
         ];
 
+        let shortcuts: &[(&str, &str)] = if matches!(self.ui_mode, UIMode::CtrlXPrefix) {
+            &ctrl_x_shortcuts
+        } else {
+            &shortcuts
+        };
+
         let mut spans: Vec<Span> = Vec::new();
         for (i, (key, desc)) in shortcuts.iter().enumerate() {
             if i > 0 {
@@ -703,6 +927,443 @@ impl App {
         render_yes_no_buttons(frame, content_layout[5]);
     }
 
+    fn render_properties_popup(&self, frame: &mut Frame) {
+        let Some(view) = &self.properties else {
+            return;
+        };
+        let area = centered_rect(70, 70, frame.area());
+        let inner = render_dialog_frame(frame, area, "Properties", THEME.dialog_border);
+
+        let layout = Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).split(inner);
+        frame.render_widget(
+            Paragraph::new(properties_lines(&view.rows())).wrap(Wrap { trim: false }),
+            layout[0],
+        );
+        let hint =
+            Paragraph::new("Esc/Enter to close").style(Style::default().fg(THEME.dialog_hint));
+        frame.render_widget(hint, layout[1]);
+    }
+
+    /// Properties of the active cursor entry in place of the inactive pane
+    fn render_info_panel(&self, frame: &mut Frame, area: Rect) {
+        let block = Block::default()
+            .title(" Info ")
+            .title_style(Style::default().fg(THEME.pane_title))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(THEME.pane_inactive_border));
+        let lines = self
+            .info
+            .as_ref()
+            .map(|info| properties_lines(&info.rows()))
+            .unwrap_or_default();
+        frame.render_widget(
+            Paragraph::new(lines)
+                .block(block)
+                .wrap(Wrap { trim: false }),
+            area,
+        );
+    }
+
+    /// Preview of the active cursor file in place of the inactive pane
+    fn render_quick_view(&self, frame: &mut Frame, area: Rect) {
+        let title = self
+            .quick_view
+            .as_ref()
+            .map(|view| {
+                let name = view.path.file_name().unwrap_or_default().to_string_lossy();
+                match &view.state {
+                    QuickViewState::Ready(viewer) => {
+                        format!(" {} - {} ", name, viewer.mode.label())
+                    }
+                    _ => format!(" {} ", name),
+                }
+            })
+            .unwrap_or_default();
+        let block = Block::default()
+            .title(title)
+            .title_style(Style::default().fg(THEME.pane_title))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(THEME.pane_inactive_border));
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let hint_style = Style::default().fg(THEME.dialog_hint);
+        let para = match self.quick_view.as_ref().map(|view| &view.state) {
+            Some(QuickViewState::Ready(viewer)) => match &viewer.error {
+                Some(error) => Paragraph::new(format!("Error: {}", error))
+                    .style(Style::default().fg(THEME.status_error_fg)),
+                None => {
+                    let lines: Vec<Line> = viewer
+                        .visible_lines(inner.height as usize)
+                        .iter()
+                        .map(|s| Line::raw(s.as_str()))
+                        .collect();
+                    let para = Paragraph::new(lines).style(Style::default().fg(THEME.file_fg));
+                    // Hex lines are laid out for their width; wrapping would scramble them
+                    if viewer.mode == ViewMode::Hex {
+                        para
+                    } else {
+                        para.wrap(Wrap { trim: false })
+                    }
+                }
+            },
+            Some(QuickViewState::Loading(_)) => Paragraph::new("Loading...").style(hint_style),
+            Some(QuickViewState::Unavailable(reason)) => Paragraph::new(*reason).style(hint_style),
+            None => Paragraph::new(""),
+        };
+        frame.render_widget(para, inner);
+    }
+
+    fn render_link_dialog(&self, frame: &mut Frame, form: &LinkForm) {
+        let area = centered_rect(70, 30, frame.area());
+        let inner = render_dialog_frame(frame, area, form.kind.title(), THEME.dialog_border);
+
+        let focused_style = Style::default()
+            .bg(THEME.cursor_active_bg)
+            .fg(THEME.cursor_active_fg);
+        let input_style = Style::default()
+            .fg(THEME.dialog_input_fg)
+            .bg(THEME.dialog_input_bg);
+        let label_style = |idx: usize| {
+            if idx == form.focus {
+                focused_style
+            } else {
+                Style::default()
+            }
+        };
+        let cursor = |idx: usize| if idx == form.focus { "█" } else { "" };
+
+        let mut lines = vec![
+            Line::raw(""),
+            Line::styled(
+                format!(" {}", form.kind.target_label()),
+                label_style(links::FOCUS_TARGET),
+            ),
+            Line::styled(
+                format!(" {}{}", form.target, cursor(links::FOCUS_TARGET)),
+                input_style,
+            ),
+            Line::raw(""),
+        ];
+        if form.kind == LinkKind::EditSymlink {
+            lines.push(Line::raw(" Link:"));
+            lines.push(Line::styled(
+                format!(" {}", form.link),
+                Style::default().fg(THEME.dialog_hint),
+            ));
+        } else {
+            lines.push(Line::styled(" Link:", label_style(links::FOCUS_LINK)));
+            lines.push(Line::styled(
+                format!(" {}{}", form.link, cursor(links::FOCUS_LINK)),
+                input_style,
+            ));
+        }
+        if form.kind != LinkKind::Hardlink {
+            lines.push(Line::raw(""));
+            lines.push(Line::styled(
+                format!(" {} Relative target", checkbox(form.relative)),
+                label_style(links::FOCUS_RELATIVE),
+            ));
+        }
+
+        let layout = Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).split(inner);
+        frame.render_widget(Paragraph::new(lines), layout[0]);
+        let hint = if form.kind == LinkKind::Hardlink {
+            "Tab: field | Enter: create | Esc: cancel"
+        } else {
+            "Tab: field | Ctrl+R: relative/absolute | Enter: confirm | Esc: cancel"
+        };
+        let hint = Paragraph::new(hint).style(Style::default().fg(THEME.dialog_hint));
+        frame.render_widget(hint, layout[1]);
+    }
+
+    fn render_compress_dialog(&self, frame: &mut Frame, form: &CompressForm) {
+        let area = centered_rect(70, 30, frame.area());
+        let title = match form.sources.as_slice() {
+            [single] => format!(
+                "Compress: {}",
+                single.file_name().unwrap_or_default().to_string_lossy()
+            ),
+            sources => format!("Compress: {} items", sources.len()),
+        };
+        let inner = render_dialog_frame(frame, area, &title, THEME.dialog_border);
+
+        let focused_style = Style::default()
+            .bg(THEME.cursor_active_bg)
+            .fg(THEME.cursor_active_fg);
+        let input_style = Style::default()
+            .fg(THEME.dialog_input_fg)
+            .bg(THEME.dialog_input_bg);
+        let label_style = |idx: usize| {
+            if idx == form.focus {
+                focused_style
+            } else {
+                Style::default()
+            }
+        };
+        let cursor = if form.focus == compress::FOCUS_NAME {
+            "█"
+        } else {
+            ""
+        };
+
+        let formats: Vec<Span> = CompressFormat::ALL
+            .iter()
+            .flat_map(|&format| {
+                let style = if format == form.format {
+                    input_style
+                } else {
+                    Style::default().fg(THEME.dialog_hint)
+                };
+                [
+                    Span::raw(" "),
+                    Span::styled(format!(" {} ", format.label()), style),
+                ]
+            })
+            .collect();
+        let level = match form.format.levels() {
+            Some((low, high, _)) => format!(" Level: ◂ {} ▸  ({}-{})", form.level, low, high),
+            None => " Level: none".to_string(),
+        };
+
+        let lines = vec![
+            Line::raw(""),
+            Line::styled(" Archive:", label_style(compress::FOCUS_NAME)),
+            Line::styled(format!(" {}{}", form.name, cursor), input_style),
+            Line::raw(""),
+            Line::from(
+                std::iter::once(Span::styled(
+                    " Format:",
+                    label_style(compress::FOCUS_FORMAT),
+                ))
+                .chain(formats)
+                .collect::<Vec<_>>(),
+            ),
+            Line::raw(""),
+            Line::styled(level, label_style(compress::FOCUS_LEVEL)),
+        ];
+
+        let layout = Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).split(inner);
+        frame.render_widget(Paragraph::new(lines), layout[0]);
+        let hint = Paragraph::new("Tab: field | ←/→: format, level | Enter: create | Esc: cancel")
+            .style(Style::default().fg(THEME.dialog_hint));
+        frame.render_widget(hint, layout[1]);
+    }
+
+    fn render_extract_dialog(&self, frame: &mut Frame, form: &ExtractForm) {
+        let area = centered_rect(70, 30, frame.area());
+        let title = format!(
+            "Extract: {}",
+            form.archive
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+        );
+        let inner = render_dialog_frame(frame, area, &title, THEME.dialog_border);
+
+        let focused_style = Style::default()
+            .bg(THEME.cursor_active_bg)
+            .fg(THEME.cursor_active_fg);
+        let input_style = Style::default()
+            .fg(THEME.dialog_input_fg)
+            .bg(THEME.dialog_input_bg);
+        let label_style = |idx: usize| {
+            if idx == form.focus {
+                focused_style
+            } else {
+                Style::default()
+            }
+        };
+        let cursor = if form.focus == extract::FOCUS_DEST {
+            "█"
+        } else {
+            ""
+        };
+
+        let lines = vec![
+            Line::raw(""),
+            Line::styled(" To directory:", label_style(extract::FOCUS_DEST)),
+            Line::styled(format!(" {}{}", form.dest, cursor), input_style),
+            Line::raw(""),
+            Line::styled(
+                format!(
+                    " {} Into subdirectory {}/",
+                    checkbox(form.subdir),
+                    form.subdir_name()
+                ),
+                label_style(extract::FOCUS_SUBDIR),
+            ),
+        ];
+
+        let layout = Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).split(inner);
+        frame.render_widget(Paragraph::new(lines), layout[0]);
+        let hint = Paragraph::new("Tab: field | Space: toggle | Enter: extract | Esc: cancel")
+            .style(Style::default().fg(THEME.dialog_hint));
+        frame.render_widget(hint, layout[1]);
+    }
+
+    fn render_attributes_dialog(&self, frame: &mut Frame, form: &AttrForm) {
+        let area = centered_rect(60, 70, frame.area());
+        let title = match form.targets.as_slice() {
+            [single] => format!(
+                "Attributes: {}",
+                single.file_name().unwrap_or_default().to_string_lossy()
+            ),
+            targets => format!("Attributes: {} items", targets.len()),
+        };
+        let inner = render_dialog_frame(frame, area, &title, THEME.dialog_border);
+
+        let focused_style = Style::default()
+            .bg(THEME.cursor_active_bg)
+            .fg(THEME.cursor_active_fg);
+        let style_for = |idx: usize| {
+            if idx == form.focus {
+                focused_style
+            } else {
+                Style::default()
+            }
+        };
+        let input_style = Style::default()
+            .fg(THEME.dialog_input_fg)
+            .bg(THEME.dialog_input_bg);
+        let text_field = |idx: usize, label: &str, value: &str| {
+            let cursor = if idx == form.focus { "█" } else { "" };
+            Line::from(vec![
+                Span::raw("  "),
+                Span::styled(format!("{:<7}", label), style_for(idx)),
+                Span::styled(format!("{}{}", value, cursor), input_style),
+            ])
+        };
+        let check = |idx: usize, label: &str| {
+            Span::styled(
+                format!("{} {}", checkbox(form.checked(idx)), label),
+                style_for(idx),
+            )
+        };
+        let bit = |i: usize| check(attributes::FOCUS_FIRST_BIT + i, "");
+
+        let mut lines = vec![
+            Line::raw(""),
+            text_field(attributes::FOCUS_OCTAL, "Octal:", &form.octal),
+            Line::raw(""),
+            Line::styled(
+                "           Read  Write  Exec",
+                Style::default().fg(THEME.dialog_hint),
+            ),
+        ];
+        for (row, label) in ["Owner", "Group", "Other"].iter().enumerate() {
+            let mut spans = vec![Span::raw(format!("  {:<8} ", label))];
+            for col in 0..3 {
+                spans.push(bit(row * 3 + col));
+                spans.push(Span::raw("  "));
+            }
+            lines.push(Line::from(spans));
+        }
+        lines.push(Line::from(vec![
+            Span::raw("  "),
+            check(attributes::FOCUS_FIRST_BIT + 9, "Set UID"),
+            Span::raw("   "),
+            check(attributes::FOCUS_FIRST_BIT + 10, "Set GID"),
+            Span::raw("   "),
+            check(attributes::FOCUS_FIRST_BIT + 11, "Sticky"),
+        ]));
+        lines.push(Line::styled(
+            format!("  Mode: {}", attributes::mode_string(form.mode)),
+            Style::default().fg(THEME.dialog_hint),
+        ));
+        lines.push(Line::raw(""));
+        lines.push(text_field(attributes::FOCUS_OWNER, "Owner:", &form.owner));
+        lines.push(text_field(attributes::FOCUS_GROUP, "Group:", &form.group));
+        if form.has_dirs {
+            lines.push(Line::raw(""));
+            lines.push(Line::from(vec![
+                Span::raw("  "),
+                check(attributes::FOCUS_RECURSIVE, "Recursive:"),
+                Span::raw("  "),
+                check(attributes::FOCUS_FILES, "Files"),
+                Span::raw("  "),
+                check(attributes::FOCUS_DIRS, "Directories"),
+            ]));
+        }
+        if form.targets.len() > 1 {
+            lines.push(Line::raw(""));
+            lines.push(Line::styled(
+                "  Showing the first item; only edited values are applied",
+                Style::default().fg(THEME.dialog_hint),
+            ));
+        }
+
+        let layout = Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).split(inner);
+        frame.render_widget(Paragraph::new(lines), layout[0]);
+        let hint = Paragraph::new("Tab/arrows: field | Space: toggle | Enter: apply | Esc: cancel")
+            .style(Style::default().fg(THEME.dialog_hint));
+        frame.render_widget(hint, layout[1]);
+    }
+
+    fn render_confirm_batch_rename(
+        &self,
+        frame: &mut Frame,
+        renames: &[(PathBuf, PathBuf)],
+        cycles: usize,
+    ) {
+        let area = centered_rect(60, 50, frame.area());
+        let inner = render_dialog_frame(frame, area, "Confirm Rename", THEME.dialog_border);
+
+        let layout = Layout::vertical([
+            Constraint::Length(1), // spacer
+            Constraint::Length(1), // summary
+            Constraint::Length(1), // spacer
+            Constraint::Min(1),    // renames
+            Constraint::Length(1), // cycle note
+            Constraint::Length(1), // spacer
+            Constraint::Length(1), // buttons
+            Constraint::Length(1), // spacer
+        ])
+        .split(inner);
+
+        let summary = Paragraph::new(format!("Rename {} entries?", renames.len()))
+            .alignment(ratatui::layout::Alignment::Center);
+        frame.render_widget(summary, layout[1]);
+
+        let name = |p: &Path| {
+            p.file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned()
+        };
+        let shown = layout[3].height as usize;
+        let mut lines: Vec<Line> = renames
+            .iter()
+            .take(shown)
+            .map(|(from, to)| {
+                Line::from(vec![
+                    Span::raw(format!("  {} → ", name(from))),
+                    Span::styled(name(to), Style::default().fg(THEME.diff_added)),
+                ])
+            })
+            .collect();
+        if renames.len() > shown {
+            lines.pop();
+            lines.push(Line::raw(format!(
+                "  ... and {} more",
+                renames.len() - shown + 1
+            )));
+        }
+        frame.render_widget(Paragraph::new(lines), layout[3]);
+
+        if cycles > 0 {
+            let note = Paragraph::new(format!(
+                "Includes {} swap(s)/cycle(s), resolved through temporary names",
+                cycles
+            ))
+            .style(Style::default().fg(THEME.dialog_warning_text))
+            .alignment(ratatui::layout::Alignment::Center);
+            frame.render_widget(note, layout[4]);
+        }
+
+        render_yes_no_buttons(frame, layout[6]);
+    }
+
     fn render_mkdir_dialog(&self, frame: &mut Frame, input: &str) {
         let area = centered_rect(50, 20, frame.area());
         let inner = render_dialog_frame(frame, area, "Create Directory", THEME.dialog_border);
@@ -808,70 +1469,898 @@ impl App {
         frame.render_widget(hint, layout[4]);
     }
 
-    fn render_command_line(&self, frame: &mut Frame, input: &str) {
-        // Render at the very bottom of the screen
-        let area = Rect {
-            x: 0,
-            y: frame.area().height.saturating_sub(1),
-            width: frame.area().width,
-            height: 1,
+    fn render_select_group_dialog(
+        &self,
+        frame: &mut Frame,
+        input: &str,
+        select: bool,
+        files_only: bool,
+        case_sensitive: bool,
+    ) {
+        let area = centered_rect(50, 25, frame.area());
+        let title = if select {
+            "Select Group"
+        } else {
+            "Unselect Group"
         };
+        let inner = render_dialog_frame(frame, area, title, THEME.dialog_border);
 
-        frame.render_widget(Clear, area);
+        let layout = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(1), // label
+            Constraint::Length(1), // input
+            Constraint::Length(1),
+            Constraint::Length(1), // options
+            Constraint::Length(1),
+            Constraint::Length(1), // hint
+            Constraint::Min(0),
+        ])
+        .split(inner);
 
-        let pane_path = &self.active_pane().path;
+        let label = Paragraph::new("Pattern (*, ?, [a-z]):");
+        frame.render_widget(label, layout[1]);
 
-        let prompt = format!("{}$ {}█", pane_path.display(), input);
-        let line = Paragraph::new(prompt).style(
+        let input_display = format!("{}█", input);
+        let input_para = Paragraph::new(input_display).style(
             Style::default()
                 .fg(THEME.dialog_input_fg)
                 .bg(THEME.dialog_input_bg),
         );
-        frame.render_widget(line, area);
-    }
-
-    fn render_search_bar(&self, frame: &mut Frame, query: &str) {
-        // Render at the very bottom of the screen
-        let area = Rect {
-            x: 0,
-            y: frame.area().height.saturating_sub(1),
-            width: frame.area().width,
-            height: 1,
-        };
-
-        frame.render_widget(Clear, area);
+        frame.render_widget(input_para, layout[2]);
 
-        let prompt = format!("Search: {}█  (Ctrl+S: next, Esc: cancel)", query);
-        let line = Paragraph::new(prompt).style(
-            Style::default()
-                .fg(THEME.dialog_input_fg)
-                .bg(THEME.dialog_input_bg),
+        let options = format!(
+            "{} Files only (^F)   {} Case sensitive (^T)",
+            checkbox(files_only),
+            checkbox(case_sensitive)
         );
-        frame.render_widget(line, area);
-    }
+        frame.render_widget(Paragraph::new(options), layout[4]);
 
-    fn render_quit_dialog(&self, frame: &mut Frame) {
-        let area = centered_rect(40, 25, frame.area());
-        let inner = render_dialog_frame(frame, area, "Quit", THEME.dialog_warning_border);
+        let hint = Paragraph::new("Enter to confirm, Esc to cancel")
+            .style(Style::default().fg(THEME.dialog_hint));
+        frame.render_widget(hint, layout[6]);
+    }
 
-        let active_jobs = self.job_manager.active_job_count();
+    fn render_hotlist(&self, frame: &mut Frame, selected: usize, rename: Option<&str>) {
+        let area = centered_rect(60, 60, frame.area());
+        let inner = render_dialog_frame(frame, area, "Directory Hotlist", THEME.dialog_border);
 
         let layout = Layout::vertical([
-            Constraint::Length(1), // spacer
-            Constraint::Length(1), // job count
-            Constraint::Length(1), // question
-            Constraint::Length(1), // spacer
-            Constraint::Length(1), // buttons
-            Constraint::Min(0),
+            Constraint::Min(1),    // bookmarks
+            Constraint::Length(1), // rename input
+            Constraint::Length(1), // hint
         ])
         .split(inner);
 
-        let msg = format!(
-            "{} job{} still running.",
-            active_jobs,
-            if active_jobs == 1 { " is" } else { "s are" }
-        );
-        let warning = Paragraph::new(msg)
+        let items = &self.bookmarks.items;
+        if items.is_empty() {
+            let msg = Paragraph::new("No bookmarks - press 'a' to add the current directory")
+                .style(Style::default().fg(THEME.dialog_hint));
+            frame.render_widget(msg, layout[0]);
+        } else {
+            let name_width = items
+                .iter()
+                .map(|b| b.name.chars().count())
+                .max()
+                .unwrap_or(0)
+                .min(30);
+            let list_items: Vec<ListItem> = items
+                .iter()
+                .map(|b| {
+                    let mark = b.mark.map(|m| format!("'{}", m)).unwrap_or_default();
+                    ListItem::new(format!(
+                        "{:<2} {:<width$}  {}",
+                        mark,
+                        b.name,
+                        b.path.display(),
+                        width = name_width
+                    ))
+                    .style(Style::default().fg(THEME.directory_fg))
+                })
+                .collect();
+
+            let list = List::new(list_items)
+                .highlight_style(
+                    Style::default()
+                        .bg(THEME.cursor_active_bg)
+                        .fg(THEME.cursor_active_fg),
+                )
+                .highlight_symbol("▶ ");
+            let mut state = ListState::default().with_selected(Some(selected));
+            frame.render_stateful_widget(list, layout[0], &mut state);
+        }
+
+        if let Some(input) = rename {
+            let input_para = Paragraph::new(format!("Name: {}█", input)).style(
+                Style::default()
+                    .fg(THEME.dialog_input_fg)
+                    .bg(THEME.dialog_input_bg),
+            );
+            frame.render_widget(input_para, layout[1]);
+        }
+
+        let hint = if rename.is_some() {
+            "Enter: save name | Esc: cancel"
+        } else {
+            "Enter: go | a: add current | r: rename | d: delete | J/K: move | Esc: close"
+        };
+        let hint = Paragraph::new(hint).style(Style::default().fg(THEME.dialog_hint));
+        frame.render_widget(hint, layout[2]);
+    }
+
+    fn render_dir_history(&self, frame: &mut Frame, selected: usize) {
+        let area = centered_rect(60, 60, frame.area());
+        let inner = render_dialog_frame(frame, area, "Directory History", THEME.dialog_border);
+
+        let layout = Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).split(inner);
+
+        let current = &self.active_pane().path;
+        let items: Vec<ListItem> = self
+            .active_pane()
+            .history
+            .recent()
+            .iter()
+            .map(|path| {
+                let marker = if path == current { "● " } else { "  " };
+                ListItem::new(format!("{}{}", marker, path.display()))
+                    .style(Style::default().fg(THEME.directory_fg))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .highlight_style(
+                Style::default()
+                    .bg(THEME.cursor_active_bg)
+                    .fg(THEME.cursor_active_fg),
+            )
+            .highlight_symbol("▶ ");
+        let mut state = ListState::default().with_selected(Some(selected));
+        frame.render_stateful_widget(list, layout[0], &mut state);
+
+        let hint = Paragraph::new("Enter: go | Alt+←/→: back/forward | Esc: close")
+            .style(Style::default().fg(THEME.dialog_hint));
+        frame.render_widget(hint, layout[1]);
+    }
+
+    fn render_mounts(
+        &self,
+        frame: &mut Frame,
+        mounts: &[(Mount, Option<DiskSpace>)],
+        selected: usize,
+        show_all: bool,
+    ) {
+        let area = centered_rect(80, 60, frame.area());
+        let title = if show_all {
+            "Filesystems (all)"
+        } else {
+            "Filesystems"
+        };
+        let inner = render_dialog_frame(frame, area, title, THEME.dialog_border);
+
+        let layout = Layout::vertical([
+            Constraint::Length(1), // header
+            Constraint::Min(1),    // mounts
+            Constraint::Length(1), // hint
+        ])
+        .split(inner);
+
+        // Mount point takes whatever the fixed columns leave over
+        let point_width = (inner.width as usize).saturating_sub(2 + 16 + 8 + 4 * 9);
+        let row = |point: &str, device: &str, fs_type: &str, figures: [String; 4]| {
+            let [size, used, free, percent] = figures;
+            format!(
+                "{:<pw$.pw$}{:<16.15}{:<8.7}{:>9}{:>9}{:>9}{:>9}",
+                point,
+                device,
+                fs_type,
+                size,
+                used,
+                free,
+                percent,
+                pw = point_width
+            )
+        };
+
+        let header = row(
+            "Mount point",
+            "Device",
+            "Type",
+            ["Size", "Used", "Free", "Use%"].map(str::to_string),
+        );
+        frame.render_widget(
+            Paragraph::new(format!("  {header}")).style(Style::default().fg(THEME.dialog_hint)),
+            layout[0],
+        );
+
+        let current = &self.active_pane().path;
+        let items: Vec<ListItem> = mounts
+            .iter()
+            .map(|(mount, space)| {
+                let figures = match space {
+                    Some(space) if space.total > 0 => [
+                        format_size(space.total),
+                        format_size(space.used()),
+                        format_size(space.available),
+                        format!("{}%", space.used() * 100 / space.total),
+                    ],
+                    _ => ["-", "-", "-", "-"].map(str::to_string),
+                };
+                let style = if current.starts_with(&mount.mount_point) {
+                    Style::default().fg(THEME.directory_fg)
+                } else {
+                    Style::default().fg(THEME.file_fg)
+                };
+                let text = row(
+                    &mount.mount_point.display().to_string(),
+                    &mount.device,
+                    &mount.fs_type,
+                    figures,
+                );
+                ListItem::new(text).style(style)
+            })
+            .collect();
+
+        let list = List::new(items)
+            .highlight_style(
+                Style::default()
+                    .bg(THEME.cursor_active_bg)
+                    .fg(THEME.cursor_active_fg),
+            )
+            .highlight_symbol("▶ ");
+        let mut state = ListState::default().with_selected(Some(selected));
+        frame.render_stateful_widget(list, layout[1], &mut state);
+
+        let hint = if show_all {
+            "Enter: go | a: hide pseudo filesystems | Esc: close"
+        } else {
+            "Enter: go | a: show all filesystems | Esc: close"
+        };
+        let hint = Paragraph::new(hint).style(Style::default().fg(THEME.dialog_hint));
+        frame.render_widget(hint, layout[2]);
+    }
+
+    fn render_frecency_jump(
+        &self,
+        frame: &mut Frame,
+        query: &str,
+        candidates: &[PathBuf],
+        selected: usize,
+    ) {
+        let area = centered_rect(60, 60, frame.area());
+        let inner = render_dialog_frame(frame, area, "Jump to Directory", THEME.dialog_border);
+
+        let layout = Layout::vertical([
+            Constraint::Length(1), // query
+            Constraint::Min(1),    // matches
+            Constraint::Length(1), // hint
+        ])
+        .split(inner);
+
+        let input_para = Paragraph::new(format!("> {}█", query)).style(
+            Style::default()
+                .fg(THEME.dialog_input_fg)
+                .bg(THEME.dialog_input_bg),
+        );
+        frame.render_widget(input_para, layout[0]);
+
+        let matches = filter_jump_candidates(candidates, query);
+        if matches.is_empty() {
+            let msg = if candidates.is_empty() {
+                "No visited directories yet"
+            } else {
+                "No matches"
+            };
+            let msg = Paragraph::new(msg).style(Style::default().fg(THEME.dialog_hint));
+            frame.render_widget(msg, layout[1]);
+        } else {
+            let items: Vec<ListItem> = matches
+                .iter()
+                .map(|path| {
+                    ListItem::new(path.display().to_string())
+                        .style(Style::default().fg(THEME.directory_fg))
+                })
+                .collect();
+
+            let list = List::new(items)
+                .highlight_style(
+                    Style::default()
+                        .bg(THEME.cursor_active_bg)
+                        .fg(THEME.cursor_active_fg),
+                )
+                .highlight_symbol("▶ ");
+            let mut state = ListState::default().with_selected(Some(selected));
+            frame.render_stateful_widget(list, layout[1], &mut state);
+        }
+
+        let hint = Paragraph::new("Type to filter | ↑/↓: select | Enter: go | Esc: close")
+            .style(Style::default().fg(THEME.dialog_hint));
+        frame.render_widget(hint, layout[2]);
+    }
+
+    fn render_find_dialog(&self, frame: &mut Frame, form: &FindForm) {
+        let texts: Vec<&str> = (0..find::TEXT_FIELD_COUNT).map(|i| form.text(i)).collect();
+        let options: Vec<String> = (find::TEXT_FIELD_COUNT..find::FIELD_LABELS.len())
+            .map(|i| format!("{} {}", checkbox(form.checked(i)), find::FIELD_LABELS[i]))
+            .collect();
+        render_search_form(
+            frame,
+            "Find File",
+            &find::FIELD_LABELS,
+            form.focus,
+            &texts,
+            &options,
+        );
+    }
+
+    fn render_bulk_rename(&self, frame: &mut Frame, rename: &BulkRename) {
+        let area = centered_rect(80, 85, frame.area());
+        let inner = render_dialog_frame(frame, area, "Bulk Rename", THEME.dialog_border);
+
+        let form = &rename.form;
+        let texts: Vec<&str> = (0..bulk_rename::TEXT_FIELD_COUNT)
+            .map(|i| form.text(i))
+            .collect();
+        let options = vec![
+            format!("{} {}", checkbox(form.regex), bulk_rename::FIELD_LABELS[7]),
+            format!(
+                "{} {}",
+                checkbox(form.case_sensitive),
+                bulk_rename::FIELD_LABELS[8]
+            ),
+            format!("{}: {}", bulk_rename::FIELD_LABELS[9], form.case.label()),
+        ];
+        let field_count = texts.len() + options.len();
+        let layout = Layout::vertical([
+            Constraint::Length(field_count as u16 + 2),
+            Constraint::Length(1), // Preview header
+            Constraint::Min(0),    // Preview rows
+            Constraint::Length(1), // Hint
+        ])
+        .split(inner);
+        render_form_fields(
+            frame,
+            layout[0],
+            &bulk_rename::FIELD_LABELS,
+            form.focus,
+            &texts,
+            &options,
+        );
+
+        let planned = match &rename.preview {
+            Ok(planned) => planned,
+            Err(msg) => {
+                let error =
+                    Paragraph::new(msg.as_str()).style(Style::default().fg(THEME.status_error_fg));
+                frame.render_widget(error, layout[1]);
+                return;
+            }
+        };
+
+        let (renames, conflicts) = rename.summary();
+        let header = format!(
+            "Preview: {} of {} renamed, {} conflicts",
+            renames,
+            planned.len(),
+            conflicts
+        );
+        let header_style = if conflicts > 0 {
+            Style::default().fg(THEME.status_error_fg)
+        } else {
+            Style::default().fg(THEME.pane_title)
+        };
+        frame.render_widget(Paragraph::new(header).style(header_style), layout[1]);
+
+        let old_width = planned
+            .iter()
+            .map(|p| p.old_name.chars().count())
+            .max()
+            .unwrap_or(0)
+            .min(inner.width as usize / 2);
+        let height = layout[2].height as usize;
+        let lines: Vec<Line> = planned
+            .iter()
+            .skip(rename.scroll)
+            .take(height)
+            .map(|p| {
+                let style = match p.status {
+                    RenameStatus::Unchanged => Style::default().fg(THEME.dialog_hint),
+                    RenameStatus::Rename => Style::default().fg(THEME.diff_added),
+                    _ => Style::default().fg(THEME.status_error_fg),
+                };
+                let old: String = p.old_name.chars().take(old_width).collect();
+                Line::from(vec![
+                    Span::raw(format!("{:<width$} → ", old, width = old_width)),
+                    Span::styled(p.new_name.as_str(), style),
+                    Span::styled(format!("  {}", p.status.label()), style),
+                ])
+            })
+            .collect();
+        frame.render_widget(Paragraph::new(lines), layout[2]);
+
+        let hint = Paragraph::new(
+            "Tab/↑/↓: field | Space: toggle | PgUp/PgDn: scroll | Enter: rename | Esc: cancel",
+        )
+        .style(Style::default().fg(THEME.dialog_hint));
+        frame.render_widget(hint, layout[3]);
+    }
+
+    fn render_grep_dialog(&self, frame: &mut Frame, form: &GrepForm) {
+        let texts: Vec<&str> = (0..grep::TEXT_FIELD_COUNT).map(|i| form.text(i)).collect();
+        let options: Vec<String> = (grep::TEXT_FIELD_COUNT..grep::FIELD_LABELS.len())
+            .map(|i| {
+                if i == grep::TEXT_FIELD_COUNT {
+                    format!("{}: {}", grep::FIELD_LABELS[i], form.case.label())
+                } else {
+                    format!("{} {}", checkbox(form.checked(i)), grep::FIELD_LABELS[i])
+                }
+            })
+            .collect();
+        render_search_form(
+            frame,
+            "Search File Contents",
+            &grep::FIELD_LABELS,
+            form.focus,
+            &texts,
+            &options,
+        );
+    }
+
+    fn render_grep_results(&self, frame: &mut Frame, selected: usize) {
+        let Some(grep) = &self.grep else {
+            return;
+        };
+
+        let area = centered_rect(90, 80, frame.area());
+        let status = if grep.running {
+            "searching"
+        } else if grep.hit_limit() {
+            "stopped at limit"
+        } else {
+            "done"
+        };
+        let title = format!(
+            "Grep: {} matches, {} files searched ({})",
+            grep.matches.len(),
+            grep.files_searched,
+            status
+        );
+        let inner = render_dialog_frame(frame, area, &title, THEME.dialog_border);
+
+        let layout = Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).split(inner);
+
+        if grep.matches.is_empty() {
+            let msg = if grep.running {
+                "Searching..."
+            } else {
+                "No matches"
+            };
+            let msg = Paragraph::new(msg).style(Style::default().fg(THEME.dialog_hint));
+            frame.render_widget(msg, layout[0]);
+        } else {
+            // Only build items for the visible page; result lists can be huge
+            let page = (layout[0].height as usize).max(1);
+            let start = selected / page * page;
+            let items: Vec<ListItem> = grep
+                .matches
+                .iter()
+                .skip(start)
+                .take(page)
+                .map(|m| {
+                    let name = m.path.strip_prefix(&grep.start_dir).unwrap_or(&m.path);
+                    ListItem::new(Line::from(vec![
+                        Span::styled(
+                            format!("{}:{}: ", name.display(), m.line),
+                            Style::default().fg(THEME.directory_fg),
+                        ),
+                        Span::styled(m.text.as_str(), Style::default().fg(THEME.file_fg)),
+                    ]))
+                })
+                .collect();
+
+            let list = List::new(items)
+                .highlight_style(
+                    Style::default()
+                        .bg(THEME.cursor_active_bg)
+                        .fg(THEME.cursor_active_fg),
+                )
+                .highlight_symbol("▶ ");
+            let mut state = ListState::default().with_selected(Some(selected - start));
+            frame.render_stateful_widget(list, layout[0], &mut state);
+        }
+
+        let hint = Paragraph::new(
+            "Enter/F3: view at line | o: go to file | s: stop | n: new search | Esc: close",
+        )
+        .style(Style::default().fg(THEME.dialog_hint));
+        frame.render_widget(hint, layout[1]);
+    }
+
+    fn render_find_results(&self, frame: &mut Frame, selected: usize) {
+        let Some(find) = &self.find else {
+            return;
+        };
+
+        let area = centered_rect(80, 80, frame.area());
+        let status = if find.running { "searching" } else { "done" };
+        let title = format!(
+            "Find: {} found, {} scanned ({})",
+            find.results.len(),
+            find.scanned,
+            status
+        );
+        let inner = render_dialog_frame(frame, area, &title, THEME.dialog_border);
+
+        let layout = Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).split(inner);
+
+        if find.results.is_empty() {
+            let msg = if find.running {
+                "Searching..."
+            } else {
+                "No files found"
+            };
+            let msg = Paragraph::new(msg).style(Style::default().fg(THEME.dialog_hint));
+            frame.render_widget(msg, layout[0]);
+        } else {
+            // Only build items for the visible page; result lists can be huge
+            let page = (layout[0].height as usize).max(1);
+            let start = selected / page * page;
+            let items: Vec<ListItem> = find
+                .results
+                .iter()
+                .skip(start)
+                .take(page)
+                .map(|path| {
+                    let name = path.strip_prefix(&find.start_dir).unwrap_or(path);
+                    ListItem::new(name.display().to_string())
+                        .style(Style::default().fg(THEME.file_fg))
+                })
+                .collect();
+
+            let list = List::new(items)
+                .highlight_style(
+                    Style::default()
+                        .bg(THEME.cursor_active_bg)
+                        .fg(THEME.cursor_active_fg),
+                )
+                .highlight_symbol("▶ ");
+            let mut state = ListState::default().with_selected(Some(selected - start));
+            frame.render_stateful_widget(list, layout[0], &mut state);
+        }
+
+        let hint = Paragraph::new(
+            "Enter: go to | F3/v: view | p: panelize | s: stop | n: new search | Esc: close",
+        )
+        .style(Style::default().fg(THEME.dialog_hint));
+        frame.render_widget(hint, layout[1]);
+    }
+
+    fn render_disk_usage(&self, frame: &mut Frame) {
+        let Some(du) = &self.disk_usage else {
+            return;
+        };
+
+        let area = centered_rect(90, 85, frame.area());
+        let kind = if du.allocated {
+            "allocated"
+        } else {
+            "apparent"
+        };
+        let title = format!(
+            "Disk Usage: {} ({} size)",
+            du.current_path().display(),
+            kind
+        );
+        let inner = render_dialog_frame(frame, area, &title, THEME.dialog_border);
+
+        let layout = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(1),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+        let Some(dir) = du.current() else {
+            let msg = format!("Scanning... {} entries", du.scanned_count());
+            let msg = Paragraph::new(msg).style(Style::default().fg(THEME.dialog_hint));
+            frame.render_widget(msg, layout[1]);
+            return;
+        };
+
+        let total = dir.size(du.allocated);
+        let summary = format!(
+            " Total {} in {} files{}",
+            format_bytes(total),
+            dir.files,
+            if dir.error {
+                " (some entries unreadable)"
+            } else {
+                ""
+            }
+        );
+        frame.render_widget(
+            Paragraph::new(summary).style(Style::default().fg(THEME.dialog_hint)),
+            layout[0],
+        );
+
+        if dir.children.is_empty() {
+            let msg =
+                Paragraph::new("Empty directory").style(Style::default().fg(THEME.dialog_hint));
+            frame.render_widget(msg, layout[1]);
+        } else {
+            // Bars are relative to the largest entry, as in ncdu
+            let largest = dir.children[0].size(du.allocated).max(1);
+            let page = (layout[1].height as usize).max(1);
+            let start = du.selected / page * page;
+            let items: Vec<ListItem> = dir
+                .children
+                .iter()
+                .skip(start)
+                .take(page)
+                .map(|node| {
+                    let size = node.size(du.allocated);
+                    let filled = (size as u128 * DU_BAR_WIDTH as u128 / largest as u128) as usize;
+                    let percent = size as f64 * 100.0 / total.max(1) as f64;
+                    let name = if node.is_dir {
+                        format!("{}/", node.name)
+                    } else {
+                        node.name.clone()
+                    };
+                    let files = if node.is_dir {
+                        format!("  ({} files)", node.files)
+                    } else {
+                        String::new()
+                    };
+                    let text = format!(
+                        "{}{:>8} {:>5.1}% [{:<bar$}] {}{}",
+                        if node.error { "!" } else { " " },
+                        format_size(size),
+                        percent,
+                        "#".repeat(filled),
+                        name,
+                        files,
+                        bar = DU_BAR_WIDTH
+                    );
+                    let style = if node.is_dir {
+                        Style::default()
+                            .fg(THEME.directory_fg)
+                            .add_modifier(Modifier::BOLD)
+                    } else {
+                        Style::default().fg(THEME.file_fg)
+                    };
+                    ListItem::new(text).style(style)
+                })
+                .collect();
+
+            let list = List::new(items)
+                .highlight_style(
+                    Style::default()
+                        .bg(THEME.cursor_active_bg)
+                        .fg(THEME.cursor_active_fg),
+                )
+                .highlight_symbol("▶ ");
+            let mut state = ListState::default().with_selected(Some(du.selected - start));
+            frame.render_stateful_widget(list, layout[1], &mut state);
+        }
+
+        let hint = Paragraph::new(
+            "Enter/→: open | ←: back | a: apparent/allocated | d: delete | r: rescan | Esc: close",
+        )
+        .style(Style::default().fg(THEME.dialog_hint));
+        frame.render_widget(hint, layout[2]);
+    }
+
+    fn render_compare_dialog(&self, frame: &mut Frame, method: CompareMethod, recursive: bool) {
+        let area = centered_rect(50, 25, frame.area());
+        let inner = render_dialog_frame(frame, area, "Compare Directories", THEME.dialog_border);
+
+        let layout = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(1), // method
+            Constraint::Length(1), // recursive
+            Constraint::Length(1),
+            Constraint::Length(1), // hint
+            Constraint::Min(0),
+        ])
+        .split(inner);
+
+        let method_line = format!("Compare by: {} (m)", method.label());
+        frame.render_widget(Paragraph::new(method_line), layout[1]);
+        let recursive_line = format!("{} Recursive (r)", checkbox(recursive));
+        frame.render_widget(Paragraph::new(recursive_line), layout[2]);
+
+        let hint = Paragraph::new("Enter to compare, Esc to cancel")
+            .style(Style::default().fg(THEME.dialog_hint));
+        frame.render_widget(hint, layout[4]);
+    }
+
+    fn render_compare_results(&self, frame: &mut Frame, selected: usize) {
+        let Some(run) = &self.compare else {
+            return;
+        };
+
+        let area = centered_rect(70, 70, frame.area());
+        let title = match &run.differences {
+            None => "Comparing...".to_owned(),
+            Some(d) => format!("Compare: {} differences", d.len()),
+        };
+        let inner = render_dialog_frame(frame, area, &title, THEME.dialog_border);
+
+        let layout = Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).split(inner);
+
+        match &run.differences {
+            None => {
+                let msg = Paragraph::new(format!(
+                    "Comparing {} and {}...",
+                    run.left.display(),
+                    run.right.display()
+                ))
+                .style(Style::default().fg(THEME.dialog_hint));
+                frame.render_widget(msg, layout[0]);
+            }
+            Some(differences) if differences.is_empty() => {
+                let msg = Paragraph::new("The directories are identical")
+                    .style(Style::default().fg(THEME.dialog_hint));
+                frame.render_widget(msg, layout[0]);
+            }
+            Some(differences) => {
+                let items: Vec<ListItem> = differences
+                    .iter()
+                    .map(|d| {
+                        ListItem::new(format!("{}  {}", d.kind.symbol(), d.rel_path.display()))
+                            .style(Style::default().fg(THEME.file_fg))
+                    })
+                    .collect();
+                let list = List::new(items)
+                    .highlight_style(
+                        Style::default()
+                            .bg(THEME.cursor_active_bg)
+                            .fg(THEME.cursor_active_fg),
+                    )
+                    .highlight_symbol("▶ ");
+                let mut state = ListState::default().with_selected(Some(selected));
+                frame.render_stateful_widget(list, layout[0], &mut state);
+            }
+        }
+
+        let hint = Paragraph::new(
+            "<+/+>: only left/right | </>: newer side | !=: differs | Top-level differences are selected",
+        )
+        .style(Style::default().fg(THEME.dialog_hint));
+        frame.render_widget(hint, layout[1]);
+    }
+
+    fn render_fuzzy_finder(&self, frame: &mut Frame) {
+        let Some(finder) = &self.finder else {
+            return;
+        };
+
+        let area = centered_rect(80, 80, frame.area());
+        let inner = render_dialog_frame(frame, area, "Find", THEME.dialog_border);
+
+        let layout = Layout::vertical([
+            Constraint::Length(1), // query
+            Constraint::Length(1), // counts
+            Constraint::Min(1),    // matches
+            Constraint::Length(1), // hint
+        ])
+        .split(inner);
+
+        let input_para = Paragraph::new(format!("> {}█", finder.query)).style(
+            Style::default()
+                .fg(THEME.dialog_input_fg)
+                .bg(THEME.dialog_input_bg),
+        );
+        frame.render_widget(input_para, layout[0]);
+
+        let mut counts = format!("{}/{}", finder.matches.len(), finder.items.len());
+        if finder.walking {
+            counts.push_str(" (scanning...)");
+        }
+        if finder.respect_gitignore {
+            counts.push_str(" [.gitignore]");
+        }
+        let counts = Paragraph::new(counts).style(Style::default().fg(THEME.dialog_hint));
+        frame.render_widget(counts, layout[1]);
+
+        // Only build items for the visible page; trees can be huge
+        let page = (layout[2].height as usize).max(1);
+        let start = finder.selected / page * page;
+        let items: Vec<ListItem> = finder
+            .matches
+            .iter()
+            .skip(start)
+            .take(page)
+            .map(|&i| {
+                let item = &finder.items[i];
+                if item.is_dir {
+                    ListItem::new(format!("{}/", item.rel_path))
+                        .style(Style::default().fg(THEME.directory_fg))
+                } else {
+                    ListItem::new(item.rel_path.as_str()).style(Style::default().fg(THEME.file_fg))
+                }
+            })
+            .collect();
+
+        let list = List::new(items)
+            .highlight_style(
+                Style::default()
+                    .bg(THEME.cursor_active_bg)
+                    .fg(THEME.cursor_active_fg),
+            )
+            .highlight_symbol("▶ ");
+        let mut state = ListState::default().with_selected(Some(finder.selected - start));
+        frame.render_stateful_widget(list, layout[2], &mut state);
+
+        let hint = Paragraph::new(
+            "Enter: go to | F3/^V: view | ^G: toggle .gitignore | ↑/↓ ^P/^N: select | Esc: close",
+        )
+        .style(Style::default().fg(THEME.dialog_hint));
+        frame.render_widget(hint, layout[3]);
+    }
+
+    fn render_command_line(&self, frame: &mut Frame, input: &str) {
+        // Render at the very bottom of the screen
+        let area = Rect {
+            x: 0,
+            y: frame.area().height.saturating_sub(1),
+            width: frame.area().width,
+            height: 1,
+        };
+
+        frame.render_widget(Clear, area);
+
+        let pane_path = &self.active_pane().path;
+
+        let prompt = format!("{}$ {}█", pane_path.display(), input);
+        let line = Paragraph::new(prompt).style(
+            Style::default()
+                .fg(THEME.dialog_input_fg)
+                .bg(THEME.dialog_input_bg),
+        );
+        frame.render_widget(line, area);
+    }
+
+    fn render_search_bar(&self, frame: &mut Frame, query: &str) {
+        // Render at the very bottom of the screen
+        let area = Rect {
+            x: 0,
+            y: frame.area().height.saturating_sub(1),
+            width: frame.area().width,
+            height: 1,
+        };
+
+        frame.render_widget(Clear, area);
+
+        let prompt = format!("Search: {}█  (Ctrl+S: next, Esc: cancel)", query);
+        let line = Paragraph::new(prompt).style(
+            Style::default()
+                .fg(THEME.dialog_input_fg)
+                .bg(THEME.dialog_input_bg),
+        );
+        frame.render_widget(line, area);
+    }
+
+    fn render_quit_dialog(&self, frame: &mut Frame) {
+        let area = centered_rect(40, 25, frame.area());
+        let inner = render_dialog_frame(frame, area, "Quit", THEME.dialog_warning_border);
+
+        let active_jobs = self.job_manager.active_job_count();
+
+        let layout = Layout::vertical([
+            Constraint::Length(1), // spacer
+            Constraint::Length(1), // job count
+            Constraint::Length(1), // question
+            Constraint::Length(1), // spacer
+            Constraint::Length(1), // buttons
+            Constraint::Min(0),
+        ])
+        .split(inner);
+
+        let msg = format!(
+            "{} job{} still running.",
+            active_jobs,
+            if active_jobs == 1 { " is" } else { "s are" }
+        );
+        let warning = Paragraph::new(msg)
             .style(Style::default().fg(THEME.dialog_warning_text))
             .alignment(ratatui::layout::Alignment::Center);
         frame.render_widget(warning, layout[1]);
@@ -939,7 +2428,21 @@ impl App {
         } else {
             // Show content
             let lines = viewer.visible_lines(visible_height);
-            let content: Vec<Line> = lines.iter().map(|s| Line::raw(s.as_str())).collect();
+            let highlight = viewer
+                .highlight_line
+                .filter(|_| viewer.mode == crate::viewer::ViewMode::Text);
+            let content: Vec<Line> = lines
+                .iter()
+                .enumerate()
+                .map(|(i, s)| {
+                    let line = Line::raw(s.as_str());
+                    if highlight == Some(viewer.scroll_offset + i) {
+                        line.style(Style::default().bg(THEME.selected_bg).fg(THEME.selected_fg))
+                    } else {
+                        line
+                    }
+                })
+                .collect();
             let mut para = Paragraph::new(content)
                 .style(Style::default().fg(THEME.file_fg).bg(THEME.dialog_bg));
 
@@ -986,4 +2489,341 @@ impl App {
         );
         frame.render_widget(help_bar, layout[3]);
     }
+    fn render_diff_viewer(&self, frame: &mut Frame, viewer: &DiffViewer) {
+        let area = frame.area();
+        frame.render_widget(Clear, area);
+
+        let layout = Layout::vertical([
+            Constraint::Length(1), // Title bar
+            Constraint::Min(0),    // Content
+            Constraint::Length(1), // Help bar
+        ])
+        .split(area);
+        let visible_height = layout[1].height as usize;
+
+        // Title bar
+        let name = |path: &Path| {
+            path.file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned()
+        };
+        let layout_label = match viewer.layout {
+            DiffLayout::SideBySide => "side by side",
+            DiffLayout::Unified => "unified",
+        };
+        let summary = match (viewer.hunk_count(), viewer.current_hunk()) {
+            _ if viewer.error.is_some() => String::new(),
+            (0, _) => " - identical".to_owned(),
+            (count, Some(idx)) => format!(" - hunk {}/{}", idx + 1, count),
+            (count, None) => format!(" - {} hunks", count),
+        };
+        let title = format!(
+            " {} ↔ {} - {}{}{} ",
+            name(&viewer.left_path),
+            name(&viewer.right_path),
+            layout_label,
+            if viewer.binary { ", hex" } else { "" },
+            summary
+        );
+        let title_bar = Paragraph::new(title).style(
+            Style::default()
+                .fg(THEME.cursor_active_fg)
+                .bg(THEME.cursor_active_bg),
+        );
+        frame.render_widget(title_bar, layout[0]);
+
+        if let Some(error) = &viewer.error {
+            let error_para = Paragraph::new(format!("Error: {}", error))
+                .style(Style::default().fg(THEME.status_error_fg))
+                .block(Block::default().borders(Borders::ALL));
+            frame.render_widget(error_para, layout[1]);
+        } else {
+            match viewer.layout {
+                DiffLayout::SideBySide => render_side_by_side(frame, layout[1], viewer),
+                DiffLayout::Unified => render_unified(frame, layout[1], viewer),
+            }
+        }
+
+        // Help bar with position info
+        let help_text = format!(
+            " j/k:scroll  PgUp/Dn:page  n/p:next/prev hunk  Tab:layout  q/Esc:close  │  {} ",
+            viewer.position_info(visible_height)
+        );
+        let help_bar = Paragraph::new(help_text).style(
+            Style::default()
+                .fg(THEME.help_desc_fg)
+                .bg(THEME.help_desc_bg),
+        );
+        frame.render_widget(help_bar, layout[2]);
+    }
+}
+
+/// A pane in tree mode: one line per visible directory, indented by depth
+fn render_tree(
+    frame: &mut Frame,
+    area: Rect,
+    tree: &mut DirTree,
+    is_active: bool,
+    border_style: Style,
+) {
+    let block = Block::default()
+        .title(format!(" Tree: {} ", tree.root().display()))
+        .title_style(Style::default().fg(THEME.pane_title))
+        .borders(Borders::ALL)
+        .border_style(border_style);
+
+    let items: Vec<ListItem> = tree
+        .nodes
+        .iter()
+        .map(|node| {
+            let marker = if node.loading {
+                "…"
+            } else if node.has_children == Some(false) {
+                " "
+            } else if node.expanded {
+                "▾"
+            } else {
+                "▸"
+            };
+            let text = format!("{}{} {}", "  ".repeat(node.depth), marker, node.name);
+            ListItem::new(text).style(
+                Style::default()
+                    .fg(THEME.directory_fg)
+                    .add_modifier(Modifier::BOLD),
+            )
+        })
+        .collect();
+
+    let highlight_style = if is_active {
+        Style::default()
+            .bg(THEME.cursor_active_bg)
+            .fg(THEME.cursor_active_fg)
+            .add_modifier(Modifier::BOLD)
+    } else {
+        Style::default()
+            .bg(THEME.cursor_inactive_bg)
+            .fg(THEME.cursor_inactive_fg)
+    };
+
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(highlight_style)
+        .highlight_symbol("▶ ");
+    frame.render_stateful_widget(list, area, &mut tree.list_state);
+}
+
+/// Label/value rows of a properties view, labels aligned
+fn properties_lines(rows: &[(&'static str, String)]) -> Vec<Line<'static>> {
+    rows.iter()
+        .map(|(label, value)| {
+            Line::from(vec![
+                Span::styled(
+                    format!(" {:<11} ", label),
+                    Style::default().fg(THEME.dialog_hint),
+                ),
+                Span::raw(value.clone()),
+            ])
+        })
+        .collect()
+}
+
+fn diff_style(kind: RowKind) -> Style {
+    let fg = match kind {
+        RowKind::Same => THEME.file_fg,
+        RowKind::Changed => THEME.diff_changed,
+        RowKind::Removed => THEME.diff_removed,
+        RowKind::Added => THEME.diff_added,
+    };
+    Style::default().fg(fg)
+}
+
+/// Width of the line number gutter; hex dumps carry their own offsets
+fn diff_gutter_width(viewer: &DiffViewer, max_number: usize) -> usize {
+    if viewer.binary {
+        0
+    } else {
+        max_number.to_string().len() + 1
+    }
+}
+
+fn diff_gutter(number: Option<usize>, width: usize) -> Span<'static> {
+    let text = match number {
+        Some(n) if width > 0 => format!("{:>w$} ", n, w = width - 1),
+        _ => " ".repeat(width),
+    };
+    Span::styled(text, Style::default().fg(THEME.diff_gutter))
+}
+
+fn diff_side_line(line: &Option<DiffLine>, kind: RowKind, gutter: usize) -> Line<'_> {
+    match line {
+        Some(l) => Line::from(vec![
+            diff_gutter(Some(l.number), gutter),
+            Span::styled(l.text.as_str(), diff_style(kind)),
+        ]),
+        None => Line::raw(""),
+    }
+}
+
+fn render_side_by_side(frame: &mut Frame, area: Rect, viewer: &DiffViewer) {
+    let columns = Layout::horizontal([
+        Constraint::Fill(1),
+        Constraint::Length(1),
+        Constraint::Fill(1),
+    ])
+    .split(area);
+
+    let height = area.height as usize;
+    let start = viewer.scroll_offset.min(viewer.side_rows.len());
+    let rows = &viewer.side_rows[start..(start + height).min(viewer.side_rows.len())];
+    let max_number = viewer
+        .side_rows
+        .last()
+        .map(|r| {
+            let number = |l: &Option<DiffLine>| l.as_ref().map_or(0, |l| l.number);
+            number(&r.left).max(number(&r.right))
+        })
+        .unwrap_or(0);
+    let gutter = diff_gutter_width(viewer, max_number);
+
+    let left: Vec<Line> = rows
+        .iter()
+        .map(|r| diff_side_line(&r.left, r.kind, gutter))
+        .collect();
+    let right: Vec<Line> = rows
+        .iter()
+        .map(|r| diff_side_line(&r.right, r.kind, gutter))
+        .collect();
+    let separator: Vec<Line> = rows
+        .iter()
+        .map(|r| {
+            let mark = match r.kind {
+                RowKind::Same => "│",
+                RowKind::Changed => "≠",
+                RowKind::Removed => "<",
+                RowKind::Added => ">",
+            };
+            Line::styled(mark, diff_style(r.kind))
+        })
+        .collect();
+
+    let base = Style::default().fg(THEME.file_fg).bg(THEME.dialog_bg);
+    frame.render_widget(Paragraph::new(left).style(base), columns[0]);
+    frame.render_widget(Paragraph::new(separator).style(base), columns[1]);
+    frame.render_widget(Paragraph::new(right).style(base), columns[2]);
+}
+
+fn render_unified(frame: &mut Frame, area: Rect, viewer: &DiffViewer) {
+    let height = area.height as usize;
+    let start = viewer.scroll_offset.min(viewer.unified_rows.len());
+    let rows = &viewer.unified_rows[start..(start + height).min(viewer.unified_rows.len())];
+    let max_number = viewer
+        .unified_rows
+        .iter()
+        .rev()
+        .find_map(|r| r.left_number.max(r.right_number))
+        .unwrap_or(0);
+    let gutter = diff_gutter_width(viewer, max_number);
+
+    let lines: Vec<Line> = rows
+        .iter()
+        .map(|r| {
+            let sign = match r.kind {
+                RowKind::Removed => "-",
+                RowKind::Added => "+",
+                RowKind::Same | RowKind::Changed => " ",
+            };
+            Line::from(vec![
+                diff_gutter(r.left_number, gutter),
+                diff_gutter(r.right_number, gutter),
+                Span::styled(format!("{} {}", sign, r.text), diff_style(r.kind)),
+            ])
+        })
+        .collect();
+    let para = Paragraph::new(lines).style(Style::default().fg(THEME.file_fg).bg(THEME.dialog_bg));
+    frame.render_widget(para, area);
+}
+
+fn checkbox(checked: bool) -> &'static str {
+    if checked { "[x]" } else { "[ ]" }
+}
+
+/// Render a search dialog: labelled text inputs followed by option lines.
+/// `labels` covers all fields in focus order; `texts` holds the input values
+/// and `options` the already formatted option lines.
+fn render_search_form(
+    frame: &mut Frame,
+    title: &str,
+    labels: &[&str],
+    focus: usize,
+    texts: &[&str],
+    options: &[String],
+) {
+    let area = centered_rect(60, 60, frame.area());
+    let inner = render_dialog_frame(frame, area, title, THEME.dialog_border);
+
+    let field_count = texts.len() + options.len();
+    let layout = Layout::vertical([
+        Constraint::Length(field_count as u16 + 2),
+        Constraint::Length(1),
+        Constraint::Min(0),
+    ])
+    .split(inner);
+    render_form_fields(frame, layout[0], labels, focus, texts, options);
+
+    let hint = Paragraph::new("Tab/↑/↓: field | Space: toggle | Enter: search | Esc: cancel")
+        .style(Style::default().fg(THEME.dialog_hint));
+    frame.render_widget(hint, layout[1]);
+}
+
+/// Render labelled text inputs followed by option lines, one per row after
+/// a blank first row
+fn render_form_fields(
+    frame: &mut Frame,
+    area: Rect,
+    labels: &[&str],
+    focus: usize,
+    texts: &[&str],
+    options: &[String],
+) {
+    let field_count = texts.len() + options.len();
+    let mut constraints = vec![Constraint::Length(1); field_count + 1];
+    constraints.push(Constraint::Min(0));
+    let layout = Layout::vertical(constraints).split(area);
+
+    let focused_style = Style::default()
+        .bg(THEME.cursor_active_bg)
+        .fg(THEME.cursor_active_fg);
+    let style_for = |idx: usize| {
+        if idx == focus {
+            focused_style
+        } else {
+            Style::default()
+        }
+    };
+    let label_width = labels[..texts.len()]
+        .iter()
+        .map(|l| l.len())
+        .max()
+        .unwrap_or(0);
+    let input_style = Style::default()
+        .fg(THEME.dialog_input_fg)
+        .bg(THEME.dialog_input_bg);
+
+    for (idx, text) in texts.iter().enumerate() {
+        let cursor = if idx == focus { "█" } else { "" };
+        let line = Line::from(vec![
+            Span::styled(
+                format!("{:<width$} ", labels[idx], width = label_width),
+                style_for(idx),
+            ),
+            Span::styled(format!("{}{}", text, cursor), input_style),
+        ]);
+        frame.render_widget(Paragraph::new(line), layout[idx + 1]);
+    }
+    for (i, option) in options.iter().enumerate() {
+        let idx = texts.len() + i;
+        let line = Line::from(Span::styled(option.as_str(), style_for(idx)));
+        frame.render_widget(Paragraph::new(line), layout[idx + 1]);
+    }
 }
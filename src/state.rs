@@ -1,13 +1,13 @@
 use std::{
     env,
     io::{BufRead, BufReader, BufWriter, Write},
-    path::{Path, PathBuf},
+    path::PathBuf,
 };
 
 const APP_NAME: &str = "rmc";
 
-/// Get the state file path following XDG Base Directory specification
-pub fn get_state_file_path() -> PathBuf {
+/// Get rmc's state directory following XDG Base Directory specification
+pub fn get_state_dir() -> PathBuf {
     let state_home = env::var("XDG_STATE_HOME")
         .map(PathBuf::from)
         .unwrap_or_else(|_| {
@@ -15,12 +15,41 @@ pub fn get_state_file_path() -> PathBuf {
             PathBuf::from(home).join(".local/state")
         });
 
-    state_home.join(APP_NAME).join("state")
+    state_home.join(APP_NAME)
+}
+
+/// Get the state file path
+pub fn get_state_file_path() -> PathBuf {
+    get_state_dir().join("state")
+}
+
+/// Saved tabs of one pane
+#[derive(Default)]
+pub struct PaneTabsState {
+    pub paths: Vec<PathBuf>,
+    pub active: usize,
+}
+
+impl PaneTabsState {
+    /// Drop the tabs that are gone (None). The active tab stays active; if it
+    /// is gone itself, the next remaining tab takes its place.
+    fn keep_existing(tabs: Vec<Option<PathBuf>>, active: usize) -> Self {
+        let active = tabs[..active.min(tabs.len())].iter().flatten().count();
+        let paths: Vec<PathBuf> = tabs.into_iter().flatten().collect();
+        Self {
+            active: active.min(paths.len().saturating_sub(1)),
+            paths,
+        }
+    }
 }
 
 #[derive(Default)]
 pub struct AppState {
-    pub right_path: Option<PathBuf>,
+    pub left_tabs: PaneTabsState,
+    pub right_tabs: PaneTabsState,
+    /// Directory history of each pane's active tab, oldest first
+    pub left_history: Vec<PathBuf>,
+    pub right_history: Vec<PathBuf>,
 }
 
 impl AppState {
@@ -32,21 +61,50 @@ impl AppState {
 
         let reader = BufReader::new(file);
         let mut state = Self::default();
+        // Older state files only stored the right pane path
+        let mut legacy_right = None;
+        // Saved tabs in file order, None where the directory is gone, so the
+        // active index can be mapped onto the tabs that are kept
+        let mut left_tabs = Vec::new();
+        let mut right_tabs = Vec::new();
 
         for line in reader.lines().map_while(Result::ok) {
             if let Some((key, value)) = line.split_once('=') {
-                let path = PathBuf::from(value);
+                match key {
+                    "left_active_tab" => {
+                        state.left_tabs.active = value.parse().unwrap_or(0);
+                        continue;
+                    }
+                    "right_active_tab" => {
+                        state.right_tabs.active = value.parse().unwrap_or(0);
+                        continue;
+                    }
+                    _ => {}
+                }
+
                 // Only use the path if it still exists
-                if path.is_dir() && key == "right" {
-                    state.right_path = Some(path);
+                let path = Some(PathBuf::from(value)).filter(|p| p.is_dir());
+                match key {
+                    "right" => legacy_right = path.or(legacy_right),
+                    "left_tab" => left_tabs.push(path),
+                    "right_tab" => right_tabs.push(path),
+                    "left_history" => state.left_history.extend(path),
+                    "right_history" => state.right_history.extend(path),
+                    _ => {}
                 }
             }
         }
 
+        state.left_tabs = PaneTabsState::keep_existing(left_tabs, state.left_tabs.active);
+        state.right_tabs = PaneTabsState::keep_existing(right_tabs, state.right_tabs.active);
+        if state.right_tabs.paths.is_empty() {
+            state.right_tabs.paths.extend(legacy_right);
+        }
+
         state
     }
 
-    pub fn save(right_path: &Path) {
+    pub fn save(&self) {
         let path = get_state_file_path();
 
         // Create parent directories if needed
@@ -59,6 +117,39 @@ impl AppState {
         };
 
         let mut writer = BufWriter::new(file);
-        let _ = writeln!(writer, "right={}", right_path.display());
+        for (prefix, tabs) in [("left", &self.left_tabs), ("right", &self.right_tabs)] {
+            for tab_path in &tabs.paths {
+                let _ = writeln!(writer, "{}_tab={}", prefix, tab_path.display());
+            }
+            let _ = writeln!(writer, "{}_active_tab={}", prefix, tabs.active);
+        }
+        for (prefix, history) in [("left", &self.left_history), ("right", &self.right_history)] {
+            for dir in history {
+                let _ = writeln!(writer, "{}_history={}", prefix, dir.display());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn active_tab_follows_its_path_when_earlier_tabs_are_gone() {
+        let tab = |name: &str| Some(PathBuf::from(name));
+
+        let tabs = PaneTabsState::keep_existing(vec![None, tab("b"), tab("c"), tab("d")], 2);
+        assert_eq!(tabs.paths, [PathBuf::from("b"), "c".into(), "d".into()]);
+        assert_eq!(tabs.active, 1);
+
+        // A vanished active tab hands over to the next one that is kept
+        let tabs = PaneTabsState::keep_existing(vec![tab("a"), None, tab("c")], 1);
+        assert_eq!(tabs.active, 1);
+        let tabs = PaneTabsState::keep_existing(vec![tab("a"), None], 1);
+        assert_eq!(tabs.active, 0);
+        let tabs = PaneTabsState::keep_existing(vec![None], 0);
+        assert!(tabs.paths.is_empty());
+        assert_eq!(tabs.active, 0);
     }
 }
@@ -0,0 +1,185 @@
+//! Directory tabs for a pane
+//!
+//! The active tab's `PaneState` lives in `App.left`/`App.right` so the rest of
+//! the app keeps working on a single pane state. A `TabSet` holds the
+//! background tabs and swaps them in and out of that live slot.
+
+use std::mem;
+
+use crate::{pane::PaneState, state::PaneTabsState};
+
+/// Maximum characters of a directory name shown in a tab label
+const TAB_LABEL_MAX_CHARS: usize = 16;
+
+#[derive(Default)]
+pub struct TabSet {
+    /// Inactive tabs in display order (the live tab is not stored here)
+    background: Vec<PaneState>,
+    /// Display index of the live tab
+    active: usize,
+}
+
+impl TabSet {
+    /// Build a tab set around the live tab, with background tabs before and after it
+    pub fn with_background(before: Vec<PaneState>, after: Vec<PaneState>) -> Self {
+        let active = before.len();
+        let mut background = before;
+        background.extend(after);
+        Self { background, active }
+    }
+
+    /// Total number of tabs, including the live one
+    pub fn len(&self) -> usize {
+        self.background.len() + 1
+    }
+
+    pub fn active(&self) -> usize {
+        self.active
+    }
+
+    /// Make tab `idx` the live tab, storing the current live tab in the background
+    pub fn switch_to(&mut self, live: &mut PaneState, idx: usize) {
+        if idx == self.active || idx >= self.len() {
+            return;
+        }
+        let tab = self
+            .background
+            .remove(if idx < self.active { idx } else { idx - 1 });
+        let previous = mem::replace(live, tab);
+        // Put the previous live tab back at its display position
+        let insert_at = if idx < self.active {
+            self.active - 1
+        } else {
+            self.active
+        };
+        self.background.insert(insert_at, previous);
+        self.active = idx;
+        // Background tabs aren't watched, so bring the listing up to date
+        live.load_entries_async();
+    }
+
+    pub fn next(&mut self, live: &mut PaneState) {
+        let idx = (self.active + 1) % self.len();
+        self.switch_to(live, idx);
+    }
+
+    pub fn prev(&mut self, live: &mut PaneState) {
+        let idx = (self.active + self.len() - 1) % self.len();
+        self.switch_to(live, idx);
+    }
+
+    /// Open a new tab right after the live one and make it live
+    pub fn open(&mut self, live: &mut PaneState, tab: PaneState) {
+        let previous = mem::replace(live, tab);
+        self.background.insert(self.active, previous);
+        self.active += 1;
+    }
+
+    /// Close the live tab, replacing it with its right neighbour (or left if it
+    /// was the last). Returns false if it is the only tab.
+    pub fn close(&mut self, live: &mut PaneState) -> bool {
+        if self.background.is_empty() {
+            return false;
+        }
+        let next_idx = self.active.min(self.background.len() - 1);
+        *live = self.background.remove(next_idx);
+        self.active = next_idx;
+        live.load_entries_async();
+        true
+    }
+
+    /// Iterate over all tabs in display order
+    pub fn iter<'a>(&'a self, live: &'a PaneState) -> impl Iterator<Item = &'a PaneState> {
+        let (before, after) = self.background.split_at(self.active);
+        before
+            .iter()
+            .chain(std::iter::once(live))
+            .chain(after.iter())
+    }
+
+    /// Tab paths and active index for the state file
+    pub fn to_state(&self, live: &PaneState) -> PaneTabsState {
+        PaneTabsState {
+            // A tab inside an archive is saved as the archive's directory
+            paths: self
+                .iter(live)
+                .map(|tab| match &tab.archive {
+                    Some(view) => view.file.parent().unwrap_or(&tab.path).to_path_buf(),
+                    None => tab.path.clone(),
+                })
+                .collect(),
+            active: self.active,
+        }
+    }
+
+    /// Short labels for all tabs in display order
+    pub fn labels(&self, live: &PaneState) -> Vec<String> {
+        self.iter(live)
+            .enumerate()
+            .map(|(i, tab)| {
+                let name = tab
+                    .path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| tab.path.display().to_string());
+                let name = if name.chars().count() > TAB_LABEL_MAX_CHARS {
+                    let truncated: String = name.chars().take(TAB_LABEL_MAX_CHARS - 1).collect();
+                    format!("{}…", truncated)
+                } else {
+                    name
+                };
+                format!("{}:{}", i + 1, name)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+
+    use crate::test_util::unique_temp_dir;
+
+    fn tab_names(tabs: &TabSet, live: &PaneState) -> Vec<String> {
+        tabs.iter(live)
+            .map(|t| t.path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn open_switch_and_close_keep_display_order() {
+        let root = unique_temp_dir("tabs");
+        for name in ["a", "b", "c"] {
+            fs::create_dir_all(root.join(name)).unwrap();
+        }
+        let pane = |name: &str| PaneState::new(root.join(name)).unwrap();
+
+        let mut live = pane("a");
+        let mut tabs = TabSet::default();
+        tabs.open(&mut live, pane("b"));
+        tabs.open(&mut live, pane("c"));
+        assert_eq!(tab_names(&tabs, &live), ["a", "b", "c"]);
+        assert_eq!(tabs.active(), 2);
+
+        tabs.switch_to(&mut live, 0);
+        assert_eq!(live.path, root.join("a"));
+        assert_eq!(tab_names(&tabs, &live), ["a", "b", "c"]);
+
+        tabs.prev(&mut live);
+        assert_eq!(live.path, root.join("c"));
+
+        tabs.switch_to(&mut live, 1);
+        assert!(tabs.close(&mut live));
+        assert_eq!(live.path, root.join("c"));
+        assert_eq!(tab_names(&tabs, &live), ["a", "c"]);
+        assert_eq!(tabs.labels(&live), ["1:a", "2:c"]);
+
+        assert!(tabs.close(&mut live));
+        assert!(!tabs.close(&mut live));
+        assert_eq!(live.path, root.join("a"));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
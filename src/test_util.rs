@@ -0,0 +1,15 @@
+//! Fixtures shared by the unit tests
+
+use std::{
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// A fresh path under the system temp directory; the caller creates it
+pub fn unique_temp_dir(name: &str) -> PathBuf {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    std::env::temp_dir().join(format!("rmc-{name}-{}-{nanos}", std::process::id()))
+}
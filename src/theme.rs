@@ -51,6 +51,12 @@ pub struct Theme {
     pub dialog_shadow: Color,
     pub dialog_button_fg: Color,
     pub dialog_button_bg: Color,
+
+    // Diff viewer
+    pub diff_added: Color,
+    pub diff_removed: Color,
+    pub diff_changed: Color,
+    pub diff_gutter: Color,
 }
 
 // Tokyo Night inspired color palette
@@ -105,4 +111,10 @@ pub const THEME: Theme = Theme {
     dialog_shadow: Color::Rgb(15, 15, 20),    // #0f0f14 - very dark
     dialog_button_fg: Color::Rgb(169, 177, 214), // #a9b1d6 - light gray
     dialog_button_bg: Color::Rgb(56, 62, 87), // #383e57 - button bg
+
+    // Diff viewer
+    diff_added: Color::Rgb(158, 206, 106), // #9ece6a - soft green
+    diff_removed: Color::Rgb(247, 118, 142), // #f7768e - soft red
+    diff_changed: Color::Rgb(224, 175, 104), // #e0af68 - muted orange
+    diff_gutter: Color::Rgb(86, 95, 137),  // #565f89 - muted gray
 };
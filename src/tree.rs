@@ -0,0 +1,307 @@
+//! Directory tree mode for a pane
+//!
+//! The tree is kept as a flat list of the visible nodes in display order;
+//! expanding a node inserts its children right after it, collapsing removes
+//! them again. Directories are read in background threads the first time
+//! they are expanded and cached afterwards. Moving the cursor in the tree
+//! drives the other pane's listing.
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+};
+
+use ratatui::widgets::ListState;
+
+pub struct TreeNode {
+    pub path: PathBuf,
+    pub name: String,
+    pub depth: usize,
+    pub expanded: bool,
+    /// Children are being read in the background
+    pub loading: bool,
+    /// None until the directory has been read
+    pub has_children: Option<bool>,
+}
+
+impl TreeNode {
+    fn new(path: PathBuf, depth: usize) -> Self {
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.display().to_string());
+        Self {
+            path,
+            name,
+            depth,
+            expanded: false,
+            loading: false,
+            has_children: None,
+        }
+    }
+}
+
+struct LoadedChildren {
+    dir: PathBuf,
+    children: Vec<PathBuf>,
+}
+
+/// Subdirectories of `dir`, sorted like the listing (case-insensitive)
+fn read_subdirs(dir: &Path, show_hidden: bool) -> Vec<PathBuf> {
+    let Ok(read_dir) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<PathBuf> = read_dir
+        .filter_map(|e| e.ok())
+        .filter(|e| show_hidden || !e.file_name().to_string_lossy().starts_with('.'))
+        .filter(|e| e.path().is_dir())
+        .map(|e| e.path())
+        .collect();
+    dirs.sort_by_key(|p| {
+        p.file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_lowercase()
+    });
+    dirs
+}
+
+pub struct DirTree {
+    /// Visible nodes in display order; the root is always first
+    pub nodes: Vec<TreeNode>,
+    pub list_state: ListState,
+    show_hidden: bool,
+    /// Subdirectories of every directory read so far
+    children: HashMap<PathBuf, Vec<PathBuf>>,
+    /// Node to put the cursor on as soon as it shows up (after going up a level)
+    reveal: Option<PathBuf>,
+    tx: Sender<LoadedChildren>,
+    rx: Receiver<LoadedChildren>,
+}
+
+impl DirTree {
+    /// Tree rooted at `root`, with the root expanded
+    pub fn new(root: PathBuf, show_hidden: bool) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let mut tree = Self {
+            nodes: vec![TreeNode::new(root, 0)],
+            list_state: ListState::default().with_selected(Some(0)),
+            show_hidden,
+            children: HashMap::new(),
+            reveal: None,
+            tx,
+            rx,
+        };
+        tree.expand(0);
+        tree
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.nodes[0].path
+    }
+
+    pub fn selected(&self) -> usize {
+        self.list_state.selected().unwrap_or(0)
+    }
+
+    pub fn selected_path(&self) -> &Path {
+        &self.nodes[self.selected()].path
+    }
+
+    /// Move the cursor by `delta` rows, clamped to the tree
+    pub fn move_by(&mut self, delta: isize) {
+        let last = self.nodes.len() as isize - 1;
+        let idx = (self.selected() as isize + delta).clamp(0, last);
+        self.list_state.select(Some(idx as usize));
+    }
+
+    pub fn move_to_end(&mut self) {
+        self.list_state.select(Some(self.nodes.len() - 1));
+    }
+
+    fn expand(&mut self, idx: usize) {
+        let node = &mut self.nodes[idx];
+        if node.expanded {
+            return;
+        }
+        node.expanded = true;
+        if let Some(children) = self.children.get(&node.path) {
+            let children = children.clone();
+            self.insert_children(idx, children);
+            return;
+        }
+
+        node.loading = true;
+        let dir = node.path.clone();
+        let show_hidden = self.show_hidden;
+        let tx = self.tx.clone();
+        thread::spawn(move || {
+            let children = read_subdirs(&dir, show_hidden);
+            let _ = tx.send(LoadedChildren { dir, children });
+        });
+    }
+
+    fn collapse(&mut self, idx: usize) {
+        let depth = self.nodes[idx].depth;
+        let end = self.nodes[idx + 1..]
+            .iter()
+            .position(|n| n.depth <= depth)
+            .map_or(self.nodes.len(), |p| idx + 1 + p);
+        self.nodes.drain(idx + 1..end);
+        self.nodes[idx].expanded = false;
+
+        let selected = self.selected();
+        if selected > idx && selected < end {
+            self.list_state.select(Some(idx));
+        } else if selected >= end {
+            self.list_state.select(Some(selected - (end - idx - 1)));
+        }
+    }
+
+    fn insert_children(&mut self, idx: usize, children: Vec<PathBuf>) {
+        let depth = self.nodes[idx].depth + 1;
+        let count = children.len();
+        self.nodes[idx].has_children = Some(count > 0);
+        self.nodes.splice(
+            idx + 1..idx + 1,
+            children.into_iter().map(|path| TreeNode::new(path, depth)),
+        );
+        let selected = self.selected();
+        if selected > idx {
+            self.list_state.select(Some(selected + count));
+        }
+    }
+
+    /// Expand the cursor node, or step into its first child if it is
+    /// already expanded
+    pub fn expand_or_enter(&mut self) {
+        let idx = self.selected();
+        if !self.nodes[idx].expanded {
+            self.expand(idx);
+        } else if self.nodes[idx].has_children == Some(true) {
+            self.list_state.select(Some(idx + 1));
+        }
+    }
+
+    /// Collapse the cursor node, or move to its parent. On the root, the
+    /// tree is re-rooted one level up.
+    pub fn collapse_or_leave(&mut self) {
+        let idx = self.selected();
+        if idx > 0 && self.nodes[idx].expanded {
+            self.collapse(idx);
+        } else if idx > 0 {
+            let depth = self.nodes[idx].depth;
+            let parent = self.nodes[..idx]
+                .iter()
+                .rposition(|n| n.depth < depth)
+                .unwrap_or(0);
+            self.list_state.select(Some(parent));
+        } else if let Some(parent) = self.root().parent().map(Path::to_path_buf) {
+            let old_root = self.root().to_path_buf();
+            self.nodes = vec![TreeNode::new(parent, 0)];
+            self.list_state.select(Some(0));
+            self.reveal = Some(old_root);
+            self.expand(0);
+            self.take_reveal();
+        }
+    }
+
+    /// Move the cursor to the node waiting to be revealed, once it is visible
+    fn take_reveal(&mut self) -> bool {
+        let Some(path) = &self.reveal else {
+            return false;
+        };
+        let Some(idx) = self.nodes.iter().position(|n| &n.path == path) else {
+            return false;
+        };
+        self.list_state.select(Some(idx));
+        self.reveal = None;
+        true
+    }
+
+    /// Apply directories read in the background. Returns true if the cursor
+    /// moved to another node as a result.
+    pub fn poll(&mut self) -> bool {
+        let mut moved = false;
+        while let Ok(loaded) = self.rx.try_recv() {
+            let idx = self
+                .nodes
+                .iter()
+                .position(|n| n.path == loaded.dir && n.loading);
+            self.children
+                .insert(loaded.dir.clone(), loaded.children.clone());
+            if let Some(idx) = idx {
+                self.nodes[idx].loading = false;
+                if self.nodes[idx].expanded {
+                    self.insert_children(idx, loaded.children);
+                }
+            }
+            moved |= self.take_reveal();
+        }
+        moved
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{fs, time::Duration};
+
+    use crate::test_util::unique_temp_dir;
+
+    fn wait_for_loads(tree: &mut DirTree) {
+        for _ in 0..200 {
+            tree.poll();
+            if !tree.nodes.iter().any(|n| n.loading) {
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        panic!("tree did not finish loading");
+    }
+
+    fn names(tree: &DirTree) -> Vec<(usize, &str)> {
+        tree.nodes
+            .iter()
+            .map(|n| (n.depth, n.name.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn expands_collapses_and_climbs_above_the_root() {
+        let root = unique_temp_dir("tree");
+        for dir in ["top/b/inner", "top/a", "top/.hidden"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        fs::write(root.join("top/file.txt"), "not a dir").unwrap();
+
+        let mut tree = DirTree::new(root.join("top"), false);
+        wait_for_loads(&mut tree);
+        assert_eq!(names(&tree), [(0, "top"), (1, "a"), (1, "b")]);
+
+        tree.move_to_end();
+        tree.expand_or_enter();
+        wait_for_loads(&mut tree);
+        assert_eq!(tree.nodes.len(), 4);
+        tree.expand_or_enter();
+        assert_eq!(tree.selected_path(), root.join("top/b/inner"));
+
+        // Left on a leaf goes to its parent, then collapses it
+        tree.collapse_or_leave();
+        assert_eq!(tree.selected_path(), root.join("top/b"));
+        tree.collapse_or_leave();
+        assert_eq!(tree.nodes.len(), 3);
+
+        // Left on the root re-roots one level up, keeping the cursor on the old root
+        tree.move_by(-10);
+        tree.collapse_or_leave();
+        wait_for_loads(&mut tree);
+        assert_eq!(tree.root(), root);
+        assert_eq!(tree.selected_path(), root.join("top"));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -1,5 +1,13 @@
 // Utility functions and constants for the file manager
 
+use std::{
+    ffi::{CString, OsString},
+    io,
+    os::unix::ffi::OsStringExt,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
 // ============================================================================
 // Constants
 // ============================================================================
@@ -28,6 +36,21 @@ pub const THROUGHPUT_SAMPLE_INTERVAL_MS: u64 = 200;
 /// Rename progress dialog auto-close delay in seconds
 pub const RENAME_DIALOG_TIMEOUT_SECS: u64 = 4;
 
+/// Quiet period after the last filesystem event before a watched pane reloads
+pub const WATCH_DEBOUNCE_MS: u64 = 250;
+
+/// Maximum delay before reloading a watched pane that keeps changing
+pub const WATCH_MAX_DELAY_MS: u64 = 2000;
+
+/// Width of the proportional bars in the disk usage view
+pub const DU_BAR_WIDTH: usize = 20;
+
+/// Directories kept in the shared size cache before it is cleared
+pub const SIZE_CACHE_MAX_DIRS: usize = 200_000;
+
+/// How often a running directory size is pushed to the listing
+pub const SIZE_PROGRESS_INTERVAL_MS: u64 = 100;
+
 // ============================================================================
 // Byte Formatting
 // ============================================================================
@@ -81,10 +104,243 @@ fn format_bytes_impl(bytes: u64, long_suffix: bool) -> String {
     format!("{:.1}{}", value, suffix)
 }
 
+/// Parse a human-readable size like "512", "10K", "1.5M" or "2G" into bytes
+pub fn parse_size(s: &str) -> Option<u64> {
+    let s = s.trim();
+    let (number, multiplier) = match s.chars().last()?.to_ascii_uppercase() {
+        'K' => (&s[..s.len() - 1], 1u64 << 10),
+        'M' => (&s[..s.len() - 1], 1 << 20),
+        'G' => (&s[..s.len() - 1], 1 << 30),
+        'T' => (&s[..s.len() - 1], 1 << 40),
+        _ => (s, 1),
+    };
+    let value: f64 = number.trim().parse().ok()?;
+    (value >= 0.0).then_some((value * multiplier as f64) as u64)
+}
+
+/// Calendar date and time of day in UTC as
+/// (year, month, day, hour, minute, second)
+pub fn utc_datetime(time: SystemTime) -> (i64, u32, u32, u32, u32, u32) {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    let (days, rem) = (secs.div_euclid(86_400), secs.rem_euclid(86_400) as u32);
+
+    // Days since the epoch to a proleptic Gregorian date (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    (year, month, day, rem / 3600, rem / 60 % 60, rem % 60)
+}
+
+// ============================================================================
+// Paths
+// ============================================================================
+
+/// Expand a leading `~` to the home directory
+pub fn expand_tilde(path: &str) -> PathBuf {
+    let home = std::env::var("HOME").map(PathBuf::from);
+    match (path, home) {
+        ("~", Ok(home)) => home,
+        (p, Ok(home)) if p.starts_with("~/") => home.join(&p[2..]),
+        (p, _) => PathBuf::from(p),
+    }
+}
+
+/// Create a directory under the system temp directory that only this user
+/// can open, named `prefix` plus random characters (see mkdtemp(3))
+pub fn private_temp_dir(prefix: &str) -> io::Result<PathBuf> {
+    let template = std::env::temp_dir().join(format!("{}XXXXXX", prefix));
+    let mut bytes = CString::new(template.into_os_string().into_vec())?.into_bytes_with_nul();
+    // SAFETY: bytes is a NUL-terminated template, which mkdtemp fills in place
+    if unsafe { libc::mkdtemp(bytes.as_mut_ptr().cast()) }.is_null() {
+        return Err(io::Error::last_os_error());
+    }
+    bytes.pop();
+    Ok(PathBuf::from(OsString::from_vec(bytes)))
+}
+
+// ============================================================================
+// Pattern Matching
+// ============================================================================
+
+/// Match a file name against a shell-style glob pattern.
+/// Supports `*`, `?` and bracket classes like `[abc]`, `[a-z]` and `[!0-9]`.
+pub fn glob_match(pattern: &str, name: &str, case_sensitive: bool) -> bool {
+    let (pattern, name) = if case_sensitive {
+        (pattern.to_owned(), name.to_owned())
+    } else {
+        (pattern.to_lowercase(), name.to_lowercase())
+    };
+    let pat: Vec<char> = pattern.chars().collect();
+    let txt: Vec<char> = name.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    // Position of the last '*' in the pattern and the text index it matched up to
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < txt.len() {
+        let step = match pat.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+                continue;
+            }
+            Some('?') => Some(1),
+            Some('[') => match_bracket(&pat[p..], txt[t]),
+            Some(&c) if c == txt[t] => Some(1),
+            _ => None,
+        };
+
+        match step {
+            Some(len) => {
+                p += len;
+                t += 1;
+            }
+            None => match backtrack {
+                // Let the last '*' swallow one more character and retry
+                Some((star_p, star_t)) => {
+                    backtrack = Some((star_p, star_t + 1));
+                    p = star_p + 1;
+                    t = star_t + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pat[p..].iter().all(|&c| c == '*')
+}
+
+/// Match a single character against a bracket class at the start of `pat`.
+/// Returns the length of the class in the pattern if the character matched.
+/// An unterminated '[' is treated as a literal.
+fn match_bracket(pat: &[char], c: char) -> Option<usize> {
+    let mut i = 1;
+    let negate = matches!(pat.get(i), Some('!') | Some('^'));
+    if negate {
+        i += 1;
+    }
+
+    let mut matched = false;
+    let mut first = true;
+    while i < pat.len() && (pat[i] != ']' || first) {
+        first = false;
+        if pat.get(i + 1) == Some(&'-') && pat.get(i + 2).is_some_and(|&e| e != ']') {
+            if pat[i] <= c && c <= pat[i + 2] {
+                matched = true;
+            }
+            i += 3;
+        } else {
+            if pat[i] == c {
+                matched = true;
+            }
+            i += 1;
+        }
+    }
+
+    if i >= pat.len() {
+        // No closing bracket: match '[' literally
+        return (c == '[').then_some(1);
+    }
+
+    (matched != negate).then_some(i + 1)
+}
+
+/// Case-insensitive fuzzy match: every non-space character of `query` must
+/// appear in `text` in order, though not necessarily adjacent.
+pub fn fuzzy_match(query: &str, text: &str) -> bool {
+    let mut text_chars = text.chars().flat_map(char::to_lowercase);
+    query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .all(|q| text_chars.any(|t| t == q))
+}
+
+/// Score a fuzzy match of `query` against a path-like `text`, or None if it
+/// doesn't match. Higher is better: matches at word boundaries, consecutive
+/// matches and matches in the final path component score higher, and shorter
+/// texts win ties.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i64> {
+    let query: Vec<char> = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+    let text: Vec<char> = text.chars().collect();
+    let length_penalty = text.len() as i64;
+    let Some(&first) = query.first() else {
+        return Some(-length_penalty);
+    };
+
+    let lower = |c: char| c.to_lowercase().next().unwrap_or(c);
+    let name_start = text.iter().rposition(|&c| c == '/').map_or(0, |i| i + 1);
+
+    // Greedy match from every occurrence of the first query character, keeping
+    // the best, so "main" prefers "src/main.rs" over the "main" in "domain/"
+    let mut best = None;
+    for start in (0..text.len()).filter(|&i| lower(text[i]) == first) {
+        let mut score = 0i64;
+        let mut qi = 0;
+        let mut prev = None;
+        for i in start..text.len() {
+            if qi == query.len() {
+                break;
+            }
+            if lower(text[i]) != query[qi] {
+                continue;
+            }
+            let boundary = i == 0
+                || matches!(text[i - 1], '/' | '_' | '-' | '.' | ' ')
+                || (text[i - 1].is_lowercase() && text[i].is_uppercase());
+            score += 1;
+            if boundary {
+                score += 8;
+            }
+            if prev.is_some_and(|p| p + 1 == i) {
+                score += 5;
+            }
+            if i >= name_start {
+                score += 2;
+            }
+            prev = Some(i);
+            qi += 1;
+        }
+        if qi == query.len() && best.is_none_or(|b| score > b) {
+            best = Some(score);
+        }
+    }
+    best.map(|score| score * 16 - length_penalty)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn private_temp_dirs_are_fresh_and_owner_only() {
+        let first = private_temp_dir("rmc-util-").unwrap();
+        let second = private_temp_dir("rmc-util-").unwrap();
+        assert_ne!(first, second);
+        let name = first.file_name().unwrap().to_string_lossy().into_owned();
+        assert!(name.starts_with("rmc-util-") && name.len() == "rmc-util-".len() + 6);
+        let mode = std::fs::metadata(&first).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+        let _ = std::fs::remove_dir(first);
+        let _ = std::fs::remove_dir(second);
+    }
+
     #[test]
     fn test_format_bytes() {
         assert_eq!(format_bytes(0), "0B");
@@ -103,4 +359,62 @@ mod tests {
         assert_eq!(format_size(1024 * 1024), "1.0M");
         assert_eq!(format_size(1024 * 1024 * 1024), "1.0G");
     }
+
+    #[test]
+    fn test_utc_datetime() {
+        assert_eq!(utc_datetime(UNIX_EPOCH), (1970, 1, 1, 0, 0, 0));
+        let time = UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000);
+        assert_eq!(utc_datetime(time), (2023, 11, 14, 22, 13, 20));
+        // Leap day
+        let time = UNIX_EPOCH + std::time::Duration::from_secs(951_782_400);
+        assert_eq!(utc_datetime(time), (2000, 2, 29, 0, 0, 0));
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("512"), Some(512));
+        assert_eq!(parse_size("10K"), Some(10 * 1024));
+        assert_eq!(parse_size("1.5m"), Some(1024 * 1024 * 3 / 2));
+        assert_eq!(parse_size(""), None);
+        assert_eq!(parse_size("abc"), None);
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*.rs", "main.rs", true));
+        assert!(!glob_match("*.rs", "main.rs.bak", true));
+        assert!(glob_match("*", "", true));
+        assert!(glob_match("a?c", "abc", true));
+        assert!(!glob_match("a?c", "ac", true));
+        assert!(glob_match("*.tar.*", "foo.tar.gz", true));
+        assert!(glob_match("[a-c]*", "beta", true));
+        assert!(!glob_match("[!a-c]*", "beta", true));
+        assert!(glob_match("file[0-9].txt", "file7.txt", true));
+        assert!(glob_match("[", "[", true));
+    }
+
+    #[test]
+    fn test_glob_match_case() {
+        assert!(!glob_match("*.JPG", "photo.jpg", true));
+        assert!(glob_match("*.JPG", "photo.jpg", false));
+    }
+
+    #[test]
+    fn test_fuzzy_match() {
+        assert!(fuzzy_match("", "anything"));
+        assert!(fuzzy_match("prj src", "/home/user/Projects/rmc/src"));
+        assert!(fuzzy_match("RMC", "/home/user/projects/rmc"));
+        assert!(!fuzzy_match("src prj", "/home/user/projects/rmc"));
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(fuzzy_score("xyz", "src/main.rs"), None);
+        let score = |q, t| fuzzy_score(q, t).unwrap();
+        // Whole words in the file name beat scattered matches
+        assert!(score("main", "src/main.rs") > score("main", "src/domain/inner.rs"));
+        assert!(score("fb", "src/foo_bar.rs") > score("fb", "src/fabric.rs"));
+        // Ties go to the shorter path
+        assert!(score("lib", "lib.rs") > score("lib", "nested/deeper/lib.rs"));
+    }
 }
@@ -1,12 +1,22 @@
 use std::{
+    io::Read,
     path::{Path, PathBuf},
     process::Command,
 };
 
+use crate::{
+    archive::{ArchiveEntry, ArchiveFormat, ArchiveListing, EntryKind},
+    attributes::mode_string,
+};
+
 /// Maximum file size to read (50 MB)
-const MAX_FILE_SIZE: usize = 50 * 1024 * 1024;
+pub const MAX_FILE_SIZE: usize = 50 * 1024 * 1024;
 /// Maximum lines to keep from tool output
 const MAX_OUTPUT_LINES: usize = 50_000;
+/// Lines of context kept above a line jumped to with `goto_line`
+const GOTO_CONTEXT_LINES: usize = 5;
+/// Bytes shown per hex dump line
+pub const HEX_BYTES_PER_LINE: usize = 16;
 
 /// Different view modes for the file viewer
 #[derive(Clone, Copy, PartialEq, Eq, Default, Hash)]
@@ -120,6 +130,8 @@ pub struct FileViewer {
     pub truncated: bool,
     /// Original file size (before truncation)
     pub original_size: u64,
+    /// Line (index into `content`) to highlight in Text mode, e.g. a grep match
+    pub highlight_line: Option<usize>,
     /// Cached tool outputs to avoid re-running
     tool_cache: std::collections::HashMap<ViewMode, Vec<String>>,
 }
@@ -127,6 +139,11 @@ pub struct FileViewer {
 impl FileViewer {
     /// Create a new file viewer for the given path
     pub fn new(path: PathBuf) -> Self {
+        Self::with_limit(path, MAX_FILE_SIZE)
+    }
+
+    /// Create a viewer that reads at most `limit` bytes of the file
+    pub fn with_limit(path: PathBuf, limit: usize) -> Self {
         let mut viewer = Self {
             path,
             file_type: FileType::Unknown,
@@ -137,14 +154,15 @@ impl FileViewer {
             error: None,
             truncated: false,
             original_size: 0,
+            highlight_line: None,
             tool_cache: std::collections::HashMap::new(),
         };
-        viewer.load_file();
+        viewer.load_file(limit);
         viewer
     }
 
-    /// Load the file and detect its type
-    fn load_file(&mut self) {
+    /// Load up to `limit` bytes of the file and detect its type
+    fn load_file(&mut self, limit: usize) {
         // Get file size first
         let metadata = match std::fs::metadata(&self.path) {
             Ok(m) => m,
@@ -157,18 +175,15 @@ impl FileViewer {
         self.original_size = metadata.len();
 
         // Check if file is too large
-        if metadata.len() > MAX_FILE_SIZE as u64 {
+        if metadata.len() > limit as u64 {
             self.truncated = true;
         }
 
-        match std::fs::read(&self.path) {
-            Ok(bytes) => {
-                let bytes = if bytes.len() > MAX_FILE_SIZE {
-                    bytes[..MAX_FILE_SIZE].to_vec()
-                } else {
-                    bytes
-                };
-
+        let mut bytes = Vec::new();
+        let read = std::fs::File::open(&self.path)
+            .and_then(|file| file.take(limit as u64).read_to_end(&mut bytes));
+        match read {
+            Ok(_) => {
                 self.file_type = detect_file_type(&self.path, &bytes);
                 self.raw_bytes = bytes;
 
@@ -240,44 +255,12 @@ impl FileViewer {
 
     /// Load file as hex dump
     fn load_hex(&self) -> Result<Vec<String>, String> {
-        let mut lines = Vec::new();
-
-        for (i, chunk) in self.raw_bytes.chunks(16).enumerate() {
-            let offset = i * 16;
-
-            // Build hex part
-            let mut hex_part = String::with_capacity(48);
-            for (j, byte) in chunk.iter().enumerate() {
-                if j == 8 {
-                    hex_part.push(' ');
-                }
-                hex_part.push_str(&format!("{:02x} ", byte));
-            }
-            // Pad if less than 16 bytes
-            let padding = 16 - chunk.len();
-            for j in 0..padding {
-                if chunk.len() + j == 8 {
-                    hex_part.push(' ');
-                }
-                hex_part.push_str("   ");
-            }
-
-            // Build ASCII part
-            let ascii_part: String = chunk
-                .iter()
-                .map(|&b| {
-                    if b.is_ascii_graphic() || b == b' ' {
-                        b as char
-                    } else {
-                        '.'
-                    }
-                })
-                .collect();
-
-            lines.push(format!("{:08x}  {} |{}|", offset, hex_part, ascii_part));
-        }
-
-        Ok(lines)
+        Ok(self
+            .raw_bytes
+            .chunks(HEX_BYTES_PER_LINE)
+            .enumerate()
+            .map(|(i, chunk)| hex_dump_line(i * HEX_BYTES_PER_LINE, chunk))
+            .collect())
     }
 
     /// Load and pretty-print JSON
@@ -310,6 +293,16 @@ impl FileViewer {
 
     /// Load archive contents
     fn load_archive(&self) -> Result<Vec<String>, String> {
+        // Formats with a native reader don't need external tools
+        if ArchiveFormat::detect(&self.path).is_some() {
+            let listing = ArchiveListing::read(&self.path)?;
+            return Ok(listing
+                .entries()
+                .take(MAX_OUTPUT_LINES)
+                .map(archive_line)
+                .collect());
+        }
+
         let path_str = self.path.to_string_lossy();
 
         // Detect archive type and use appropriate tool
@@ -326,6 +319,8 @@ impl FileViewer {
             self.run_tool("gzip", &["-l"])
         } else if path_str.ends_with(".xz") && !path_str.ends_with(".tar.xz") {
             self.run_tool("xz", &["-l"])
+        } else if path_str.ends_with(".zst") {
+            self.run_tool("zstd", &["-l"])
         } else if path_str.ends_with(".7z") {
             self.run_tool("7z", &["l"])
         } else if path_str.ends_with(".rar") {
@@ -339,7 +334,6 @@ impl FileViewer {
     /// Uses spawn + try_wait with a timeout to avoid blocking forever
     /// when network filesystems cause system-wide slowdowns.
     fn run_tool(&self, tool: &str, args: &[&str]) -> Result<Vec<String>, String> {
-        use std::io::Read;
         use std::os::unix::process::CommandExt;
         use std::process::Stdio;
         use std::time::{Duration, Instant};
@@ -432,25 +426,13 @@ impl FileViewer {
         }
     }
 
-    /// Scroll up by n lines
-    pub fn scroll_up(&mut self, n: usize) {
-        self.scroll_offset = self.scroll_offset.saturating_sub(n);
-    }
-
-    /// Scroll down by n lines
-    pub fn scroll_down(&mut self, n: usize, visible_height: usize) {
-        let max_offset = self.content.len().saturating_sub(visible_height);
-        self.scroll_offset = (self.scroll_offset + n).min(max_offset);
-    }
-
-    /// Jump to top
-    pub fn scroll_to_top(&mut self) {
-        self.scroll_offset = 0;
-    }
-
-    /// Jump to bottom
-    pub fn scroll_to_bottom(&mut self, visible_height: usize) {
-        self.scroll_offset = self.content.len().saturating_sub(visible_height);
+    /// Show the file as text scrolled to a 1-based line number, highlighting it
+    pub fn goto_line(&mut self, line: usize) {
+        self.set_mode(ViewMode::Text);
+        let idx = line.saturating_sub(1);
+        self.highlight_line = Some(idx);
+        // Keep a few lines of context above the target
+        self.scroll_offset = idx.saturating_sub(GOTO_CONTEXT_LINES);
     }
 
     /// Get visible lines for rendering
@@ -473,18 +455,62 @@ impl FileViewer {
     pub fn file_size(&self) -> usize {
         self.raw_bytes.len()
     }
+}
+
+impl Scrollable for FileViewer {
+    fn line_count(&self) -> usize {
+        self.content.len()
+    }
+
+    fn scroll_offset(&self) -> usize {
+        self.scroll_offset
+    }
+
+    fn set_scroll_offset(&mut self, offset: usize) {
+        self.scroll_offset = offset;
+    }
+}
+
+/// Line-based vertical scrolling shared by the file and diff viewers
+pub trait Scrollable {
+    /// Total number of scrollable lines
+    fn line_count(&self) -> usize;
+    fn scroll_offset(&self) -> usize;
+    fn set_scroll_offset(&mut self, offset: usize);
+
+    /// Scroll up by n lines
+    fn scroll_up(&mut self, n: usize) {
+        self.set_scroll_offset(self.scroll_offset().saturating_sub(n));
+    }
+
+    /// Scroll down by n lines
+    fn scroll_down(&mut self, n: usize, visible_height: usize) {
+        let max_offset = self.line_count().saturating_sub(visible_height);
+        self.set_scroll_offset((self.scroll_offset() + n).min(max_offset));
+    }
+
+    /// Jump to top
+    fn scroll_to_top(&mut self) {
+        self.set_scroll_offset(0);
+    }
+
+    /// Jump to bottom
+    fn scroll_to_bottom(&mut self, visible_height: usize) {
+        self.set_scroll_offset(self.line_count().saturating_sub(visible_height));
+    }
 
     /// Get current position info for status bar
-    pub fn position_info(&self, visible_height: usize) -> String {
-        let total = self.content.len();
+    fn position_info(&self, visible_height: usize) -> String {
+        let total = self.line_count();
         if total == 0 {
             return "Empty".to_owned();
         }
 
-        let top = self.scroll_offset + 1;
-        let bottom = (self.scroll_offset + visible_height).min(total);
+        let offset = self.scroll_offset();
+        let top = offset + 1;
+        let bottom = (offset + visible_height).min(total);
         let percent = if total > visible_height {
-            (self.scroll_offset * 100) / (total - visible_height).max(1)
+            (offset * 100) / (total - visible_height).max(1)
         } else {
             100
         };
@@ -493,6 +519,57 @@ impl FileViewer {
     }
 }
 
+/// Format one hex dump line: offset, hex bytes in two groups of 8, and ASCII
+pub fn hex_dump_line(offset: usize, chunk: &[u8]) -> String {
+    // Build hex part
+    let mut hex_part = String::with_capacity(50);
+    for (j, byte) in chunk.iter().enumerate() {
+        if j == 8 {
+            hex_part.push(' ');
+        }
+        hex_part.push_str(&format!("{:02x} ", byte));
+    }
+    // Pad if less than 16 bytes
+    let padding = HEX_BYTES_PER_LINE - chunk.len();
+    for j in 0..padding {
+        if chunk.len() + j == 8 {
+            hex_part.push(' ');
+        }
+        hex_part.push_str("   ");
+    }
+
+    // Build ASCII part
+    let ascii_part: String = chunk
+        .iter()
+        .map(|&b| {
+            if b.is_ascii_graphic() || b == b' ' {
+                b as char
+            } else {
+                '.'
+            }
+        })
+        .collect();
+
+    format!("{:08x}  {} |{}|", offset, hex_part, ascii_part)
+}
+
+/// One line of a natively read archive listing, like `tar -tv` prints
+fn archive_line(entry: &ArchiveEntry) -> String {
+    let (kind, suffix) = match &entry.kind {
+        EntryKind::File => ('-', String::new()),
+        EntryKind::Dir => ('d', "/".to_owned()),
+        EntryKind::Symlink(target) => ('l', format!(" -> {}", target)),
+        EntryKind::Hardlink(target) => ('h', format!(" link to {}", target)),
+    };
+    let mode = entry
+        .mode
+        .map_or_else(|| "---------".to_owned(), mode_string);
+    format!(
+        "{}{} {:>12}  {}{}",
+        kind, mode, entry.size, entry.path, suffix
+    )
+}
+
 /// Detect file type from path extension and content
 fn detect_file_type(path: &Path, bytes: &[u8]) -> FileType {
     // Check for ELF magic
@@ -509,7 +586,8 @@ fn detect_file_type(path: &Path, bytes: &[u8]) -> FileType {
 
     match ext.as_str() {
         // Archives
-        "tar" | "gz" | "tgz" | "bz2" | "xz" | "zip" | "jar" | "7z" | "rar" => FileType::Archive,
+        "tar" | "gz" | "tgz" | "bz2" | "xz" | "txz" | "zst" | "tzst" | "zip" | "jar" | "7z"
+        | "rar" => FileType::Archive,
         // Images
         "jpg" | "jpeg" | "png" | "gif" | "bmp" | "webp" | "tiff" | "ico" | "svg" => FileType::Image,
         // JSON
@@ -536,7 +614,7 @@ fn detect_file_type(path: &Path, bytes: &[u8]) -> FileType {
 }
 
 /// Check if content is likely text (no null bytes, mostly printable)
-fn is_likely_text(bytes: &[u8]) -> bool {
+pub fn is_likely_text(bytes: &[u8]) -> bool {
     if bytes.is_empty() {
         return true;
     }
@@ -0,0 +1,163 @@
+//! Filesystem watching for auto-refreshing panes
+//!
+//! Wraps a non-blocking inotify instance that watches the directories shown
+//! in the panes and reports which of them changed, with debouncing so a burst
+//! of events (e.g. a build writing many files) results in a single reload.
+
+use std::{
+    collections::HashMap,
+    io::ErrorKind,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use inotify::{EventMask, Inotify, WatchDescriptor, WatchMask};
+
+use crate::util::{WATCH_DEBOUNCE_MS, WATCH_MAX_DELAY_MS};
+
+/// Events that change what a directory listing shows
+fn watch_mask() -> WatchMask {
+    WatchMask::CREATE
+        | WatchMask::DELETE
+        | WatchMask::MOVED_FROM
+        | WatchMask::MOVED_TO
+        | WatchMask::CLOSE_WRITE
+        | WatchMask::ATTRIB
+        | WatchMask::DELETE_SELF
+        | WatchMask::MOVE_SELF
+        | WatchMask::ONLYDIR
+}
+
+/// A directory with events that haven't been reported yet
+struct PendingChange {
+    /// When the first unreported event arrived
+    first: Instant,
+    /// When the most recent event arrived
+    last: Instant,
+}
+
+pub struct DirWatcher {
+    inotify: Inotify,
+    /// Watched directories by their watch descriptor
+    watches: HashMap<WatchDescriptor, PathBuf>,
+    pending: HashMap<PathBuf, PendingChange>,
+    buffer: Vec<u8>,
+}
+
+impl DirWatcher {
+    pub fn new() -> std::io::Result<Self> {
+        Ok(Self {
+            inotify: Inotify::init()?,
+            watches: HashMap::new(),
+            pending: HashMap::new(),
+            buffer: vec![0u8; 4096],
+        })
+    }
+
+    /// Make the watched set equal to `paths`, adding and removing watches as needed.
+    /// Directories that can't be watched (e.g. permission denied) are silently skipped.
+    pub fn sync(&mut self, paths: &[&PathBuf]) {
+        let stale: Vec<WatchDescriptor> = self
+            .watches
+            .iter()
+            .filter(|(_, p)| !paths.contains(p))
+            .map(|(wd, _)| wd.clone())
+            .collect();
+        for wd in stale {
+            if let Some(path) = self.watches.remove(&wd) {
+                self.pending.remove(&path);
+            }
+            let _ = self.inotify.watches().remove(wd);
+        }
+
+        for path in paths {
+            if self.watches.values().any(|p| p == *path) {
+                continue;
+            }
+            if let Ok(wd) = self.inotify.watches().add(path, watch_mask()) {
+                self.watches.insert(wd, (*path).clone());
+            }
+        }
+    }
+
+    /// Drain pending inotify events and return the directories whose changes
+    /// have settled (no new events for the debounce interval), or that have been
+    /// changing continuously for longer than the maximum delay.
+    pub fn poll_changes(&mut self) -> Vec<PathBuf> {
+        let now = Instant::now();
+
+        loop {
+            match self.inotify.read_events(&mut self.buffer) {
+                Ok(events) => {
+                    for event in events {
+                        let Some(path) = self.watches.get(&event.wd).cloned() else {
+                            continue;
+                        };
+                        // The kernel dropped the watch (directory deleted or unmounted).
+                        // Forget it so `sync` can re-add the path if it comes back.
+                        if event.mask.contains(EventMask::IGNORED) {
+                            self.watches.remove(&event.wd);
+                        }
+                        self.pending
+                            .entry(path)
+                            .and_modify(|p| p.last = now)
+                            .or_insert(PendingChange {
+                                first: now,
+                                last: now,
+                            });
+                    }
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(_) => break,
+            }
+        }
+
+        let debounce = Duration::from_millis(WATCH_DEBOUNCE_MS);
+        let max_delay = Duration::from_millis(WATCH_MAX_DELAY_MS);
+
+        let ready: Vec<PathBuf> = self
+            .pending
+            .iter()
+            .filter(|(_, p)| {
+                now.duration_since(p.last) >= debounce || now.duration_since(p.first) >= max_delay
+            })
+            .map(|(path, _)| path.clone())
+            .collect();
+
+        for path in &ready {
+            self.pending.remove(path);
+        }
+
+        ready
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+
+    use crate::test_util::unique_temp_dir;
+
+    #[test]
+    fn reports_changed_directory_after_debounce() {
+        let dir = unique_temp_dir("watch");
+        fs::create_dir_all(&dir).unwrap();
+
+        let mut watcher = DirWatcher::new().unwrap();
+        watcher.sync(&[&dir]);
+
+        fs::write(dir.join("new.txt"), b"hello").unwrap();
+
+        // Events are held back until the debounce interval has passed
+        assert!(watcher.poll_changes().is_empty());
+        std::thread::sleep(Duration::from_millis(WATCH_DEBOUNCE_MS + 50));
+        assert_eq!(watcher.poll_changes(), vec![dir.clone()]);
+
+        // Nothing more to report once the change has been delivered
+        assert!(watcher.poll_changes().is_empty());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}